@@ -19,3 +19,36 @@ fn from_i16_to<T: rodio::Sample + FromSample<i16>>(bencher: Bencher) {
                 .for_each(divan::black_box_drop)
         })
 }
+
+#[divan::bench]
+fn mixer_matched_format(bencher: Bencher) {
+    bencher
+        .with_inputs(|| TestSource::music_wav().to_f32s())
+        .bench_values(|source| {
+            let (controller, mixer) = rodio::dynamic_mixer::mixer::<f32>(2, 44100);
+            controller.add(source);
+            mixer.for_each(divan::black_box_drop)
+        })
+}
+
+#[divan::bench]
+fn mixer_channels_conv(bencher: Bencher) {
+    bencher
+        .with_inputs(|| TestSource::music_wav().to_f32s())
+        .bench_values(|source| {
+            let (controller, mixer) = rodio::dynamic_mixer::mixer::<f32>(1, 44100);
+            controller.add(source);
+            mixer.for_each(divan::black_box_drop)
+        })
+}
+
+#[divan::bench]
+fn mixer_rate_conv(bencher: Bencher) {
+    bencher
+        .with_inputs(|| TestSource::music_wav().to_f32s())
+        .bench_values(|source| {
+            let (controller, mixer) = rodio::dynamic_mixer::mixer::<f32>(2, 48000);
+            controller.add(source);
+            mixer.for_each(divan::black_box_drop)
+        })
+}
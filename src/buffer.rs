@@ -10,9 +10,12 @@
 //! ```
 //!
 
+use std::fmt;
 use std::time::Duration;
 
-use crate::source::SeekError;
+use cpal::FromSample;
+
+use crate::source::{SeekError, UniformSourceIterator};
 use crate::{Sample, Source};
 
 /// A buffer of samples treated as a source.
@@ -31,10 +34,14 @@ where
 {
     /// Builds a new `SamplesBuffer`.
     ///
+    /// The samples can be of any [`Sample`] type, for example `i16` or `f32`, and are expected
+    /// to be interleaved with one sample per channel in every frame.
+    ///
     /// # Panic
     ///
     /// - Panics if the number of channels is zero.
     /// - Panics if the samples rate is zero.
+    /// - Panics if the number of samples is not a multiple of the number of channels.
     /// - Panics if the length of the buffer is larger than approximately 16 billion elements.
     ///   This is because the calculation of the duration would overflow.
     ///
@@ -42,10 +49,19 @@ where
     where
         D: Into<Vec<S>>,
     {
-        assert!(channels != 0);
-        assert!(sample_rate != 0);
+        assert!(channels != 0, "SamplesBuffer requires at least one channel");
+        assert!(
+            sample_rate != 0,
+            "SamplesBuffer requires a non-zero sample rate"
+        );
 
         let data = data.into();
+        assert!(
+            data.len() % channels as usize == 0,
+            "SamplesBuffer got {} samples, which is not a multiple of {} channels",
+            data.len(),
+            channels
+        );
         let duration_ns = 1_000_000_000u64.checked_mul(data.len() as u64).unwrap()
             / sample_rate as u64
             / channels as u64;
@@ -62,8 +78,74 @@ where
             duration,
         }
     }
+
+    /// Drains a source into a new `SamplesBuffer`, keeping its channels count and sample rate.
+    ///
+    /// The samples stay interleaved like in the source. If the source changes its channels
+    /// count or sample rate midway through, the rest of it is converted to match the start.
+    ///
+    /// Collecting stops when the source ends or after `max_duration`, whichever comes first. A
+    /// source that reports no [`total_duration`](Source::total_duration) may never end, so it
+    /// is rejected with [`UnboundedSourceError`] unless `max_duration` is given.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rodio::buffer::SamplesBuffer;
+    /// use rodio::source::SineWave;
+    ///
+    /// let source = SineWave::new(440.0);
+    /// let buffer: SamplesBuffer<f32> =
+    ///     SamplesBuffer::from_source(source, Some(Duration::from_millis(10))).unwrap();
+    /// ```
+    pub fn from_source<I>(
+        source: I,
+        max_duration: Option<Duration>,
+    ) -> Result<SamplesBuffer<S>, UnboundedSourceError>
+    where
+        I: Source,
+        I::Item: Sample,
+        S: FromSample<I::Item>,
+    {
+        match max_duration {
+            Some(max_duration) => Ok(Self::collect(source.take_duration(max_duration))),
+            None if source.total_duration().is_some() => Ok(Self::collect(source)),
+            None => Err(UnboundedSourceError),
+        }
+    }
+
+    fn collect<I>(source: I) -> SamplesBuffer<S>
+    where
+        I: Source,
+        I::Item: Sample,
+        S: FromSample<I::Item>,
+    {
+        let channels = source.channels();
+        let sample_rate = source.sample_rate();
+        let mut data: Vec<S> = UniformSourceIterator::new(source, channels, sample_rate).collect();
+        // A source cut off by `max_duration` might end in the middle of a frame.
+        data.truncate(data.len() - data.len() % channels as usize);
+        SamplesBuffer::new(channels, sample_rate, data)
+    }
+}
+
+/// Returned by [`SamplesBuffer::from_source`] when the source might be infinite and no maximum
+/// duration was given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnboundedSourceError;
+
+impl fmt::Display for UnboundedSourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The source may be infinite, a maximum duration is required"
+        )
+    }
 }
 
+impl std::error::Error for UnboundedSourceError {}
+
 impl<S> Source for SamplesBuffer<S>
 where
     S: Sample,
@@ -88,6 +170,23 @@ where
         Some(self.duration)
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        Some(self.data.len() - self.pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        let frames = (self.pos / self.channels as usize) as u64;
+        let nanos = frames * 1_000_000_000 / self.sample_rate as u64;
+        Some(Duration::from_nanos(nanos))
+    }
+
     // this is fast because all the samples are in memory already
     // and due to the constant sample_rate we can jump to the right
     // sample directly
@@ -95,17 +194,22 @@ where
     /// This jumps in memory till the sample for `pos`.
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
-        let curr_channel = self.pos % self.channels() as usize;
-        let new_pos = pos.as_secs_f32() * self.sample_rate() as f32 * self.channels() as f32;
-        // saturate pos at the end of the source
-        let new_pos = new_pos as usize;
-        let new_pos = new_pos.min(self.data.len());
+        let channels = self.channels() as usize;
+        let new_pos = pos.as_secs_f64() * self.sample_rate() as f64 * channels as f64;
+        // go to the start of the frame
+        let new_pos = new_pos as usize / channels * channels;
 
-        // make sure the next sample is for the right channel
-        let new_pos = new_pos.next_multiple_of(self.channels() as usize);
-        let new_pos = new_pos - curr_channel;
+        // saturate pos at the end of the source
+        self.pos = new_pos.min(self.data.len());
+        Ok(())
+    }
 
-        self.pos = new_pos;
+    /// This jumps in memory to the frame of sample `n`.
+    #[inline]
+    fn try_seek_samples(&mut self, n: u64) -> Result<(), SeekError> {
+        let channels = self.channels as usize;
+        let n = usize::try_from(n).unwrap_or(usize::MAX);
+        self.pos = (n / channels * channels).min(self.data.len());
         Ok(())
     }
 }
@@ -125,12 +229,15 @@ where
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.data.len(), Some(self.data.len()))
+        let remaining = self.data.len() - self.pos;
+        (remaining, Some(remaining))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use crate::buffer::SamplesBuffer;
     use crate::source::Source;
 
@@ -140,17 +247,38 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "at least one channel")]
     fn panic_if_zero_channels() {
         SamplesBuffer::new(0, 44100, vec![0i16, 0, 0, 0, 0, 0]);
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "non-zero sample rate")]
     fn panic_if_zero_sample_rate() {
         SamplesBuffer::new(1, 0, vec![0i16, 0, 0, 0, 0, 0]);
     }
 
+    #[test]
+    #[should_panic(expected = "not a multiple of 2 channels")]
+    fn panic_if_incomplete_frame() {
+        SamplesBuffer::new(2, 44100, vec![0i16, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn sample_format() {
+        let buf = SamplesBuffer::new(1, 44100, vec![0.5f32]);
+        assert_eq!(buf.sample_format(), crate::SampleFormat::F32);
+        let buf = SamplesBuffer::new(1, 44100, vec![0u8]);
+        assert_eq!(buf.sample_format(), crate::SampleFormat::U8);
+    }
+
+    #[test]
+    fn f32_samples() {
+        let buf = SamplesBuffer::new(2, 44100, vec![0.1f32, -0.1, 0.25, -0.25]);
+        assert_eq!(buf.channels(), 2);
+        assert_eq!(buf.collect::<Vec<_>>(), vec![0.1, -0.1, 0.25, -0.25]);
+    }
+
     #[test]
     fn duration_basic() {
         let buf = SamplesBuffer::new(2, 2, vec![0i16, 0, 0, 0, 0, 0]);
@@ -171,9 +299,55 @@ mod tests {
         assert_eq!(buf.next(), None);
     }
 
+    #[test]
+    fn samples_remaining() {
+        let mut buf = SamplesBuffer::new(2, 44100, vec![1i16, 2, 3, 4, 5, 6]).amplify(0.5);
+        assert_eq!(buf.samples_remaining(), Some(6));
+        buf.next();
+        assert_eq!(buf.samples_remaining(), Some(5));
+        buf.by_ref().take(3).count();
+        assert_eq!(buf.samples_remaining(), Some(2));
+        buf.by_ref().count();
+        assert_eq!(buf.samples_remaining(), Some(0));
+    }
+
+    #[test]
+    fn is_seekable() {
+        let buf = SamplesBuffer::new(1, 44100, vec![1i16, 2, 3]);
+        assert!(buf.is_seekable());
+        assert!(buf
+            .clone()
+            .amplify(0.5)
+            .fade_in(Duration::from_secs(1))
+            .is_seekable());
+        assert!(!buf.buffered().is_seekable());
+    }
+
+    #[test]
+    fn from_source() {
+        let source = SamplesBuffer::new(2, 44100, vec![1i16, 2, 3, 4, 5, 6]).amplify(2.0);
+        let buf = SamplesBuffer::<i16>::from_source(source, None).unwrap();
+        assert_eq!(buf.channels(), 2);
+        assert_eq!(buf.sample_rate(), 44100);
+        assert_eq!(buf.collect::<Vec<_>>(), vec![2, 4, 6, 8, 10, 12]);
+    }
+
+    #[test]
+    fn from_infinite_source() {
+        let source = crate::source::SineWave::new(440.0);
+        assert_eq!(
+            SamplesBuffer::<f32>::from_source(source.clone(), None).unwrap_err(),
+            super::UnboundedSourceError
+        );
+
+        let buf = SamplesBuffer::<f32>::from_source(source, Some(Duration::from_millis(100)));
+        assert_eq!(buf.unwrap().count(), 4800);
+    }
+
     #[cfg(test)]
     mod try_seek {
         use super::*;
+        use crate::source::SeekDirection;
         use std::time::Duration;
 
         #[test]
@@ -195,7 +369,83 @@ mod tests {
             assert!(buf.next().is_some_and(|s| s % 2 == 0));
 
             buf.try_seek(Duration::from_secs(6)).unwrap();
-            assert!(buf.next().is_some_and(|s| s % 2 == 1),);
+            assert!(buf.next().is_some_and(|s| s % 2 == 0),);
+        }
+
+        #[test]
+        fn snaps_to_the_frame_start() {
+            let mut buf = SamplesBuffer::new(2, 10, (0..40i16).collect::<Vec<_>>());
+            // sample 7, the second channel of the fourth frame
+            buf.try_seek(Duration::from_millis(350)).unwrap();
+            assert_eq!(buf.next(), Some(6));
+        }
+
+        #[test]
+        fn to_half() {
+            let mut buf = SamplesBuffer::new(2, 10, (0..40i16).collect::<Vec<_>>());
+            buf.try_seek(buf.total_duration().unwrap() / 2).unwrap();
+            assert_eq!(buf.next(), Some(20));
+            assert_eq!(buf.size_hint(), (19, Some(19)));
+        }
+
+        #[test]
+        fn past_the_end() {
+            let mut buf = SamplesBuffer::new(2, 10, (0..40i16).collect::<Vec<_>>());
+            buf.next();
+            buf.try_seek(Duration::from_secs(10)).unwrap();
+            assert_eq!(buf.next(), None);
+        }
+
+        #[test]
+        fn by_samples() {
+            let mut buf = SamplesBuffer::new(2, 10, (0..40i16).collect::<Vec<_>>());
+            buf.try_seek_samples(13).unwrap();
+            assert_eq!(buf.next(), Some(12));
+            // the default works with time, and lands on the same frame
+            let mut amplified = buf.clone().amplify(1.0);
+            amplified.try_seek_samples(27).unwrap();
+            assert_eq!(amplified.next(), Some(26));
+
+            buf.try_seek_samples(1000).unwrap();
+            assert_eq!(buf.next(), None);
+        }
+
+        #[test]
+        fn relative() {
+            let mut buf = SamplesBuffer::new(2, 10, (0..40i16).collect::<Vec<_>>());
+            buf.try_seek_relative(Duration::from_millis(500), SeekDirection::Forward)
+                .unwrap();
+            assert_eq!(buf.playback_pos(), Some(Duration::from_millis(500)));
+            buf.try_seek_relative(Duration::from_millis(200), SeekDirection::Backward)
+                .unwrap();
+            assert_eq!(buf.next(), Some(6));
+        }
+
+        #[test]
+        fn relative_before_the_start() {
+            let mut buf = SamplesBuffer::new(2, 10, (0..40i16).collect::<Vec<_>>());
+            buf.by_ref().take(9).count();
+            buf.try_seek_relative(Duration::from_secs(10), SeekDirection::Backward)
+                .unwrap();
+            assert_eq!(buf.playback_pos(), Some(Duration::ZERO));
+            assert_eq!(buf.next(), Some(0));
+        }
+
+        #[test]
+        fn relative_past_the_end() {
+            let mut buf = SamplesBuffer::new(2, 10, (0..40i16).collect::<Vec<_>>());
+            buf.try_seek_relative(Duration::from_secs(10), SeekDirection::Forward)
+                .unwrap();
+            assert_eq!(buf.playback_pos(), buf.total_duration());
+            assert_eq!(buf.next(), None);
+        }
+
+        #[test]
+        fn to_start_mid_frame() {
+            let mut buf = SamplesBuffer::new(2, 10, (0..40i16).collect::<Vec<_>>());
+            buf.next();
+            buf.try_seek(Duration::ZERO).unwrap();
+            assert_eq!(buf.next(), Some(0));
         }
     }
 }
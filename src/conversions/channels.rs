@@ -47,6 +47,12 @@ where
         self.input
     }
 
+    /// Get access to the iterator
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
     /// Get mutable access to the iterator
     #[inline]
     pub fn inner_mut(&mut self) -> &mut I {
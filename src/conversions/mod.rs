@@ -8,10 +8,13 @@ This includes conversion between sample formats, channels or sample rates.
 pub use self::channels::ChannelCountConverter;
 pub use self::sample::DataConverter;
 pub use self::sample::Sample;
+pub use self::sample::WideSample;
 pub use self::sample_rate::SampleRateConverter;
+pub use self::sinc::SincSampleRateConverter;
 
 mod channels;
 // TODO: < shouldn't be public ; there's a bug in Rust 1.4 and below that makes This
 // `pub` mandatory
 pub mod sample;
 mod sample_rate;
+mod sinc;
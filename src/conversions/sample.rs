@@ -1,5 +1,6 @@
-use cpal::{FromSample, Sample as CpalSample};
+use cpal::{FromSample, Sample as CpalSample, SampleFormat};
 use std::marker::PhantomData;
+use std::ops::Add;
 
 /// Converts the samples data type to `O`.
 #[derive(Clone, Debug)]
@@ -24,6 +25,12 @@ impl<I, O> DataConverter<I, O> {
         self.input
     }
 
+    /// get access to the iterator
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
     /// get mutable access to the iterator
     #[inline]
     pub fn inner_mut(&mut self) -> &mut I {
@@ -60,18 +67,28 @@ where
 
 /// Represents a value of a single sample.
 ///
-/// This trait is implemented by default on three types: `i16`, `u16` and `f32`.
+/// This trait is implemented by default on `i8`, `u8`, `i16`, `u16`, `f32` and `f64`.
 ///
+/// - For `i8`, silence corresponds to the value `0`. The minimum and maximum amplitudes are
+///   represented by `i8::MIN` and `i8::MAX` respectively.
+/// - For `u8`, silence corresponds to the value `128`. The minimum and maximum amplitudes are
+///   represented by `0` and `u8::MAX` respectively.
 /// - For `i16`, silence corresponds to the value `0`. The minimum and maximum amplitudes are
 ///   represented by `i16::min_value()` and `i16::max_value()` respectively.
 /// - For `u16`, silence corresponds to the value `u16::max_value() / 2`. The minimum and maximum
 ///   amplitudes are represented by `0` and `u16::max_value()` respectively.
-/// - For `f32`, silence corresponds to the value `0.0`. The minimum and maximum amplitudes are
-///   represented by `-1.0` and `1.0` respectively.
+/// - For `f32` and `f64`, silence corresponds to the value `0.0`. The minimum and maximum
+///   amplitudes are represented by `-1.0` and `1.0` respectively.
 ///
 /// You can implement this trait on your own type as well if you wish so.
 ///
 pub trait Sample: CpalSample {
+    /// The format of this type of sample.
+    ///
+    /// Defaults to `F32`, the format the samples are played in, for the types that are not
+    /// one of the formats of cpal.
+    const FORMAT: SampleFormat = SampleFormat::F32;
+
     /// Linear interpolation between two samples.
     ///
     /// The result should be equal to
@@ -86,11 +103,122 @@ pub trait Sample: CpalSample {
     /// Calls `saturating_add` on the sample.
     fn saturating_add(self, other: Self) -> Self;
 
+    /// Adds two samples, wrapping around at the edges of the range of amplitudes.
+    ///
+    /// The default implementation saturates instead, for types that have no natural way to
+    /// wrap.
+    #[inline]
+    fn wrapping_add(self, other: Self) -> Self {
+        self.saturating_add(other)
+    }
+
+    /// Adds two samples, returns `None` if the result does not fit in the range of amplitudes.
+    ///
+    /// The default implementation compares the sum of the `f32` values to the
+    /// `[-1.0, 1.0]` range.
+    #[inline]
+    fn checked_add(self, other: Self) -> Option<Self> {
+        let sum = self.to_f32() + other.to_f32();
+        (-1.0..=1.0)
+            .contains(&sum)
+            .then(|| self.saturating_add(other))
+    }
+
     /// Returns the value corresponding to the absence of sound.
     fn zero_value() -> Self;
 }
 
+impl Sample for u8 {
+    const FORMAT: SampleFormat = SampleFormat::U8;
+
+    #[inline]
+    fn lerp(first: u8, second: u8, numerator: u32, denominator: u32) -> u8 {
+        let a = first as i32;
+        let b = second as i32;
+        let n = numerator as i32;
+        let d = denominator as i32;
+        (a + (b - a) * n / d) as u8
+    }
+
+    #[inline]
+    fn amplify(self, value: f32) -> u8 {
+        // Scale the distance from silence, not the raw value.
+        ((self as f32 - 128.0) * value + 128.0).clamp(0.0, u8::MAX as f32) as u8
+    }
+
+    #[inline]
+    fn to_f32(self) -> f32 {
+        // Convert u8 to f32 in the range [-1.0, 1.0]
+        (self as f32 - 128.0) / 128.0
+    }
+
+    #[inline]
+    fn saturating_add(self, other: u8) -> u8 {
+        // Both values are offset by 128, remove one of the offsets before clamping.
+        (self as i16 + other as i16 - 128).clamp(0, u8::MAX as i16) as u8
+    }
+
+    #[inline]
+    fn wrapping_add(self, other: u8) -> u8 {
+        // Both values are offset by 128, remove one of them.
+        self.wrapping_add(other).wrapping_sub(128)
+    }
+
+    #[inline]
+    fn checked_add(self, other: u8) -> Option<u8> {
+        u8::try_from(self as i16 + other as i16 - 128).ok()
+    }
+
+    #[inline]
+    fn zero_value() -> u8 {
+        128
+    }
+}
+
+impl Sample for i8 {
+    const FORMAT: SampleFormat = SampleFormat::I8;
+
+    #[inline]
+    fn lerp(first: i8, second: i8, numerator: u32, denominator: u32) -> i8 {
+        (first as i32 + (second as i32 - first as i32) * numerator as i32 / denominator as i32)
+            as i8
+    }
+
+    #[inline]
+    fn amplify(self, value: f32) -> i8 {
+        ((self as f32) * value) as i8
+    }
+
+    #[inline]
+    fn to_f32(self) -> f32 {
+        // Convert i8 to f32 in the range [-1.0, 1.0]
+        self as f32 / 128.0
+    }
+
+    #[inline]
+    fn saturating_add(self, other: i8) -> i8 {
+        self.saturating_add(other)
+    }
+
+    #[inline]
+    fn wrapping_add(self, other: i8) -> i8 {
+        self.wrapping_add(other)
+    }
+
+    #[inline]
+    fn checked_add(self, other: i8) -> Option<i8> {
+        self.checked_add(other)
+    }
+
+    #[inline]
+    fn zero_value() -> i8 {
+        0
+    }
+}
+
 impl Sample for u16 {
+    const FORMAT: SampleFormat = SampleFormat::U16;
+
     #[inline]
     fn lerp(first: u16, second: u16, numerator: u32, denominator: u32) -> u16 {
         let a = first as i32;
@@ -116,6 +244,17 @@ impl Sample for u16 {
         self.saturating_add(other)
     }
 
+    #[inline]
+    fn wrapping_add(self, other: u16) -> u16 {
+        // Both values are offset by 32768, remove one of them.
+        self.wrapping_add(other).wrapping_sub(32768)
+    }
+
+    #[inline]
+    fn checked_add(self, other: u16) -> Option<u16> {
+        u16::try_from(self as i32 + other as i32 - 32768).ok()
+    }
+
     #[inline]
     fn zero_value() -> u16 {
         32768
@@ -123,6 +262,8 @@ impl Sample for u16 {
 }
 
 impl Sample for i16 {
+    const FORMAT: SampleFormat = SampleFormat::I16;
+
     #[inline]
     fn lerp(first: i16, second: i16, numerator: u32, denominator: u32) -> i16 {
         (first as i32 + (second as i32 - first as i32) * numerator as i32 / denominator as i32)
@@ -145,6 +286,16 @@ impl Sample for i16 {
         self.saturating_add(other)
     }
 
+    #[inline]
+    fn wrapping_add(self, other: i16) -> i16 {
+        self.wrapping_add(other)
+    }
+
+    #[inline]
+    fn checked_add(self, other: i16) -> Option<i16> {
+        self.checked_add(other)
+    }
+
     #[inline]
     fn zero_value() -> i16 {
         0
@@ -152,6 +303,8 @@ impl Sample for i16 {
 }
 
 impl Sample for f32 {
+    const FORMAT: SampleFormat = SampleFormat::F32;
+
     #[inline]
     fn lerp(first: f32, second: f32, numerator: u32, denominator: u32) -> f32 {
         first + (second - first) * numerator as f32 / denominator as f32
@@ -173,8 +326,172 @@ impl Sample for f32 {
         self + other
     }
 
+    #[inline]
+    fn wrapping_add(self, other: f32) -> f32 {
+        let sum = self + other;
+        if (-1.0..=1.0).contains(&sum) {
+            sum
+        } else {
+            (sum + 1.0).rem_euclid(2.0) - 1.0
+        }
+    }
+
+    #[inline]
+    fn checked_add(self, other: f32) -> Option<f32> {
+        let sum = self + other;
+        (-1.0..=1.0).contains(&sum).then_some(sum)
+    }
+
     #[inline]
     fn zero_value() -> f32 {
         0.0
     }
 }
+
+impl Sample for f64 {
+    const FORMAT: SampleFormat = SampleFormat::F64;
+
+    #[inline]
+    fn lerp(first: f64, second: f64, numerator: u32, denominator: u32) -> f64 {
+        first + (second - first) * numerator as f64 / denominator as f64
+    }
+
+    #[inline]
+    fn amplify(self, value: f32) -> f64 {
+        self * value as f64
+    }
+
+    #[inline]
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    #[inline]
+    fn saturating_add(self, other: f64) -> f64 {
+        self + other
+    }
+
+    #[inline]
+    fn wrapping_add(self, other: f64) -> f64 {
+        let sum = self + other;
+        if (-1.0..=1.0).contains(&sum) {
+            sum
+        } else {
+            (sum + 1.0).rem_euclid(2.0) - 1.0
+        }
+    }
+
+    #[inline]
+    fn checked_add(self, other: f64) -> Option<f64> {
+        let sum = self + other;
+        (-1.0..=1.0).contains(&sum).then_some(sum)
+    }
+
+    #[inline]
+    fn zero_value() -> f64 {
+        0.0
+    }
+}
+
+/// A sample that can be added up in a wider type without clipping, see
+/// [`MixMode::Wide`](crate::dynamic_mixer::MixMode::Wide).
+///
+/// This trait is implemented on the same types as [`Sample`].
+pub trait WideSample: Sample {
+    /// The wider type.
+    ///
+    /// This is `i32` for the integer samples, and the type itself for `f32` and `f64`.
+    type Wide: Copy + PartialEq + Add<Output = Self::Wide>;
+
+    /// Converts the sample to the wide type, silence being zero.
+    fn to_wide(self) -> Self::Wide;
+
+    /// Converts a sum made in the wide type back to a sample, clamped to the range of
+    /// amplitudes. Like with [`saturating_add`](Sample::saturating_add), the float samples
+    /// are not clamped.
+    fn from_wide(wide: Self::Wide) -> Self;
+}
+
+impl WideSample for u8 {
+    type Wide = i32;
+
+    #[inline]
+    fn to_wide(self) -> i32 {
+        self as i32 - 128
+    }
+
+    #[inline]
+    fn from_wide(wide: i32) -> u8 {
+        (wide + 128).clamp(0, u8::MAX as i32) as u8
+    }
+}
+
+impl WideSample for i8 {
+    type Wide = i32;
+
+    #[inline]
+    fn to_wide(self) -> i32 {
+        self as i32
+    }
+
+    #[inline]
+    fn from_wide(wide: i32) -> i8 {
+        wide.clamp(i8::MIN as i32, i8::MAX as i32) as i8
+    }
+}
+
+impl WideSample for u16 {
+    type Wide = i32;
+
+    #[inline]
+    fn to_wide(self) -> i32 {
+        self as i32 - 32768
+    }
+
+    #[inline]
+    fn from_wide(wide: i32) -> u16 {
+        (wide + 32768).clamp(0, u16::MAX as i32) as u16
+    }
+}
+
+impl WideSample for i16 {
+    type Wide = i32;
+
+    #[inline]
+    fn to_wide(self) -> i32 {
+        self as i32
+    }
+
+    #[inline]
+    fn from_wide(wide: i32) -> i16 {
+        wide.clamp(i16::MIN as i32, i16::MAX as i32) as i16
+    }
+}
+
+impl WideSample for f32 {
+    type Wide = f32;
+
+    #[inline]
+    fn to_wide(self) -> f32 {
+        self
+    }
+
+    #[inline]
+    fn from_wide(wide: f32) -> f32 {
+        wide
+    }
+}
+
+impl WideSample for f64 {
+    type Wide = f64;
+
+    #[inline]
+    fn to_wide(self) -> f64 {
+        self
+    }
+
+    #[inline]
+    fn from_wide(wide: f64) -> f64 {
+        wide
+    }
+}
@@ -102,6 +102,12 @@ where
         self.input
     }
 
+    /// get access to the iterator
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
     /// get mutable access to the iterator
     #[inline]
     pub fn inner_mut(&mut self) -> &mut I {
@@ -0,0 +1,308 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use crate::conversions::Sample;
+
+/// Number of zero crossings of the sinc on each side of the interpolated position.
+const ZERO_CROSSINGS: f64 = 16.0;
+
+/// Fraction of the lowest Nyquist frequency that is let through. The rest is the transition
+/// band of the low-pass filter.
+const CUTOFF: f64 = 0.9;
+
+/// Iterator that converts from a certain sample rate to another using band-limited
+/// (windowed sinc) interpolation.
+///
+/// Compared to [`SampleRateConverter`](super::SampleRateConverter) this is slower and delays
+/// the sound by a few samples, but it lets through much less aliasing.
+#[derive(Clone, Debug)]
+pub struct SincSampleRateConverter<I>
+where
+    I: Iterator,
+{
+    /// The iterator that gives us samples.
+    input: I,
+    /// We convert chunks of `from` frames into chunks of `to` frames.
+    from: u64,
+    /// We convert chunks of `from` frames into chunks of `to` frames.
+    to: u64,
+    /// Number of channels in the stream.
+    channels: usize,
+    /// Cutoff frequency of the filter relative to the input Nyquist frequency.
+    cutoff: f64,
+    /// Number of input frames used on each side of the interpolated position.
+    half_width: u64,
+    /// Interleaved input frames around the position of the next output frame.
+    history: VecDeque<f32>,
+    /// Index of the first frame in `history`.
+    history_start: u64,
+    /// Total number of input frames, known once the input has run out.
+    input_frames: Option<u64>,
+    /// Index of the next output frame.
+    next_output_frame: u64,
+    /// Filter weights for the frames of `history`, reused between output frames.
+    weights: Vec<f64>,
+    /// The samples of the current output frame waiting to be returned, in reverse order.
+    output_buffer: Vec<I::Item>,
+    /// Converts a filtered value back to the sample type.
+    from_f32: fn(f32) -> I::Item,
+}
+
+impl<I> SincSampleRateConverter<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    /// Initializes the iterator, `from_f32` is used to convert the filtered values back to the
+    /// type of the input samples.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `from`, `to` or `num_channels` are equal to 0.
+    ///
+    pub fn new(
+        input: I,
+        from: cpal::SampleRate,
+        to: cpal::SampleRate,
+        num_channels: cpal::ChannelCount,
+        from_f32: fn(f32) -> I::Item,
+    ) -> SincSampleRateConverter<I> {
+        let from = from.0 as u64;
+        let to = to.0 as u64;
+
+        assert!(from >= 1);
+        assert!(to >= 1);
+        assert!(num_channels >= 1);
+
+        let gcd = {
+            #[inline]
+            fn gcd(a: u64, b: u64) -> u64 {
+                if b == 0 {
+                    a
+                } else {
+                    gcd(b, a % b)
+                }
+            }
+
+            gcd(from, to)
+        };
+
+        // When lowering the sample rate the filter has to be narrower, and thus longer, to
+        // remove what the output can not represent.
+        let cutoff = CUTOFF * (to as f64 / from as f64).min(1.0);
+        let half_width = (ZERO_CROSSINGS / cutoff).ceil() as u64;
+
+        SincSampleRateConverter {
+            input,
+            from: from / gcd,
+            to: to / gcd,
+            channels: num_channels as usize,
+            cutoff,
+            half_width,
+            history: VecDeque::new(),
+            history_start: 0,
+            input_frames: None,
+            next_output_frame: 0,
+            weights: Vec::new(),
+            output_buffer: Vec::with_capacity(num_channels as usize),
+            from_f32,
+        }
+    }
+
+    /// Destroys this iterator and returns the underlying iterator.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Get access to the iterator
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// get mutable access to the iterator
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    fn history_frames(&self) -> u64 {
+        (self.history.len() / self.channels) as u64
+    }
+
+    /// Reads input frames until `history` reaches `frame` or the input runs out.
+    fn fill_history(&mut self, frame: u64) {
+        while self.input_frames.is_none() && self.history_start + self.history_frames() <= frame {
+            for channel in 0..self.channels {
+                match self.input.next() {
+                    Some(sample) => self.history.push_back(sample.to_f32()),
+                    None => {
+                        if channel != 0 {
+                            // complete the last frame with silence
+                            let missing = self.channels - channel;
+                            self.history.resize(self.history.len() + missing, 0.0);
+                        }
+                        self.input_frames = Some(self.history_start + self.history_frames());
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Windowed sinc, `distance` is in input frames.
+    fn weight(&self, distance: f64) -> f64 {
+        let width = self.half_width as f64;
+        if distance.abs() >= width {
+            return 0.0;
+        }
+
+        let x = self.cutoff * distance;
+        let sinc = if x == 0.0 {
+            1.0
+        } else {
+            (PI * x).sin() / (PI * x)
+        };
+        // Blackman window
+        let phase = PI * distance / width;
+        let window = 0.42 + 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+        self.cutoff * sinc * window
+    }
+}
+
+impl<I> Iterator for SincSampleRateConverter<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if self.from == self.to {
+            return self.input.next();
+        }
+
+        if let Some(sample) = self.output_buffer.pop() {
+            return Some(sample);
+        }
+
+        // Position of the output frame in the input, as an integer and a fractional part.
+        let position = self.next_output_frame * self.from;
+        let frame = position / self.to;
+        let fraction = (position % self.to) as f64 / self.to as f64;
+
+        self.fill_history(frame + self.half_width);
+        if self.input_frames.is_some_and(|frames| frame >= frames) {
+            return None;
+        }
+
+        // Frames before the window are never needed again.
+        let first = (frame + 1).saturating_sub(self.half_width);
+        while self.history_start < first && !self.history.is_empty() {
+            self.history.drain(..self.channels);
+            self.history_start += 1;
+        }
+
+        let mut weights = std::mem::take(&mut self.weights);
+        weights.clear();
+        weights.extend(
+            (0..self.history_frames())
+                .map(|i| self.weight(frame as f64 + fraction - (self.history_start + i) as f64)),
+        );
+
+        for channel in (0..self.channels).rev() {
+            let value: f64 = weights
+                .iter()
+                .zip(self.history.iter().skip(channel).step_by(self.channels))
+                .map(|(weight, sample)| weight * *sample as f64)
+                .sum();
+            self.output_buffer.push((self.from_f32)(value as f32));
+        }
+        self.weights = weights;
+
+        self.next_output_frame += 1;
+        self.output_buffer.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.from == self.to {
+            return self.input.size_hint();
+        }
+
+        let apply = |input_samples: usize| {
+            let input_frames =
+                self.history_start + self.history_frames() + (input_samples / self.channels) as u64;
+            let output_frames = (input_frames * self.to).div_ceil(self.from);
+            let remaining = output_frames.saturating_sub(self.next_output_frame);
+            remaining as usize * self.channels + self.output_buffer.len()
+        };
+
+        match self.input_frames {
+            Some(_) => {
+                let remaining = apply(0);
+                (remaining, Some(remaining))
+            }
+            None => {
+                let (min, max) = self.input.size_hint();
+                (apply(min), max.map(apply))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SincSampleRateConverter;
+    use cpal::SampleRate;
+
+    fn from_f32(value: f32) -> f32 {
+        value
+    }
+
+    #[test]
+    fn identity_passes_through() {
+        let input = vec![0.1f32, 0.2, 0.3, 0.4];
+        let output: Vec<f32> = SincSampleRateConverter::new(
+            input.clone().into_iter(),
+            SampleRate(44100),
+            SampleRate(44100),
+            2,
+            from_f32,
+        )
+        .collect();
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn output_length() {
+        let input = vec![0.0f32; 2 * 441];
+        let converter = SincSampleRateConverter::new(
+            input.into_iter(),
+            SampleRate(44100),
+            SampleRate(48000),
+            2,
+            from_f32,
+        );
+        assert_eq!(converter.size_hint(), (2 * 480, Some(2 * 480)));
+        assert_eq!(converter.count(), 2 * 480);
+    }
+
+    #[test]
+    fn keeps_dc_level() {
+        let input = vec![0.5f32; 1000];
+        let output: Vec<f32> = SincSampleRateConverter::new(
+            input.into_iter(),
+            SampleRate(48000),
+            SampleRate(32000),
+            1,
+            from_f32,
+        )
+        .collect();
+        // away from the edges, where the signal starts and stops
+        for sample in &output[100..500] {
+            assert!((sample - 0.5).abs() < 1e-3, "{}", sample);
+        }
+    }
+}
@@ -3,8 +3,9 @@ use std::io::{Read, Seek, SeekFrom};
 use std::mem;
 use std::time::Duration;
 
+use super::Metadata;
 use crate::source::SeekError;
-use crate::Source;
+use crate::{SampleFormat, Source};
 
 use claxon::FlacReader;
 
@@ -21,6 +22,9 @@ where
     sample_rate: u32,
     channels: u16,
     samples: Option<u64>,
+    // Number of samples returned, counting those of all the channels.
+    samples_read: u64,
+    metadata: Metadata,
 }
 
 impl<R> FlacDecoder<R>
@@ -35,6 +39,7 @@ where
 
         let reader = FlacReader::new(data).unwrap();
         let spec = reader.streaminfo();
+        let metadata = reader.tags().collect();
 
         Ok(FlacDecoder {
             reader,
@@ -47,11 +52,16 @@ where
             sample_rate: spec.sample_rate,
             channels: spec.channels as u16,
             samples: spec.samples,
+            samples_read: 0,
+            metadata,
         })
     }
     pub fn into_inner(self) -> R {
         self.reader.into_inner()
     }
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
 }
 
 impl<R> Source for FlacDecoder<R>
@@ -81,6 +91,21 @@ where
             .map(|s| Duration::from_micros(s * 1_000_000 / self.sample_rate as u64))
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        let total = self.samples? * self.channels as u64;
+        Some(total.saturating_sub(self.samples_read) as usize)
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        match self.bits_per_sample {
+            0..=8 => SampleFormat::I8,
+            9..=16 => SampleFormat::I16,
+            _ => SampleFormat::I32,
+        }
+    }
+
     #[inline]
     fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
         Err(SeekError::NotSupported {
@@ -105,6 +130,7 @@ where
                     + self.current_block_off / self.channels as usize;
                 let raw_val = self.current_block[real_offset];
                 self.current_block_off += 1;
+                self.samples_read += 1;
                 let real_val = match self.bits_per_sample.cmp(&16) {
                     Ordering::Less => (raw_val << (16 - self.bits_per_sample)) as i16,
                     Ordering::Equal => raw_val as i16,
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+/// The tags of a decoded file, such as its title or artist.
+///
+/// The tags are Vorbis comments for Ogg and Flac files, and ID3 frames for MP3 files. Their
+/// keys are upper case, with the names Vorbis comments use for the well known ones: `TITLE`,
+/// `ARTIST`, `ALBUM` and so on. Tags with an empty value are left out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metadata {
+    tags: BTreeMap<String, String>,
+}
+
+impl Metadata {
+    // For the formats that have no tags.
+    pub(crate) fn empty() -> &'static Metadata {
+        static EMPTY: Metadata = Metadata {
+            tags: BTreeMap::new(),
+        };
+        &EMPTY
+    }
+
+    // When a key is repeated, the first value is kept.
+    pub(crate) fn insert(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        if !value.is_empty() {
+            self.tags
+                .entry(key.trim().to_ascii_uppercase())
+                .or_insert_with(|| value.to_owned());
+        }
+    }
+
+    /// Returns the value of a tag, the key is not case sensitive.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.tags.get(&key.to_ascii_uppercase()).map(String::as_str)
+    }
+
+    /// Returns all the tags, by key.
+    #[inline]
+    pub fn tags(&self) -> &BTreeMap<String, String> {
+        &self.tags
+    }
+
+    /// Returns the title of the track.
+    #[inline]
+    pub fn title(&self) -> Option<&str> {
+        self.get("TITLE")
+    }
+
+    /// Returns the artist of the track.
+    #[inline]
+    pub fn artist(&self) -> Option<&str> {
+        self.get("ARTIST")
+    }
+
+    /// Returns the album the track is from.
+    #[inline]
+    pub fn album(&self) -> Option<&str> {
+        self.get("ALBUM")
+    }
+
+    /// Returns the genre of the track.
+    #[inline]
+    pub fn genre(&self) -> Option<&str> {
+        self.get("GENRE")
+    }
+
+    /// Returns the date the track was released, as written in the file.
+    #[inline]
+    pub fn date(&self) -> Option<&str> {
+        self.get("DATE")
+    }
+
+    /// Returns the number of the track on its album, as written in the file.
+    #[inline]
+    pub fn track_number(&self) -> Option<&str> {
+        self.get("TRACKNUMBER")
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Metadata
+where
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(tags: T) -> Metadata {
+        let mut metadata = Metadata::default();
+        for (key, value) in tags {
+            metadata.insert(key.as_ref(), value.as_ref());
+        }
+        metadata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Metadata;
+
+    #[test]
+    fn missing_and_empty_tags() {
+        let metadata: Metadata = [("title", "Song"), ("Album", ""), ("TITLE", "Other")]
+            .into_iter()
+            .collect();
+        assert_eq!(metadata.title(), Some("Song"));
+        assert_eq!(metadata.get("Title"), Some("Song"));
+        assert_eq!(metadata.album(), None);
+        assert_eq!(metadata.artist(), None);
+        assert_eq!(metadata.tags().len(), 1);
+    }
+}
@@ -9,7 +9,7 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use crate::source::SeekError;
-use crate::Source;
+use crate::{SampleFormat, Source};
 
 #[cfg(feature = "symphonia")]
 use self::read_seek_source::ReadSeekSource;
@@ -18,10 +18,17 @@ use ::symphonia::core::io::{MediaSource, MediaSourceStream};
 
 #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
 mod flac;
+mod metadata;
 #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
 mod mp3;
+#[cfg(feature = "opus")]
+mod opus;
+mod progress;
+mod raw_pcm;
 #[cfg(feature = "symphonia")]
 mod read_seek_source;
+mod sniff;
+mod streaming;
 #[cfg(feature = "symphonia")]
 /// Symphonia decoders types
 pub mod symphonia;
@@ -30,9 +37,15 @@ mod vorbis;
 #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
 mod wav;
 
+pub use self::metadata::Metadata;
+pub use self::progress::decode_with_progress;
+pub use self::raw_pcm::{raw_pcm, RawPcmDecoder};
+use self::sniff::Signature;
+pub use self::streaming::{decode_streaming, decode_streaming_with_policy, StreamingDecoder};
+
 /// Source of audio samples from decoding a file.
 ///
-/// Supports MP3, WAV, Vorbis and Flac.
+/// Supports MP3, WAV, Vorbis and Flac, and Opus with the `opus` feature.
 pub struct Decoder<R>(DecoderImpl<R>)
 where
     R: Read + Seek;
@@ -40,7 +53,7 @@ where
 /// Source of audio samples from decoding a file that never ends. When the
 /// end of the file is reached the decoder starts again from the beginning.
 ///
-/// Supports MP3, WAV, Vorbis and Flac.
+/// Supports MP3, WAV, Vorbis and Flac, and Opus with the `opus` feature.
 pub struct LoopedDecoder<R>(DecoderImpl<R>)
 where
     R: Read + Seek;
@@ -60,6 +73,8 @@ where
     Flac(flac::FlacDecoder<R>),
     #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
     Mp3(mp3::Mp3Decoder<R>),
+    #[cfg(feature = "opus")]
+    Opus(opus::OpusDecoder<R>),
     #[cfg(feature = "symphonia")]
     Symphonia(symphonia::SymphoniaDecoder),
     None(::std::marker::PhantomData<R>),
@@ -77,6 +92,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Flac(source) => source.next(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
             DecoderImpl::Mp3(source) => source.next(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.next(),
             #[cfg(feature = "symphonia")]
             DecoderImpl::Symphonia(source) => source.next(),
             DecoderImpl::None(_) => None,
@@ -94,6 +111,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Flac(source) => source.size_hint(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
             DecoderImpl::Mp3(source) => source.size_hint(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.size_hint(),
             #[cfg(feature = "symphonia")]
             DecoderImpl::Symphonia(source) => source.size_hint(),
             DecoderImpl::None(_) => (0, None),
@@ -111,6 +130,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Flac(source) => source.current_frame_len(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
             DecoderImpl::Mp3(source) => source.current_frame_len(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.current_frame_len(),
             #[cfg(feature = "symphonia")]
             DecoderImpl::Symphonia(source) => source.current_frame_len(),
             DecoderImpl::None(_) => Some(0),
@@ -128,6 +149,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Flac(source) => source.channels(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
             DecoderImpl::Mp3(source) => source.channels(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.channels(),
             #[cfg(feature = "symphonia")]
             DecoderImpl::Symphonia(source) => source.channels(),
             DecoderImpl::None(_) => 0,
@@ -145,6 +168,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Flac(source) => source.sample_rate(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
             DecoderImpl::Mp3(source) => source.sample_rate(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.sample_rate(),
             #[cfg(feature = "symphonia")]
             DecoderImpl::Symphonia(source) => source.sample_rate(),
             DecoderImpl::None(_) => 1,
@@ -162,12 +187,71 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Flac(source) => source.total_duration(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
             DecoderImpl::Mp3(source) => source.total_duration(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.total_duration(),
             #[cfg(feature = "symphonia")]
             DecoderImpl::Symphonia(source) => source.total_duration(),
             DecoderImpl::None(_) => Some(Duration::default()),
         }
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.samples_remaining(),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.samples_remaining(),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.samples_remaining(),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(source) => source.samples_remaining(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.samples_remaining(),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.samples_remaining(),
+            DecoderImpl::None(_) => Some(0),
+        }
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.playback_pos(),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.playback_pos(),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.playback_pos(),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(source) => source.playback_pos(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.playback_pos(),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.playback_pos(),
+            DecoderImpl::None(_) => None,
+        }
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.is_seekable(),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.is_seekable(),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.is_seekable(),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(source) => source.is_seekable(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.is_seekable(),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.is_seekable(),
+            DecoderImpl::None(_) => false,
+        }
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         match self {
@@ -179,6 +263,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Flac(source) => source.try_seek(pos),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
             DecoderImpl::Mp3(source) => source.try_seek(pos),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.try_seek(pos),
             #[cfg(feature = "symphonia")]
             DecoderImpl::Symphonia(source) => source.try_seek(pos),
             DecoderImpl::None(_) => Err(SeekError::NotSupported {
@@ -186,6 +272,41 @@ impl<R: Read + Seek> DecoderImpl<R> {
             }),
         }
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.sample_format(),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.sample_format(),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.sample_format(),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(source) => source.sample_format(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.sample_format(),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.sample_format(),
+            DecoderImpl::None(_) => SampleFormat::I16,
+        }
+    }
+
+    fn metadata(&self) -> &Metadata {
+        match self {
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.metadata(),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.metadata(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.metadata(),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.metadata(),
+            // hound and minimp3 do not read the tags
+            #[allow(unreachable_patterns)]
+            _ => Metadata::empty(),
+        }
+    }
 }
 
 impl<R> Decoder<R>
@@ -194,39 +315,63 @@ where
 {
     /// Builds a new decoder.
     ///
-    /// Attempts to automatically detect the format of the source of data.
+    /// Detects the format from the content of the data, whatever the name of the file it comes
+    /// from. The first bytes are checked against the headers of the supported formats, so only
+    /// the matching decoder is tried; when they match none of them, every decoder is tried in
+    /// turn. Returns [`DecoderError::UnrecognizedFormat`] if no decoder accepts the data.
     #[allow(unused_variables)]
-    pub fn new(data: R) -> Result<Decoder<R>, DecoderError> {
+    pub fn new(mut data: R) -> Result<Decoder<R>, DecoderError> {
+        let signature = sniff::sniff(&mut data);
+
         #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
-        let data = match wav::WavDecoder::new(data) {
-            Err(data) => data,
-            Ok(decoder) => {
-                return Ok(Decoder(DecoderImpl::Wav(decoder)));
-            }
+        let data = match sniff::might_be(signature, Signature::Wav) {
+            false => data,
+            true => match wav::WavDecoder::new(data) {
+                Err(data) => data,
+                Ok(decoder) => {
+                    return Ok(Decoder(DecoderImpl::Wav(decoder)));
+                }
+            },
         };
 
         #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
-        let data = match flac::FlacDecoder::new(data) {
-            Err(data) => data,
-            Ok(decoder) => {
-                return Ok(Decoder(DecoderImpl::Flac(decoder)));
-            }
+        let data = match sniff::might_be(signature, Signature::Flac) {
+            false => data,
+            true => match flac::FlacDecoder::new(data) {
+                Err(data) => data,
+                Ok(decoder) => {
+                    return Ok(Decoder(DecoderImpl::Flac(decoder)));
+                }
+            },
         };
 
+        #[cfg(feature = "opus")]
+        let mut data = data;
+        #[cfg(feature = "opus")]
+        if sniff::might_be(signature, Signature::Ogg) && opus::is_opus(&mut data) {
+            return opus::OpusDecoder::new(data).map(|decoder| Decoder(DecoderImpl::Opus(decoder)));
+        }
+
         #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
-        let data = match vorbis::VorbisDecoder::new(data) {
-            Err(data) => data,
-            Ok(decoder) => {
-                return Ok(Decoder(DecoderImpl::Vorbis(decoder)));
-            }
+        let data = match sniff::might_be(signature, Signature::Ogg) {
+            false => data,
+            true => match vorbis::VorbisDecoder::new(data) {
+                Err(data) => data,
+                Ok(decoder) => {
+                    return Ok(Decoder(DecoderImpl::Vorbis(decoder)));
+                }
+            },
         };
 
         #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
-        let data = match mp3::Mp3Decoder::new(data) {
-            Err(data) => data,
-            Ok(decoder) => {
-                return Ok(Decoder(DecoderImpl::Mp3(decoder)));
-            }
+        let data = match sniff::might_be(signature, Signature::Mp3) {
+            false => data,
+            true => match mp3::Mp3Decoder::new(data) {
+                Err(data) => data,
+                Ok(decoder) => {
+                    return Ok(Decoder(DecoderImpl::Mp3(decoder)));
+                }
+            },
         };
 
         #[cfg(feature = "symphonia")]
@@ -236,7 +381,7 @@ where
                 Default::default(),
             );
 
-            match symphonia::SymphoniaDecoder::new(mss, None) {
+            match symphonia::SymphoniaDecoder::new(mss, signature.map(Signature::extension)) {
                 Err(e) => Err(e),
                 Ok(decoder) => Ok(Decoder(DecoderImpl::Symphonia(decoder))),
             }
@@ -297,6 +442,18 @@ where
         Decoder::new_symphonia(data, "ogg")
     }
 
+    /// Builds a new decoder from ogg/opus data.
+    ///
+    /// The output is always at 48 kHz. Corrupt packets after the first one are played as
+    /// lost packets, which the decoder fills in.
+    #[cfg(feature = "opus")]
+    pub fn new_opus(mut data: R) -> Result<Decoder<R>, DecoderError> {
+        if !opus::is_opus(&mut data) {
+            return Err(DecoderError::UnrecognizedFormat);
+        }
+        opus::OpusDecoder::new(data).map(|decoder| Decoder(DecoderImpl::Opus(decoder)))
+    }
+
     /// Builds a new decoder from mp3 data.
     #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
     pub fn new_mp3(data: R) -> Result<Decoder<R>, DecoderError> {
@@ -382,6 +539,19 @@ impl fmt::Display for Mp4Type {
     }
 }
 
+impl<R> Decoder<R>
+where
+    R: Read + Seek,
+{
+    /// Returns the tags of the file, read along with its headers.
+    ///
+    /// WAV files, and MP3 files decoded with minimp3, have no tags.
+    #[inline]
+    pub fn metadata(&self) -> &Metadata {
+        self.0.metadata()
+    }
+}
+
 impl<R> LoopedDecoder<R>
 where
     R: Read + Seek,
@@ -389,6 +559,12 @@ where
     fn new(decoder: Decoder<R>) -> LoopedDecoder<R> {
         Self(decoder.0)
     }
+
+    /// Returns the tags of the file, read along with its headers.
+    #[inline]
+    pub fn metadata(&self) -> &Metadata {
+        self.0.metadata()
+    }
 }
 
 impl<R> Iterator for Decoder<R>
@@ -431,10 +607,30 @@ where
         self.0.total_duration()
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.0.samples_remaining()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.0.playback_pos()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.0.is_seekable()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.0.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.0.sample_format()
+    }
 }
 
 impl<R> Iterator for LoopedDecoder<R>
@@ -485,6 +681,14 @@ where
                     let sample = source.next();
                     (DecoderImpl::Mp3(source), sample)
                 }
+                #[cfg(feature = "opus")]
+                DecoderImpl::Opus(source) => {
+                    let mut reader = source.into_inner();
+                    reader.seek(SeekFrom::Start(0)).ok()?;
+                    let mut source = opus::OpusDecoder::new(reader).ok()?;
+                    let sample = source.next();
+                    (DecoderImpl::Opus(source), sample)
+                }
                 #[cfg(feature = "symphonia")]
                 DecoderImpl::Symphonia(source) => {
                     let mut reader = source.into_inner();
@@ -530,9 +734,24 @@ where
         None
     }
 
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.0.playback_pos()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.0.is_seekable()
+    }
+
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.0.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.0.sample_format()
+    }
 }
 
 /// Error that can happen when creating a decoder.
@@ -546,7 +765,7 @@ pub enum DecoderError {
     IoError(String),
 
     /// The stream contained malformed data and could not be decoded or demuxed.
-    #[cfg(feature = "symphonia")]
+    #[cfg(any(feature = "symphonia", feature = "opus"))]
     DecodeError(&'static str),
 
     /// A default or user-defined limit was reached while decoding or demuxing the stream. Limits
@@ -569,7 +788,7 @@ impl fmt::Display for DecoderError {
             DecoderError::UnrecognizedFormat => "Unrecognized format",
             #[cfg(feature = "symphonia")]
             DecoderError::IoError(msg) => &msg[..],
-            #[cfg(feature = "symphonia")]
+            #[cfg(any(feature = "symphonia", feature = "opus"))]
             DecoderError::DecodeError(msg) => msg,
             #[cfg(feature = "symphonia")]
             DecoderError::LimitError(msg) => msg,
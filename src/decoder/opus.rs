@@ -0,0 +1,482 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use ogg::reading::PacketReader;
+
+use super::{DecoderError, Metadata};
+use crate::source::SeekError;
+use crate::{SampleFormat, Source};
+
+/// Opus always decodes at 48 kHz, whatever the rate of the recording was.
+const SAMPLE_RATE: u32 = 48000;
+/// Number of frames in the longest packet, 120 ms.
+const MAX_PACKET_FRAMES: usize = 5760;
+/// Number of bytes in the longest Ogg page, a header with 255 segments of 255 bytes.
+const MAX_PAGE_LEN: u64 = 27 + 255 + 255 * 255;
+
+/// Decoder for an OGG file that contains Opus sound format.
+pub struct OpusDecoder<R>
+where
+    R: Read + Seek,
+{
+    reader: PacketReader<R>,
+    decoder: opus::Decoder,
+    channels: u16,
+    // Frames at the start of the stream that only prime the decoder, not played.
+    pre_skip: usize,
+    total_duration: Option<Duration>,
+    current_data: Vec<i16>,
+    next: usize,
+    metadata: Metadata,
+}
+
+impl<R> OpusDecoder<R>
+where
+    R: Read + Seek,
+{
+    /// Decodes the data as ogg/opus, the stream must start with an `OpusHead` packet.
+    ///
+    /// Fails if the headers can not be read, if the channels need a mapping that is not
+    /// supported or if the first packet is corrupt.
+    pub fn new(mut data: R) -> Result<OpusDecoder<R>, DecoderError> {
+        let last_granule = last_granule(&mut data);
+        let mut reader = PacketReader::new(data);
+        let head = match reader.read_packet() {
+            Ok(Some(packet)) => OpusHead::parse(&packet.data),
+            _ => None,
+        }
+        .ok_or(DecoderError::UnrecognizedFormat)?;
+        // Only the mono and stereo mapping, the others need a multistream decoder.
+        let channels = match (head.mapping_family, head.channels) {
+            (0, 1) => opus::Channels::Mono,
+            (0, 2) => opus::Channels::Stereo,
+            _ => {
+                return Err(DecoderError::DecodeError(
+                    "unsupported Opus channel mapping",
+                ))
+            }
+        };
+        // The comment header, the audio comes after it.
+        let metadata = match reader.read_packet() {
+            Ok(Some(packet)) => parse_tags(&packet.data).unwrap_or_default(),
+            _ => return Err(DecoderError::DecodeError("missing Opus comment header")),
+        };
+
+        let mut decoder = opus::Decoder::new(SAMPLE_RATE, channels)
+            .map_err(|_| DecoderError::DecodeError("could not create the Opus decoder"))?;
+        decoder
+            .set_gain(head.output_gain as i32)
+            .map_err(|_| DecoderError::DecodeError("invalid Opus output gain"))?;
+
+        let mut decoder = OpusDecoder {
+            reader,
+            decoder,
+            channels: head.channels as u16,
+            pre_skip: head.pre_skip as usize,
+            // The granule position counts the frames of the pre-skip too.
+            total_duration: last_granule.map(|granule| {
+                let frames = granule.saturating_sub(head.pre_skip as u64);
+                Duration::from_nanos(frames * 1_000_000_000 / SAMPLE_RATE as u64)
+            }),
+            current_data: Vec::with_capacity(MAX_PACKET_FRAMES * head.channels as usize),
+            next: 0,
+            metadata,
+        };
+        if decoder.decode_next_packet(false).is_err() {
+            return Err(DecoderError::DecodeError("corrupt Opus packet"));
+        }
+        Ok(decoder)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    // Decodes the next packet into `current_data`. Returns `Ok(false)` at the end of the
+    // stream, and `Err` on a corrupt packet unless `conceal` is set.
+    fn decode_next_packet(&mut self, conceal: bool) -> Result<bool, ()> {
+        // A stream that can no longer be read ends there.
+        let packet = match self.reader.read_packet() {
+            Ok(Some(packet)) => packet,
+            _ => return Ok(false),
+        };
+
+        let channels = self.channels as usize;
+        self.current_data.resize(MAX_PACKET_FRAMES * channels, 0);
+        let frames = match self
+            .decoder
+            .decode(&packet.data, &mut self.current_data, false)
+        {
+            Ok(frames) => frames,
+            // Played like a lost packet, so the timing of what follows stays right.
+            Err(_) if conceal => self
+                .decoder
+                .decode(&[], &mut self.current_data, false)
+                .unwrap_or(0),
+            Err(_) => return Err(()),
+        };
+        self.current_data.truncate(frames * channels);
+
+        let skipped = self.pre_skip.min(frames);
+        self.pre_skip -= skipped;
+        self.next = skipped * channels;
+        Ok(true)
+    }
+}
+
+impl<R> Source for OpusDecoder<R>
+where
+    R: Read + Seek,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        SampleFormat::I16
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+}
+
+impl<R> Iterator for OpusDecoder<R>
+where
+    R: Read + Seek,
+{
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        // Packets can be empty, or skipped entirely at the start.
+        while self.next >= self.current_data.len() {
+            if !self.decode_next_packet(true).unwrap_or(false) {
+                return None;
+            }
+        }
+        let sample = self.current_data[self.next];
+        self.next += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.current_data.len() - self.next, None)
+    }
+}
+
+/// The identification header that starts every Opus stream.
+struct OpusHead {
+    channels: u8,
+    pre_skip: u16,
+    // In Q7.8 dB.
+    output_gain: i16,
+    mapping_family: u8,
+}
+
+impl OpusHead {
+    fn parse(packet: &[u8]) -> Option<OpusHead> {
+        if packet.len() < 19 || &packet[..8] != b"OpusHead" {
+            return None;
+        }
+        // Versions with another major number are not compatible.
+        if packet[8] >> 4 != 0 {
+            return None;
+        }
+        Some(OpusHead {
+            channels: packet[9],
+            pre_skip: u16::from_le_bytes([packet[10], packet[11]]),
+            output_gain: i16::from_le_bytes([packet[16], packet[17]]),
+            mapping_family: packet[18],
+        })
+    }
+}
+
+/// Reads the Vorbis comments of an `OpusTags` packet.
+fn parse_tags(packet: &[u8]) -> Option<Metadata> {
+    let mut rest = packet.strip_prefix(b"OpusTags")?;
+    // The vendor string comes first.
+    take_field(&mut rest)?;
+    let count = take_u32(&mut rest)?;
+
+    let mut metadata = Metadata::default();
+    for _ in 0..count {
+        let comment = String::from_utf8_lossy(take_field(&mut rest)?);
+        if let Some((key, value)) = comment.split_once('=') {
+            metadata.insert(key, value);
+        }
+    }
+    Some(metadata)
+}
+
+fn take_u32(data: &mut &[u8]) -> Option<u32> {
+    let value = u32::from_le_bytes(data.get(..4)?.try_into().ok()?);
+    *data = &data[4..];
+    Some(value)
+}
+
+// A field prefixed by its length.
+fn take_field<'a>(data: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = take_u32(data)? as usize;
+    let field = data.get(..len)?;
+    *data = &data[len..];
+    Some(field)
+}
+
+/// Returns the granule position of the last page of the stream, which counts its frames, then
+/// resets it to where it was.
+fn last_granule<R>(data: &mut R) -> Option<u64>
+where
+    R: Read + Seek,
+{
+    let stream_pos = data.stream_position().ok()?;
+    let granule = read_last_granule(data);
+    data.seek(SeekFrom::Start(stream_pos)).ok()?;
+    granule
+}
+
+fn read_last_granule<R>(data: &mut R) -> Option<u64>
+where
+    R: Read + Seek,
+{
+    // The last page starts within the length of a page from the end.
+    let len = data.seek(SeekFrom::End(0)).ok()?;
+    data.seek(SeekFrom::Start(len.saturating_sub(MAX_PAGE_LEN)))
+        .ok()?;
+    let mut tail = Vec::new();
+    data.by_ref()
+        .take(MAX_PAGE_LEN)
+        .read_to_end(&mut tail)
+        .ok()?;
+    (0..tail.len())
+        .rev()
+        .find_map(|start| last_page_granule(&tail[start..]))
+}
+
+// The granule position of the page that `data` holds, exactly. Checking its length keeps
+// the bytes of a packet that look like a page header from being taken for one.
+fn last_page_granule(data: &[u8]) -> Option<u64> {
+    if data.len() < 27 || &data[..4] != b"OggS" || data[4] != 0 {
+        return None;
+    }
+    let segments = data[26] as usize;
+    let body: usize = data
+        .get(27..27 + segments)?
+        .iter()
+        .map(|&len| len as usize)
+        .sum();
+    if data.len() != 27 + segments + body {
+        return None;
+    }
+    let granule = u64::from_le_bytes(data[6..14].try_into().ok()?);
+    // A page on which no packet ends has no granule position.
+    (granule != u64::MAX).then_some(granule)
+}
+
+/// Returns true if the stream starts with an Opus header, then resets it to where it was.
+pub fn is_opus<R>(mut data: R) -> bool
+where
+    R: Read + Seek,
+{
+    let Ok(stream_pos) = data.stream_position() else {
+        return false;
+    };
+    let is_opus = match PacketReader::new(data.by_ref()).read_packet() {
+        Ok(Some(packet)) => OpusHead::parse(&packet.data).is_some(),
+        _ => false,
+    };
+    data.seek(SeekFrom::Start(stream_pos)).is_ok() && is_opus
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+    use super::{is_opus, last_granule, parse_tags, take_field, OpusHead};
+
+    fn head(version: u8) -> Vec<u8> {
+        let mut head = b"OpusHead".to_vec();
+        head.extend([version, 2]);
+        head.extend(312u16.to_le_bytes());
+        head.extend(48000u32.to_le_bytes());
+        head.extend((-256i16).to_le_bytes());
+        head.push(0);
+        head
+    }
+
+    fn field(data: &mut Vec<u8>, field: &[u8]) {
+        data.extend((field.len() as u32).to_le_bytes());
+        data.extend(field);
+    }
+
+    // An Ogg page that holds a single packet, shorter than 255 bytes.
+    fn page(header_type: u8, granule: u64, sequence: u32, packet: &[u8]) -> Vec<u8> {
+        let mut page = b"OggS".to_vec();
+        page.extend([0, header_type]);
+        page.extend(granule.to_le_bytes());
+        page.extend(1u32.to_le_bytes());
+        page.extend(sequence.to_le_bytes());
+        page.extend([0; 4]);
+        page.extend([1, packet.len() as u8]);
+        page.extend(packet);
+
+        let crc = page.iter().fold(0u32, |crc, &byte| {
+            (0..8).fold(crc ^ ((byte as u32) << 24), |crc, _| {
+                (crc << 1)
+                    ^ if crc & 0x8000_0000 != 0 {
+                        0x04c1_1db7
+                    } else {
+                        0
+                    }
+            })
+        });
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+        page
+    }
+
+    fn stream() -> Vec<u8> {
+        let mut tags = b"OpusTags".to_vec();
+        field(&mut tags, b"rodio");
+        tags.extend(0u32.to_le_bytes());
+
+        let mut stream = page(0x02, 0, 0, &head(1));
+        stream.extend(page(0, 0, 1, &tags));
+        stream.extend(page(0, 960, 2, &[0xf8, 0xff, 0xfe]));
+        stream.extend(page(0x04, 48312, 3, &[0xf8, 0xff, 0xfe]));
+        stream
+    }
+
+    #[test]
+    fn parses_the_head() {
+        let head = OpusHead::parse(&head(1)).unwrap();
+        assert_eq!(head.channels, 2);
+        assert_eq!(head.pre_skip, 312);
+        assert_eq!(head.output_gain, -256);
+        assert_eq!(head.mapping_family, 0);
+    }
+
+    #[test]
+    fn rejects_an_unknown_version() {
+        // Only the minor version can change.
+        assert!(OpusHead::parse(&head(0x0f)).is_some());
+        assert!(OpusHead::parse(&head(0x10)).is_none());
+    }
+
+    #[test]
+    fn rejects_a_short_head() {
+        let head = head(1);
+        assert!(OpusHead::parse(&head[..18]).is_none());
+        assert!(OpusHead::parse(b"OpusTags").is_none());
+    }
+
+    #[test]
+    fn parses_the_tags() {
+        let mut tags = b"OpusTags".to_vec();
+        field(&mut tags, b"vendor");
+        tags.extend(3u32.to_le_bytes());
+        field(&mut tags, b"TITLE=Song");
+        field(&mut tags, b"ARTIST=Someone=Else");
+        field(&mut tags, b"no separator");
+
+        let metadata = parse_tags(&tags).unwrap();
+        assert_eq!(metadata.get("TITLE"), Some("Song"));
+        assert_eq!(metadata.get("ARTIST"), Some("Someone=Else"));
+        assert_eq!(metadata.tags().len(), 2);
+
+        // A comment cut short makes the whole packet invalid.
+        assert!(parse_tags(&tags[..tags.len() - 1]).is_none());
+        assert!(parse_tags(b"OpusHead").is_none());
+    }
+
+    #[test]
+    fn takes_a_field() {
+        let mut data = Vec::new();
+        field(&mut data, b"abc");
+        data.push(7);
+        let mut rest = &data[..];
+        assert_eq!(take_field(&mut rest), Some(&b"abc"[..]));
+        assert_eq!(rest, [7]);
+
+        let mut short = &[5u8, 0, 0, 0, 1, 2][..];
+        assert_eq!(take_field(&mut short), None);
+        assert_eq!(take_field(&mut &[1u8, 0][..]), None);
+    }
+
+    #[test]
+    fn detects_opus() {
+        let mut stream = Cursor::new(stream());
+        stream.seek(SeekFrom::Start(0)).unwrap();
+        assert!(is_opus(&mut stream));
+        assert_eq!(stream.position(), 0);
+    }
+
+    #[test]
+    fn other_streams_are_not_opus() {
+        let mut vorbis = File::open("assets/music.ogg").unwrap();
+        assert!(!is_opus(&mut vorbis));
+        assert_eq!(vorbis.stream_position().unwrap(), 0);
+
+        let mut wav = File::open("assets/music.wav").unwrap();
+        assert!(!is_opus(&mut wav));
+        assert_eq!(wav.stream_position().unwrap(), 0);
+    }
+
+    struct Unseekable;
+
+    impl Read for Unseekable {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Ok(0)
+        }
+    }
+
+    impl Seek for Unseekable {
+        fn seek(&mut self, _: SeekFrom) -> io::Result<u64> {
+            Err(io::Error::new(io::ErrorKind::Unsupported, "can not seek"))
+        }
+    }
+
+    #[test]
+    fn unseekable_streams_are_not_opus() {
+        assert!(!is_opus(Unseekable));
+    }
+
+    #[test]
+    fn finds_the_last_granule() {
+        let mut stream = Cursor::new(stream());
+        stream.set_position(10);
+        assert_eq!(last_granule(&mut stream), Some(48312));
+        assert_eq!(stream.position(), 10);
+
+        // Without a whole page at the end.
+        let mut cut = stream.into_inner();
+        cut.pop();
+        assert_eq!(last_granule(&mut Cursor::new(cut)), None);
+        assert_eq!(last_granule(&mut Unseekable), None);
+    }
+}
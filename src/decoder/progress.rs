@@ -0,0 +1,171 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use super::{Decoder, DecoderError};
+use crate::buffer::SamplesBuffer;
+use crate::source::UniformSourceIterator;
+use crate::Source;
+
+/// Number of samples decoded between two calls of the progress callback.
+const SAMPLES_PER_TICK: usize = 1 << 16;
+
+/// Decodes a whole file into memory, calling `progress` as it goes.
+///
+/// This is for sources that have to be read entirely before they can play, such as a sound
+/// that is reversed or normalized, so that a long file can show a loading bar. `progress`
+/// receives the fraction of the file read so far, from `0.0` to `1.0`, and is called
+/// regularly while decoding and once at the end. When the length of the data can not be
+/// found out, it receives `None` instead but is still called as often.
+///
+/// The samples are kept in the format of the start of the file, the parts in another one are
+/// converted to it.
+pub fn decode_with_progress<R, F>(
+    mut reader: R,
+    mut progress: F,
+) -> Result<SamplesBuffer<i16>, DecoderError>
+where
+    R: Read + Seek + Send + Sync + 'static,
+    F: FnMut(Option<f32>),
+{
+    let start = reader.stream_position().unwrap_or(0);
+    // A reader that can not go back is left at the end, and is then not recognized.
+    let end = reader
+        .seek(SeekFrom::End(0))
+        .ok()
+        .filter(|_| reader.seek(SeekFrom::Start(start)).is_ok());
+    let position = Arc::new(AtomicU64::new(start));
+    let reader = CountingReader {
+        inner: reader,
+        position: position.clone(),
+    };
+    let fraction = || {
+        end.map(|end| {
+            let read = position.load(Ordering::Relaxed).saturating_sub(start);
+            match end.saturating_sub(start) {
+                0 => 1.0,
+                len => (read as f64 / len as f64).min(1.0) as f32,
+            }
+        })
+    };
+
+    let decoder = Decoder::new(reader)?;
+    let channels = decoder.channels();
+    let sample_rate = decoder.sample_rate();
+    let mut decoder = UniformSourceIterator::<_, i16>::new(decoder, channels, sample_rate);
+
+    let mut samples = Vec::new();
+    progress(fraction());
+    loop {
+        let len = samples.len();
+        samples.extend(decoder.by_ref().take(SAMPLES_PER_TICK));
+        if samples.len() - len < SAMPLES_PER_TICK {
+            break;
+        }
+        progress(fraction());
+    }
+    progress(end.map(|_| 1.0));
+
+    // A file cut in the middle of a frame.
+    samples.truncate(samples.len() - samples.len() % channels as usize);
+    Ok(SamplesBuffer::new(channels, sample_rate, samples))
+}
+
+/// Keeps track of how far the decoder has read.
+struct CountingReader<R> {
+    inner: R,
+    // The offset in the data, like the one `Seek` returns.
+    position: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = self.inner.seek(pos)?;
+        self.position.store(position, Ordering::Relaxed);
+        Ok(position)
+    }
+}
+
+#[cfg(all(test, feature = "wav"))]
+mod tests {
+    use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+    use super::decode_with_progress;
+    use crate::Source;
+
+    fn wav(samples: &[i16]) -> Vec<u8> {
+        let data_len = samples.len() as u32 * 2;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        // PCM, mono, 8 kHz, 16 bits
+        bytes.extend_from_slice(&[1, 0, 1, 0]);
+        bytes.extend_from_slice(&8000u32.to_le_bytes());
+        bytes.extend_from_slice(&16000u32.to_le_bytes());
+        bytes.extend_from_slice(&[2, 0, 16, 0]);
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        samples
+            .iter()
+            .for_each(|sample| bytes.extend_from_slice(&sample.to_le_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn reports_the_fraction_read() {
+        let samples: Vec<i16> = (0..200_000).map(|i| (i % 1000) as i16).collect();
+        let mut reports = Vec::new();
+        let decoded = decode_with_progress(Cursor::new(wav(&samples)), |fraction| {
+            reports.push(fraction.unwrap())
+        })
+        .unwrap();
+
+        assert_eq!(decoded.sample_rate(), 8000);
+        assert_eq!(decoded.collect::<Vec<_>>(), samples);
+        assert!(reports.len() > 3, "{reports:?}");
+        assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(reports.last(), Some(&1.0));
+    }
+
+    // A reader that can not tell its length.
+    struct NoEnd(Cursor<Vec<u8>>);
+
+    impl Read for NoEnd {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Seek for NoEnd {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            match pos {
+                SeekFrom::End(_) => Err(io::ErrorKind::Unsupported.into()),
+                pos => self.0.seek(pos),
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_length_still_ticks() {
+        let samples = vec![7i16; 150_000];
+        let mut reports = Vec::new();
+        let decoded = decode_with_progress(NoEnd(Cursor::new(wav(&samples))), |fraction| {
+            reports.push(fraction)
+        })
+        .unwrap();
+
+        assert_eq!(decoded.count(), samples.len());
+        assert!(reports.len() > 2);
+        assert!(reports.iter().all(Option::is_none));
+    }
+}
@@ -0,0 +1,355 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use super::DecoderError;
+use crate::source::SeekError;
+use crate::{SampleFormat, Source};
+
+/// Number of bytes read from the reader at once.
+const CHUNK_LEN: usize = 4096;
+
+/// Plays the bytes of `reader` as interleaved samples of `format`, for audio that has no
+/// container such as a file dump or a stream of synthesized audio.
+///
+/// The samples are little endian, and the unsigned formats are centered on half of their
+/// range like in WAV files. The data starts at the current position of the reader. If the
+/// reader can tell its length by seeking to its end, the source knows its total duration.
+/// Seeking jumps directly to the bytes of the frame.
+///
+/// # Errors
+///
+/// Returns [`DecoderError::UnrecognizedFormat`] if `format` is not one of the formats of
+/// [`SampleFormat`] known to this version.
+///
+/// # Panics
+///
+/// Panics if the number of channels or the sample rate is zero.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use rodio::decoder;
+/// use rodio::{SampleFormat, Source};
+///
+/// let bytes: Vec<u8> = [0i16, 1000, -1000, 0].iter().flat_map(|s| s.to_le_bytes()).collect();
+/// let source = decoder::raw_pcm(Cursor::new(bytes), 2, 44100, SampleFormat::I16).unwrap();
+/// assert_eq!(source.collect::<Vec<i16>>(), [0, 1000, -1000, 0]);
+/// ```
+pub fn raw_pcm<R>(
+    mut reader: R,
+    channels: u16,
+    sample_rate: u32,
+    format: SampleFormat,
+) -> Result<RawPcmDecoder<R>, DecoderError>
+where
+    R: Read + Seek,
+{
+    assert!(channels >= 1, "raw PCM needs at least one channel");
+    assert!(sample_rate >= 1, "raw PCM needs a sample rate above zero");
+    let sample_len = sample_len(format).ok_or(DecoderError::UnrecognizedFormat)?;
+
+    let length = (|| {
+        let start = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+        Ok::<_, io::Error>((start, end))
+    })();
+    let (start, data_len) = match length {
+        Ok((start, end)) => (start, Some(end.saturating_sub(start))),
+        // The reader is where it started, unless it failed to seek back.
+        Err(_) => (0, None),
+    };
+
+    Ok(RawPcmDecoder {
+        reader,
+        channels,
+        sample_rate,
+        format,
+        sample_len,
+        start,
+        data_len,
+        position: 0,
+        chunk: Vec::with_capacity(CHUNK_LEN),
+        chunk_pos: 0,
+        ended: false,
+    })
+}
+
+/// Source of the samples of raw PCM data, see [`raw_pcm`].
+pub struct RawPcmDecoder<R> {
+    reader: R,
+    channels: u16,
+    sample_rate: u32,
+    format: SampleFormat,
+    // Bytes per sample.
+    sample_len: usize,
+    // Where the data starts in the reader.
+    start: u64,
+    // How many bytes of data there are, if the reader could tell.
+    data_len: Option<u64>,
+    // Bytes played since the start of the data.
+    position: u64,
+    // The bytes read from the reader and not played yet, from `chunk_pos`.
+    chunk: Vec<u8>,
+    chunk_pos: usize,
+    ended: bool,
+}
+
+impl<R> RawPcmDecoder<R>
+where
+    R: Read + Seek,
+{
+    /// Returns the reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // Fills the chunk with at least one sample, returns false at the end of the data.
+    fn fill_chunk(&mut self) -> bool {
+        self.chunk.drain(..self.chunk_pos);
+        self.chunk_pos = 0;
+        while self.chunk.len() < self.sample_len {
+            let kept = self.chunk.len();
+            self.chunk.resize(CHUNK_LEN, 0);
+            match self.reader.read(&mut self.chunk[kept..]) {
+                Ok(0) => {
+                    self.chunk.truncate(kept);
+                    return false;
+                }
+                Ok(read) => self.chunk.truncate(kept + read),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => self.chunk.truncate(kept),
+                Err(_) => {
+                    self.chunk.truncate(kept);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl<R> Iterator for RawPcmDecoder<R>
+where
+    R: Read + Seek,
+{
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        if self.ended {
+            return None;
+        }
+        if self.chunk.len() - self.chunk_pos < self.sample_len && !self.fill_chunk() {
+            // A sample cut short at the end is dropped.
+            self.ended = true;
+            return None;
+        }
+        let bytes = &self.chunk[self.chunk_pos..self.chunk_pos + self.sample_len];
+        self.chunk_pos += self.sample_len;
+        self.position += self.sample_len as u64;
+        Some(to_i16(self.format, bytes))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.samples_remaining() {
+            Some(remaining) => (remaining, Some(remaining)),
+            None => (0, None),
+        }
+    }
+}
+
+impl<R> Source for RawPcmDecoder<R>
+where
+    R: Read + Seek,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let frames = self.data_len? / (self.sample_len as u64 * self.channels as u64);
+        let nanos = frames as u128 * 1_000_000_000 / self.sample_rate as u128;
+        Some(Duration::from_nanos(nanos as u64))
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        let remaining = self.data_len?.saturating_sub(self.position) / self.sample_len as u64;
+        Some(usize::try_from(remaining).unwrap_or(usize::MAX))
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.data_len.is_some()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let Some(data_len) = self.data_len else {
+            return Err(SeekError::NotSupported {
+                underlying_source: std::any::type_name::<Self>(),
+            });
+        };
+        let frame_len = self.sample_len as u64 * self.channels as u64;
+        let frame = pos.as_nanos() * self.sample_rate as u128 / 1_000_000_000;
+        // saturate pos at the end of the data
+        let frames = (data_len / frame_len) as u128;
+        let position = frame.min(frames) as u64 * frame_len;
+
+        self.reader
+            .seek(SeekFrom::Start(self.start + position))
+            .map_err(|err| SeekError::Other(Box::new(err)))?;
+        self.position = position;
+        self.chunk.clear();
+        self.chunk_pos = 0;
+        self.ended = false;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.format
+    }
+}
+
+fn sample_len(format: SampleFormat) -> Option<usize> {
+    match format {
+        SampleFormat::I8 | SampleFormat::U8 => Some(1),
+        SampleFormat::I16 | SampleFormat::U16 => Some(2),
+        SampleFormat::I32 | SampleFormat::U32 | SampleFormat::F32 => Some(4),
+        SampleFormat::I64 | SampleFormat::U64 | SampleFormat::F64 => Some(8),
+        _ => None,
+    }
+}
+
+/// Returns the little endian sample in `bytes` as an i16, keeping its most significant bits.
+fn to_i16(format: SampleFormat, bytes: &[u8]) -> i16 {
+    match format {
+        SampleFormat::I8 => (bytes[0] as i8 as i16) << 8,
+        SampleFormat::U8 => ((bytes[0] ^ 0x80) as i8 as i16) << 8,
+        SampleFormat::I16 => i16::from_le_bytes([bytes[0], bytes[1]]),
+        SampleFormat::U16 => (u16::from_le_bytes([bytes[0], bytes[1]]) ^ 0x8000) as i16,
+        SampleFormat::I32 => (i32::from_le_bytes(bytes.try_into().unwrap()) >> 16) as i16,
+        SampleFormat::U32 => {
+            ((u32::from_le_bytes(bytes.try_into().unwrap()) ^ 0x8000_0000) as i32 >> 16) as i16
+        }
+        SampleFormat::I64 => (i64::from_le_bytes(bytes.try_into().unwrap()) >> 48) as i16,
+        SampleFormat::U64 => {
+            ((u64::from_le_bytes(bytes.try_into().unwrap()) ^ 1 << 63) as i64 >> 48) as i16
+        }
+        SampleFormat::F32 => f32_to_i16(f32::from_le_bytes(bytes.try_into().unwrap())),
+        SampleFormat::F64 => f32_to_i16(f64::from_le_bytes(bytes.try_into().unwrap()) as f32),
+        // Rejected when building the decoder.
+        _ => 0,
+    }
+}
+
+/// Returns a float sample in the range [-1.0, 1.0) as an i16. The values out of range are
+/// clipped.
+fn f32_to_i16(f: f32) -> i16 {
+    (f * 32768.0).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    use super::raw_pcm;
+    use crate::{SampleFormat, Source};
+
+    fn bytes(samples: &[i16]) -> Vec<u8> {
+        samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn reads_every_format() {
+        let cases: [(SampleFormat, Vec<u8>); 6] = [
+            (SampleFormat::U8, vec![0, 128, 255]),
+            (SampleFormat::I8, vec![128, 0, 127]),
+            (SampleFormat::U16, vec![0, 0, 0, 128, 255, 255]),
+            (
+                SampleFormat::I32,
+                [i32::MIN, 0, i32::MAX]
+                    .iter()
+                    .flat_map(|s| s.to_le_bytes())
+                    .collect(),
+            ),
+            (
+                SampleFormat::F32,
+                [-1.0f32, 0.0, 1.0]
+                    .iter()
+                    .flat_map(|s| s.to_le_bytes())
+                    .collect(),
+            ),
+            (
+                SampleFormat::U64,
+                [0, 1 << 63, u64::MAX]
+                    .iter()
+                    .flat_map(|s| s.to_le_bytes())
+                    .collect(),
+            ),
+        ];
+        for (format, data) in cases {
+            let source = raw_pcm(Cursor::new(data), 1, 1000, format).unwrap();
+            let output: Vec<i16> = source.collect();
+            assert_eq!(output[0], i16::MIN, "{format:?}");
+            assert_eq!(output[1], 0, "{format:?}");
+            assert!(output[2] >= i16::MAX - 255, "{format:?} {}", output[2]);
+        }
+    }
+
+    #[test]
+    fn knows_its_length() {
+        let mut data = bytes(&(0..40).collect::<Vec<_>>());
+        // half a sample at the end is not played
+        data.push(1);
+        let mut source = raw_pcm(Cursor::new(data), 2, 10, SampleFormat::I16).unwrap();
+        assert_eq!(source.total_duration(), Some(Duration::from_secs(2)));
+        assert_eq!(source.samples_remaining(), Some(40));
+        source.next();
+        assert_eq!(source.size_hint(), (39, Some(39)));
+        assert_eq!(source.count(), 39);
+    }
+
+    #[test]
+    fn starts_at_the_position_of_the_reader() {
+        let mut reader = Cursor::new(bytes(&[7, 1, 2, 3]));
+        reader.set_position(2);
+        let mut source = raw_pcm(reader, 1, 10, SampleFormat::I16).unwrap();
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(300)));
+        source.try_seek(Duration::from_millis(200)).unwrap();
+        assert_eq!(source.collect::<Vec<_>>(), [3]);
+    }
+
+    #[test]
+    fn seeks_to_the_frame() {
+        let data = bytes(&(0..40).collect::<Vec<_>>());
+        let mut source = raw_pcm(Cursor::new(data), 2, 10, SampleFormat::I16).unwrap();
+        assert!(source.is_seekable());
+        source.by_ref().take(7).count();
+        source.try_seek(Duration::from_millis(550)).unwrap();
+        assert_eq!(source.next(), Some(10));
+        assert_eq!(source.samples_remaining(), Some(29));
+
+        source.try_seek(Duration::from_secs(10)).unwrap();
+        assert_eq!(source.next(), None);
+        source.try_seek(Duration::ZERO).unwrap();
+        assert_eq!(source.next(), Some(0));
+    }
+}
@@ -0,0 +1,125 @@
+use std::io::{Read, Seek, SeekFrom};
+
+/// Formats that can be recognized from the first bytes of a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Signature {
+    Wav,
+    Flac,
+    Ogg,
+    Mp3,
+}
+
+impl Signature {
+    /// The extension usually given to the files of this format.
+    #[cfg(feature = "symphonia")]
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Signature::Wav => "wav",
+            Signature::Flac => "flac",
+            Signature::Ogg => "ogg",
+            Signature::Mp3 => "mp3",
+        }
+    }
+}
+
+/// Tells whether a decoder for `format` is worth trying on a file with the `found` signature.
+///
+/// A file that matches none of the signatures could still be in any format, as some files have
+/// junk before their first header.
+#[inline]
+#[allow(dead_code)]
+pub(crate) fn might_be(found: Option<Signature>, format: Signature) -> bool {
+    found.is_none() || found == Some(format)
+}
+
+/// Reads the first bytes of the stream to recognize its format, then goes back to where the
+/// stream was.
+///
+/// Returns `None` if the bytes match no signature, or if they can not be read.
+pub(crate) fn sniff<R: Read + Seek>(data: &mut R) -> Option<Signature> {
+    let start = data.stream_position().ok()?;
+    let signature = sniff_at(data, start);
+    data.seek(SeekFrom::Start(start)).ok()?;
+    signature
+}
+
+fn sniff_at<R: Read + Seek>(data: &mut R, start: u64) -> Option<Signature> {
+    let mut header = Vec::with_capacity(12);
+    data.by_ref().take(12).read_to_end(&mut header).ok()?;
+
+    match header.as_slice() {
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(Signature::Wav),
+        [b'f', b'L', b'a', b'C', ..] => Some(Signature::Flac),
+        [b'O', b'g', b'g', b'S', ..] => Some(Signature::Ogg),
+        // An ID3v2 tag can be put before any format, the one after it decides.
+        [b'I', b'D', b'3', _, _, flags, size @ ..] if size.len() >= 4 => {
+            let size = size[..4]
+                .iter()
+                .fold(0u64, |size, &byte| size << 7 | (byte & 0x7f) as u64);
+            let footer = if flags & 0x10 != 0 { 10 } else { 0 };
+            let after_tag = start + 10 + size + footer;
+            data.seek(SeekFrom::Start(after_tag)).ok()?;
+            sniff_at(data, after_tag).or(Some(Signature::Mp3))
+        }
+        // The sync word of an MPEG audio frame, with a layer. ADTS AAC has no layer.
+        [0xff, second, ..] if second & 0xe0 == 0xe0 && second & 0x06 != 0 => Some(Signature::Mp3),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Seek};
+
+    use super::{sniff, Signature};
+    use crate::decoder::{Decoder, DecoderError};
+
+    #[test]
+    fn recognizes_the_signatures() {
+        let wav = b"RIFF\x24\0\0\0WAVEfmt ".to_vec();
+        assert_eq!(sniff(&mut Cursor::new(wav)), Some(Signature::Wav));
+        assert_eq!(
+            sniff(&mut Cursor::new(b"fLaC\0\0\0\x22")),
+            Some(Signature::Flac)
+        );
+        assert_eq!(sniff(&mut Cursor::new(b"OggS\0\x02")), Some(Signature::Ogg));
+        assert_eq!(
+            sniff(&mut Cursor::new(b"\xff\xfb\x90\x64")),
+            Some(Signature::Mp3)
+        );
+        // RIFF but not WAVE
+        assert_eq!(sniff(&mut Cursor::new(b"RIFF\x24\0\0\0AVI LIST")), None);
+        // ADTS AAC
+        assert_eq!(sniff(&mut Cursor::new(b"\xff\xf1\x50\x80")), None);
+        assert_eq!(sniff(&mut Cursor::new(b"")), None);
+    }
+
+    #[test]
+    fn looks_past_id3_tags() {
+        let mut tagged = b"ID3\x04\0\0\0\0\0\x02ab".to_vec();
+        assert_eq!(
+            sniff(&mut Cursor::new(tagged.clone())),
+            Some(Signature::Mp3)
+        );
+
+        tagged.extend_from_slice(b"fLaC");
+        assert_eq!(sniff(&mut Cursor::new(tagged)), Some(Signature::Flac));
+    }
+
+    #[test]
+    fn goes_back_to_the_start() {
+        let mut data = Cursor::new(b"xxfLaC\0\0".to_vec());
+        data.set_position(2);
+        assert_eq!(sniff(&mut data), Some(Signature::Flac));
+        assert_eq!(data.stream_position().unwrap(), 2);
+    }
+
+    #[test]
+    fn unknown_data_is_rejected() {
+        let noise: Vec<u8> = (0..4096u32).map(|i| (i * 7) as u8).collect();
+        assert!(matches!(
+            Decoder::new(Cursor::new(noise)),
+            Err(DecoderError::UnrecognizedFormat)
+        ));
+    }
+}
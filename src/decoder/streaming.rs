@@ -0,0 +1,185 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use super::{Decoder, DecoderError};
+use crate::source::{Prefetch, SeekError, UnderrunPolicy};
+use crate::{SampleFormat, Source};
+
+/// Number of bytes at the start of the stream that are kept to detect its format.
+const PROBE_LEN: usize = 1 << 20;
+
+/// Decodes a stream that can only be read forward, such as internet radio.
+///
+/// The format is detected from the first bytes of the stream, like [`Decoder::new`] does. It
+/// works with the formats that can be decoded without seeking, such as MP3 and Ogg Vorbis.
+///
+/// The stream is read and decoded on a background thread, ahead of playback. When the
+/// stream does not deliver data fast enough, the source plays silence until it catches up
+/// instead of blocking the audio thread. Seeking is not supported.
+pub fn decode_streaming<R>(reader: R) -> Result<StreamingDecoder, DecoderError>
+where
+    R: Read + Send + Sync + 'static,
+{
+    decode_streaming_with_policy(reader, UnderrunPolicy::EmitSilence)
+}
+
+/// Same as [`decode_streaming`], but `policy` chooses what is played when the stream does not
+/// deliver data fast enough.
+pub fn decode_streaming_with_policy<R>(
+    reader: R,
+    policy: UnderrunPolicy,
+) -> Result<StreamingDecoder, DecoderError>
+where
+    R: Read + Send + Sync + 'static,
+{
+    let decoder = Decoder::new(ForwardReader::new(reader))?;
+    let sample_format = decoder.sample_format();
+    // Half a second of audio ahead of playback.
+    let capacity = (decoder.sample_rate() as usize * decoder.channels() as usize / 2).max(4096);
+
+    Ok(StreamingDecoder {
+        input: decoder.prefetch_with_policy(capacity, policy),
+        sample_format,
+    })
+}
+
+/// Source of audio samples decoded from a stream, see [`decode_streaming`].
+pub struct StreamingDecoder {
+    input: Prefetch<i16>,
+    sample_format: SampleFormat,
+}
+
+impl StreamingDecoder {
+    /// Returns the number of frames that the stream did not deliver in time.
+    #[inline]
+    pub fn underruns(&self) -> usize {
+        self.input.underruns()
+    }
+}
+
+impl Iterator for StreamingDecoder {
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl Source for StreamingDecoder {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.sample_format
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+}
+
+/// Makes a reader that can only go forward look seekable to the decoders.
+///
+/// The start of the stream is kept so that the decoders can go back to it while they detect
+/// the format. Any other seek backwards fails.
+struct ForwardReader<R> {
+    inner: R,
+    // The first bytes of the stream.
+    start: Vec<u8>,
+    // Number of bytes read from `inner`.
+    read: u64,
+    position: u64,
+}
+
+impl<R> ForwardReader<R> {
+    fn new(inner: R) -> Self {
+        ForwardReader {
+            inner,
+            start: Vec::new(),
+            read: 0,
+            position: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for ForwardReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position < self.read {
+            // Only the start of the stream can be read again, the rest is gone.
+            let kept = self
+                .start
+                .get(self.position as usize..)
+                .filter(|kept| !kept.is_empty())
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::Unsupported, "the stream can not seek")
+                })?;
+            let len = kept.len().min(buf.len());
+            buf[..len].copy_from_slice(&kept[..len]);
+            self.position += len as u64;
+            return Ok(len);
+        }
+
+        let len = self.inner.read(buf)?;
+        if self.start.len() < PROBE_LEN && self.start.len() as u64 == self.read {
+            let keep = len.min(PROBE_LEN - self.start.len());
+            self.start.extend_from_slice(&buf[..keep]);
+        }
+        self.read += len as u64;
+        self.position = self.read;
+        Ok(len)
+    }
+}
+
+impl<R: Read> Seek for ForwardReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(target) => Some(target),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(_) => None,
+        };
+        let unsupported = || io::Error::new(io::ErrorKind::Unsupported, "the stream can not seek");
+        let target = target.ok_or_else(unsupported)?;
+
+        if target < self.read && target >= self.start.len() as u64 {
+            return Err(unsupported());
+        }
+        // Going forward is done by reading and dropping the bytes in between.
+        if target > self.read {
+            self.position = self.read;
+            let skip = target - self.read;
+            let skipped = io::copy(&mut self.by_ref().take(skip), &mut io::sink())?;
+            if skipped < skip {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+        self.position = target;
+        Ok(target)
+    }
+}
@@ -7,16 +7,16 @@ use symphonia::{
         errors::Error,
         formats::{FormatOptions, FormatReader, SeekedTo},
         io::MediaSourceStream,
-        meta::MetadataOptions,
-        probe::Hint,
+        meta::{MetadataOptions, MetadataRevision, StandardTagKey},
+        probe::{Hint, ProbeResult},
         units::{self, Time},
     },
     default::get_probe,
 };
 
-use crate::{source, Source};
+use crate::{source, SampleFormat, Source};
 
-use super::DecoderError;
+use super::{DecoderError, Metadata};
 
 // Decoder errors are not considered fatal.
 // The correct action is to just get a new packet and try again.
@@ -28,8 +28,14 @@ pub(crate) struct SymphoniaDecoder {
     current_frame_offset: usize,
     format: Box<dyn FormatReader>,
     total_duration: Option<Time>,
+    // Number of frames of the track, if known.
+    n_frames: Option<u64>,
+    // Number of samples returned since the start of the track.
+    samples_read: u64,
     buffer: SampleBuffer<i16>,
     spec: SignalSpec,
+    sample_format: SampleFormat,
+    metadata: Metadata,
 }
 
 impl SymphoniaDecoder {
@@ -57,6 +63,10 @@ impl SymphoniaDecoder {
         self.format.into_inner()
     }
 
+    pub(crate) fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
     fn init(
         mss: MediaSourceStream,
         extension: Option<&str>,
@@ -71,6 +81,7 @@ impl SymphoniaDecoder {
         };
         let metadata_opts: MetadataOptions = Default::default();
         let mut probed = get_probe().format(&hint, mss, &format_opts, &metadata_opts)?;
+        let metadata = read_metadata(&mut probed);
 
         let stream = match probed.format.default_track() {
             Some(stream) => stream,
@@ -102,6 +113,7 @@ impl SymphoniaDecoder {
             .time_base
             .zip(stream.codec_params.n_frames)
             .map(|(base, frames)| base.calc_time(frames));
+        let n_frames = stream.codec_params.n_frames;
 
         let mut decode_errors: usize = 0;
         let decoded = loop {
@@ -132,14 +144,28 @@ impl SymphoniaDecoder {
             }
         };
         let spec = decoded.spec().to_owned();
+        let sample_format = match decoded {
+            AudioBufferRef::U8(_) => SampleFormat::U8,
+            AudioBufferRef::U16(_) => SampleFormat::U16,
+            AudioBufferRef::U24(_) | AudioBufferRef::U32(_) => SampleFormat::U32,
+            AudioBufferRef::S8(_) => SampleFormat::I8,
+            AudioBufferRef::S16(_) => SampleFormat::I16,
+            AudioBufferRef::S24(_) | AudioBufferRef::S32(_) => SampleFormat::I32,
+            AudioBufferRef::F32(_) => SampleFormat::F32,
+            AudioBufferRef::F64(_) => SampleFormat::F64,
+        };
         let buffer = SymphoniaDecoder::get_buffer(decoded, &spec);
         Ok(Some(SymphoniaDecoder {
             decoder,
             current_frame_offset: 0,
             format: probed.format,
             total_duration,
+            n_frames,
+            samples_read: 0,
             buffer,
             spec,
+            sample_format,
+            metadata,
         }))
     }
 
@@ -152,6 +178,39 @@ impl SymphoniaDecoder {
     }
 }
 
+// The tags of the container come first, then those found in front of it, such as ID3 tags.
+fn read_metadata(probed: &mut ProbeResult) -> Metadata {
+    let mut metadata = Metadata::default();
+    if let Some(revision) = probed.format.metadata().current() {
+        add_tags(&mut metadata, revision);
+    }
+    if let Some(outer) = probed.metadata.get() {
+        if let Some(revision) = outer.current() {
+            add_tags(&mut metadata, revision);
+        }
+    }
+    metadata
+}
+
+fn add_tags(metadata: &mut Metadata, revision: &MetadataRevision) {
+    for tag in revision.tags() {
+        // The well known tags get the names of the Vorbis comments, whatever the format.
+        let key = match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => "TITLE",
+            Some(StandardTagKey::Artist) => "ARTIST",
+            Some(StandardTagKey::Album) => "ALBUM",
+            Some(StandardTagKey::AlbumArtist) => "ALBUMARTIST",
+            Some(StandardTagKey::Genre) => "GENRE",
+            Some(StandardTagKey::Date) => "DATE",
+            Some(StandardTagKey::TrackNumber) => "TRACKNUMBER",
+            Some(StandardTagKey::Composer) => "COMPOSER",
+            Some(StandardTagKey::Comment) => "COMMENT",
+            _ => &tag.key,
+        };
+        metadata.insert(key, &tag.value.to_string());
+    }
+}
+
 impl Source for SymphoniaDecoder {
     #[inline]
     fn current_frame_len(&self) -> Option<usize> {
@@ -171,7 +230,31 @@ impl Source for SymphoniaDecoder {
     #[inline]
     fn total_duration(&self) -> Option<Duration> {
         self.total_duration
-            .map(|Time { seconds, frac }| Duration::new(seconds, (1f64 / frac) as u32))
+            .map(|Time { seconds, frac }| Duration::new(seconds, (frac * 1e9) as u32))
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        let total = self.n_frames? * self.channels() as u64;
+        Some(total.saturating_sub(self.samples_read) as usize)
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.sample_format
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        let frames = self.samples_read / self.channels() as u64;
+        // Rounded up, a time just before the frame would seek back to the previous one.
+        let nanos = (frames * 1_000_000_000).div_ceil(self.sample_rate() as u64);
+        Some(Duration::from_nanos(nanos))
     }
 
     fn try_seek(&mut self, pos: Duration) -> Result<(), source::SeekError> {
@@ -188,9 +271,6 @@ impl Source for SymphoniaDecoder {
             pos.as_secs_f64().into()
         };
 
-        // make sure the next sample is for the right channel
-        let to_skip = self.current_frame_offset % self.channels() as usize;
-
         let seek_res = self
             .format
             .seek(
@@ -203,7 +283,6 @@ impl Source for SymphoniaDecoder {
             .map_err(SeekError::BaseSeek)?;
 
         self.refine_position(seek_res)?;
-        self.current_frame_offset += to_skip;
 
         Ok(())
     }
@@ -287,6 +366,7 @@ impl SymphoniaDecoder {
         decoded.spec().clone_into(&mut self.spec);
         self.buffer = SymphoniaDecoder::get_buffer(decoded, &self.spec);
         self.current_frame_offset = samples_to_pass as usize * self.channels() as usize;
+        self.samples_read = seek_res.required_ts * self.channels() as u64;
         Ok(())
     }
 }
@@ -327,6 +407,7 @@ impl Iterator for SymphoniaDecoder {
 
         let sample = *self.buffer.samples().get(self.current_frame_offset)?;
         self.current_frame_offset += 1;
+        self.samples_read += 1;
 
         Some(sample)
     }
@@ -1,8 +1,9 @@
 use std::io::{Read, Seek, SeekFrom};
 use std::time::Duration;
 
+use super::Metadata;
 use crate::source::SeekError;
-use crate::Source;
+use crate::{SampleFormat, Source};
 
 use lewton::inside_ogg::OggStreamReader;
 
@@ -14,6 +15,7 @@ where
     stream_reader: OggStreamReader<R>,
     current_data: Vec<i16>,
     next: usize,
+    metadata: Metadata,
 }
 
 impl<R> VorbisDecoder<R>
@@ -30,6 +32,12 @@ where
         Ok(Self::from_stream_reader(stream_reader))
     }
     pub fn from_stream_reader(mut stream_reader: OggStreamReader<R>) -> Self {
+        let metadata = stream_reader
+            .comment_hdr
+            .comment_list
+            .iter()
+            .cloned()
+            .collect();
         let mut data = match stream_reader.read_dec_packet_itl() {
             Ok(Some(d)) => d,
             _ => Vec::new(),
@@ -45,11 +53,15 @@ where
             stream_reader,
             current_data: data,
             next: 0,
+            metadata,
         }
     }
     pub fn into_inner(self) -> OggStreamReader<R> {
         self.stream_reader
     }
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
 }
 
 impl<R> Source for VorbisDecoder<R>
@@ -76,6 +88,11 @@ where
         None
     }
 
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        SampleFormat::F32
+    }
+
     /// seek is broken, https://github.com/RustAudio/lewton/issues/73.
     // We could work around it by:
     //  - using unsafe to create an instance of Self
@@ -4,7 +4,7 @@ use std::time::Duration;
 use crate::source::SeekError;
 use crate::Source;
 
-use hound::{SampleFormat, WavReader};
+use hound::{SampleFormat, WavReader, WavSpec};
 
 /// Decoder for the WAV format.
 pub struct WavDecoder<R>
@@ -129,27 +129,49 @@ where
         Some(self.total_duration)
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        Some(self.reader.len())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> crate::SampleFormat {
+        let spec = self.reader.reader.spec();
+        match (spec.sample_format, spec.bits_per_sample) {
+            (SampleFormat::Float, _) => crate::SampleFormat::F32,
+            // 8 bit wav files are unsigned
+            (SampleFormat::Int, 8) => crate::SampleFormat::U8,
+            (SampleFormat::Int, 16) => crate::SampleFormat::I16,
+            (SampleFormat::Int, _) => crate::SampleFormat::I32,
+        }
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        let frames = (self.reader.samples_read / self.channels as u32) as u64;
+        // Rounded up, a time just before the frame would seek back to the previous one.
+        let nanos = (frames * 1_000_000_000).div_ceil(self.sample_rate as u64);
+        Some(Duration::from_nanos(nanos))
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         let file_len = self.reader.reader.duration();
 
-        let new_pos = pos.as_secs_f32() * self.sample_rate() as f32;
-        let new_pos = new_pos as u32;
-        let new_pos = new_pos.min(file_len); // saturate pos at the end of the source
-
-        // make sure the next sample is for the right channel
-        let to_skip = self.reader.samples_read % self.channels() as u32;
+        // Computed exactly, so that seeking to the `playback_pos` stays on the same frame.
+        let new_pos = pos.as_nanos() * self.sample_rate as u128 / 1_000_000_000;
+        let new_pos = new_pos.min(file_len as u128) as u32; // saturate pos at the end of the source
 
         self.reader
             .reader
             .seek(new_pos)
             .map_err(SeekError::HoundDecoder)?;
         self.reader.samples_read = new_pos * self.channels() as u32;
-
-        for _ in 0..to_skip {
-            self.next();
-        }
-
         Ok(())
     }
 }
@@ -180,22 +202,31 @@ where
 {
     let stream_pos = data.stream_position().unwrap();
 
-    if WavReader::new(data.by_ref()).is_err() {
-        data.seek(SeekFrom::Start(stream_pos)).unwrap();
-        return false;
-    }
+    let is_wave = match WavReader::new(data.by_ref()) {
+        Ok(reader) => is_supported(reader.spec()),
+        Err(_) => false,
+    };
 
     data.seek(SeekFrom::Start(stream_pos)).unwrap();
-    true
+    is_wave
+}
+
+/// Returns true if the samples of this format can be converted by `SamplesIterator`.
+fn is_supported(spec: WavSpec) -> bool {
+    matches!(
+        (spec.sample_format, spec.bits_per_sample),
+        (SampleFormat::Float, 32) | (SampleFormat::Int, 8 | 16 | 24 | 32)
+    )
 }
 
 /// Returns a 32 bit WAV float as an i16. WAV floats are typically in the range of
-/// [-1.0, 1.0] while i16s are in the range [-32768, 32767]. Note that this
-/// function definitely causes precision loss but hopefully this isn't too
-/// audiable when actually playing?
+/// [-1.0, 1.0) while i16s are in the range [-32768, 32767]. The scale is the same
+/// as the integer conversions below, so that 0.5 becomes 16384 like a 24 bit
+/// 0x400000 does. Note that this function definitely causes precision loss but
+/// hopefully this isn't too audiable when actually playing?
 fn f32_to_i16(f: f32) -> i16 {
     // prefer to clip the input rather than be excessively loud.
-    (f.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+    (f * 32768.0).clamp(i16::MIN as f32, i16::MAX as f32) as i16
 }
 
 /// Returns an 8-bit WAV int as an i16. This scales the sample value by a factor
@@ -1,11 +1,21 @@
 //! Mixer that plays multiple sounds at the same time.
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::fmt;
+use std::sync::atomic::{
+    AtomicBool, AtomicIsize, AtomicU16, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering,
+};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use crate::source::{SeekError, Source, UniformSourceIterator};
-use crate::Sample;
+use crate::source::{
+    ring, Empty, RingReader, RingWriter, SeekError, Source, UniformSourceIterator,
+};
+use crate::{Sample, WideSample};
+
+#[cfg(feature = "crossbeam-channel")]
+use crossbeam_channel::{unbounded as channel, Receiver, Sender};
+#[cfg(not(feature = "crossbeam-channel"))]
+use std::sync::mpsc::{channel, Receiver, Sender};
 
 /// Builds a new mixer.
 ///
@@ -13,6 +23,10 @@ use crate::Sample;
 /// added to the mixer will be converted to these values.
 ///
 /// After creating a mixer, you can add new sounds with the controller.
+///
+/// # Panic
+///
+/// Panics if `channels` or `sample_rate` is 0.
 pub fn mixer<S>(
     channels: u16,
     sample_rate: u32,
@@ -20,30 +34,155 @@ pub fn mixer<S>(
 where
     S: Sample + Send + 'static,
 {
+    build_mixer(channels, sample_rate, MixMode::default(), None, 16)
+}
+
+/// Builds a new mixer that plays silence instead of ending when it has nothing to play.
+///
+/// This suits a mixer that feeds a device for as long as the program runs: sources added
+/// after a silence start right away. See [`DynamicMixer::with_silence_when_idle`].
+pub fn mixer_keep_alive<S>(
+    channels: u16,
+    sample_rate: u32,
+) -> (Arc<DynamicMixerController<S>>, DynamicMixer<S>)
+where
+    S: Sample + Send + 'static,
+{
+    let (input, output) = mixer(channels, sample_rate);
+    (input, output.with_silence_when_idle())
+}
+
+/// Builds a new mixer that adds the sounds together according to `mode`.
+///
+/// See [`mixer`] for the other parameters.
+#[inline]
+pub fn mixer_with_mode<S>(
+    channels: u16,
+    sample_rate: u32,
+    mode: MixMode,
+) -> (Arc<DynamicMixerController<S>>, DynamicMixer<S>)
+where
+    S: WideSample + Send + 'static,
+{
+    let sum_wide = match mode {
+        MixMode::Wide => Some(DynamicMixer::<S>::sum_wide as fn(&mut DynamicMixer<S>) -> S),
+        _ => None,
+    };
+    build_mixer(channels, sample_rate, mode, sum_wide, 16)
+}
+
+/// Builds a new mixer with room for `max_voices` sources playing or waiting to start.
+///
+/// The mixer keeps its sources in lists that grow the first time many of them play at once,
+/// which allocates memory on the audio thread. Reserving the room beforehand means that
+/// playing up to `max_voices` sources never allocates in [`next`](Iterator::next). More
+/// sources still play fine, growing the lists again as needed.
+///
+/// See [`mixer`] for the other parameters.
+#[inline]
+pub fn mixer_with_capacity<S>(
+    channels: u16,
+    sample_rate: u32,
+    max_voices: usize,
+) -> (Arc<DynamicMixerController<S>>, DynamicMixer<S>)
+where
+    S: Sample + Send + 'static,
+{
+    build_mixer(channels, sample_rate, MixMode::default(), None, max_voices)
+}
+
+fn build_mixer<S>(
+    channels: u16,
+    sample_rate: u32,
+    mode: MixMode,
+    sum_wide: Option<fn(&mut DynamicMixer<S>) -> S>,
+    capacity: usize,
+) -> (Arc<DynamicMixerController<S>>, DynamicMixer<S>)
+where
+    S: Sample + Send + 'static,
+{
+    assert!(channels >= 1, "the mixer needs at least one channel");
+    assert!(
+        sample_rate >= 1,
+        "the sample rate of the mixer can not be 0"
+    );
+
+    let (pending_tx, pending_rx) = channel();
     let input = Arc::new(DynamicMixerController {
         has_pending: AtomicBool::new(false),
-        pending_sources: Mutex::new(Vec::new()),
-        channels,
-        sample_rate,
+        pending_sources: pending_tx,
+        solo_count: Arc::new(AtomicIsize::new(0)),
+        pending_send_effect: Mutex::new(None),
+        format_changed: AtomicBool::new(false),
+        channels: AtomicU16::new(channels),
+        sample_rate: AtomicU32::new(sample_rate),
+        sample_count: AtomicUsize::new(0),
     });
 
     let output = DynamicMixer {
-        current_sources: Vec::with_capacity(16),
+        current_sources: Vec::with_capacity(capacity),
         input: input.clone(),
+        channels,
+        sample_rate,
         sample_count: 0,
-        still_pending: vec![],
-        still_current: vec![],
+        frame_origin: 0,
+        pending_sources: pending_rx,
+        // Swapped with `still_pending` whenever the mixer starts sources.
+        pending: Vec::with_capacity(capacity),
+        still_pending: Vec::with_capacity(capacity),
+        still_current: Vec::with_capacity(capacity),
+        values: Vec::with_capacity(capacity),
+        send_effect: None,
+        mode,
+        sum_wide,
+        silence_when_idle: false,
+        clip_counter: None,
+        mono_sum: None,
+        mono_acc: 0.0,
+        voice_limit: None,
     };
 
     (input, output)
 }
 
+/// How the mixer handles sums that do not fit in the range of the samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MixMode {
+    /// The sum is clamped to the loudest value the samples can hold, see
+    /// [`Sample::saturating_add`].
+    #[default]
+    Saturate,
+    /// The sum wraps around to the other end of the range, see [`Sample::wrapping_add`].
+    Wrap,
+    /// The mixer panics as soon as the sum would clip. Useful in tests to make sure the volume
+    /// of the sounds is staged properly.
+    DebugPanic,
+    /// The sum is made in [`WideSample::Wide`] and clamped only once all the sources are
+    /// added, so that sources that are loud together but cancel out do not clip. Integer
+    /// samples are added in `i32` without going through `f32`, float samples in their own
+    /// type. Only available with [`mixer_with_mode`], for samples that implement
+    /// [`WideSample`].
+    Wide,
+}
+
 /// The input of the mixer.
 pub struct DynamicMixerController<S> {
+    // Set when a source is sent, or when the mixer has something else to pick up.
     has_pending: AtomicBool,
-    pending_sources: Mutex<Vec<Box<dyn Source<Item = S> + Send>>>,
-    channels: u16,
-    sample_rate: u32,
+    // Sending never blocks, so adding a source never holds up the audio thread.
+    pending_sources: Sender<Track<S>>,
+    // Number of sources that are soloed and still playing.
+    solo_count: Arc<AtomicIsize>,
+    // The send effect that replaces the current one, if any.
+    pending_send_effect: Mutex<Option<SendEffect<S>>>,
+    // The output format changed and the mixer has not switched to it yet.
+    format_changed: AtomicBool,
+    // The output format. The mixer converts the sources that were added for another one.
+    channels: AtomicU16,
+    sample_rate: AtomicU32,
+    // The number of samples the mixer produced, so that a source starts where the mixer was
+    // when it was added.
+    sample_count: AtomicUsize,
 }
 
 impl<S> DynamicMixerController<S>
@@ -51,100 +190,432 @@ where
     S: Sample + Send + 'static,
 {
     /// Adds a new source to mix to the existing ones.
+    ///
+    /// The source starts on the frame the mixer is at when this is called, or the next one if
+    /// the mixer is in the middle of a frame, so that sources added at a known distance stay
+    /// exactly that far apart. A source always plays whole: if the mixer already played some
+    /// samples of that frame before it picked the source up, the source starts with the next
+    /// frame instead.
+    ///
+    /// The returned handle can be used to mute or solo the source while it plays.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the source has 0 channels or a sample rate of 0.
     #[inline]
-    pub fn add<T>(&self, source: T)
+    pub fn add<T>(&self, source: T) -> SourceHandle
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        // Rounded up to the next frame by the mixer, which knows where its frames start.
+        self.add_at(source, self.sample_count())
+    }
+
+    /// Adds a new source that starts playing once the mixer has produced `start_sample`
+    /// samples, counting the samples of all the channels.
+    ///
+    /// The source starts on the first frame at or after `start_sample`, or immediately if the
+    /// mixer is already past it.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the source has 0 channels or a sample rate of 0.
+    #[inline]
+    pub fn add_at<T>(&self, source: T, start_sample: usize) -> SourceHandle
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        self.add_track(source, start_sample, 0)
+    }
+
+    /// Adds a new source like [`add`](Self::add), with a priority for when the mixer is
+    /// limited to a number of voices, see [`DynamicMixer::with_voice_limit`]. The sources
+    /// added with `add` have a priority of 0.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the source has 0 channels or a sample rate of 0.
+    #[inline]
+    pub fn add_with_priority<T>(&self, source: T, priority: i32) -> SourceHandle
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        self.add_track(source, self.sample_count(), priority)
+    }
+
+    fn add_track<T>(&self, source: T, start_sample: usize, priority: i32) -> SourceHandle
     where
         T: Source<Item = S> + Send + 'static,
     {
-        let uniform_source = UniformSourceIterator::new(source, self.channels, self.sample_rate);
-        self.pending_sources
-            .lock()
-            .unwrap()
-            .push(Box::new(uniform_source) as Box<_>);
+        // Caught here rather than on the audio thread.
+        assert!(
+            source.channels() >= 1,
+            "a source added to the mixer needs at least one channel"
+        );
+        assert!(
+            source.sample_rate() >= 1,
+            "a source added to the mixer can not have a sample rate of 0"
+        );
+        let state = Arc::new(SourceState {
+            flags: AtomicU8::new(0),
+            send: AtomicU32::new(0.0f32.to_bits()),
+            fade_out: AtomicU64::new(0),
+            on_done: Mutex::new(None),
+        });
+        let (channels, sample_rate) = self.output_format();
+        let uniform_source = UniformSourceIterator::new(source, channels, sample_rate);
+        let track = Track {
+            source: Box::new(uniform_source) as Box<_>,
+            state: state.clone(),
+            audible: true,
+            send: 0.0,
+            start_sample,
+            priority,
+            fade_left: None,
+            fade_len: 0,
+            gain: 1.0,
+        };
+        // Fails once the mixer is dropped, the source is then never played.
+        let _ = self.pending_sources.send(track);
         self.has_pending.store(true, Ordering::SeqCst); // TODO: can we relax this ordering?
+
+        SourceHandle {
+            state,
+            solo_count: self.solo_count.clone(),
+        }
+    }
+
+    /// Sets the effect shared by all the sources of the mixer, typically a reverb.
+    ///
+    /// `effect` receives the sum of the sources weighted by their send amount, see
+    /// [`SourceHandle::set_reverb_send`]. Its output is added to the mix, on top of the
+    /// sources themselves. This replaces the previous send effect, if any.
+    ///
+    /// The mixer hands the sum over to the effect a whole frame at a time, so the effect hears
+    /// the sources one frame after they play.
+    pub fn set_send_effect<F, E>(&self, effect: F)
+    where
+        F: FnOnce(Box<dyn Source<Item = S> + Send>) -> E,
+        E: Source<Item = S> + Send + 'static,
+    {
+        let (channels, sample_rate) = self.output_format();
+        // The effect reads a frame while the mixer sums the next one.
+        let (writer, reader) = ring(2 * channels as usize);
+        let input = SendInput {
+            input: reader,
+            frame: Vec::with_capacity(channels as usize),
+            frame_pos: 0,
+            channels,
+            sample_rate,
+        };
+        let effect = SendEffect {
+            effect: Box::new(effect(Box::new(input))),
+            output: writer,
+            frame: Vec::with_capacity(channels as usize),
+            channels: channels as usize,
+        };
+        *self.pending_send_effect.lock().unwrap() = Some(effect);
+        self.has_pending.store(true, Ordering::SeqCst);
+    }
+
+    /// Adds a bus, a group of sources that are mixed together before being mixed with the
+    /// other sources of this mixer.
+    ///
+    /// The bus keeps playing, silently if needed, until its controller is dropped and all its
+    /// sources have finished.
+    #[inline]
+    pub fn add_bus(&self) -> MixerBusController<S> {
+        self.add_bus_with_effect(|mix| mix)
+    }
+
+    /// Adds a bus whose mix goes through `effect` before its volume is applied.
+    ///
+    /// `effect` receives the sum of the sources of the bus, for example to add some reverb
+    /// to all of them at once.
+    pub fn add_bus_with_effect<F, E>(&self, effect: F) -> MixerBusController<S>
+    where
+        F: FnOnce(Box<dyn Source<Item = S> + Send>) -> E,
+        E: Source<Item = S> + Send + 'static,
+    {
+        let (channels, sample_rate) = self.output_format();
+        let (mixer, mix) = mixer(channels, sample_rate);
+        let volume = Arc::new(AtomicU32::new(1.0f32.to_bits()));
+        let mix = BusMix {
+            mix,
+            volume: volume.clone(),
+        };
+        let bus = MixerBus {
+            input: Box::new(effect(Box::new(mix))),
+            volume: volume.clone(),
+            gain: 1.0,
+            current_channel: 0,
+        };
+        let handle = self.add(bus);
+
+        MixerBusController {
+            mixer,
+            volume,
+            handle,
+        }
+    }
+
+    /// Changes the number of channels and the sample rate of the output of the mixer.
+    ///
+    /// The sources that are playing or waiting to play are converted to the new format, no
+    /// sound is interrupted. The mixer switches at the start of its next frame. The send
+    /// effect, if any, is removed since it was built for the previous format: set it again
+    /// with [`set_send_effect`](Self::set_send_effect) afterwards.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `channels` or `sample_rate` is 0.
+    pub fn set_output_format(&self, channels: u16, sample_rate: u32) {
+        assert!(channels >= 1, "the mixer needs at least one channel");
+        assert!(
+            sample_rate >= 1,
+            "the sample rate of the mixer can not be 0"
+        );
+
+        self.channels.store(channels, Ordering::SeqCst);
+        self.sample_rate.store(sample_rate, Ordering::SeqCst);
+        *self.pending_send_effect.lock().unwrap() = None;
+        self.format_changed.store(true, Ordering::SeqCst);
+        self.has_pending.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns the number of samples the mixer has produced so far, counting the samples of all
+    /// the channels. This is what [`add_at`](Self::add_at) counts from.
+    #[inline]
+    pub fn sample_count(&self) -> usize {
+        self.sample_count.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of channels and the sample rate new sources are converted to.
+    #[inline]
+    pub fn output_format(&self) -> (u16, u32) {
+        (
+            self.channels.load(Ordering::SeqCst),
+            self.sample_rate.load(Ordering::SeqCst),
+        )
     }
 }
 
-/// The output of the mixer. Implements `Source`.
-pub struct DynamicMixer<S> {
-    // The current iterator that produces samples.
-    current_sources: Vec<Box<dyn Source<Item = S> + Send>>,
+const MUTED: u8 = 1;
+const SOLO: u8 = 2;
+// The source has finished playing, its flags can not change anymore.
+const DONE: u8 = 4;
+// The source was stopped to make room for another one.
+const STOLEN: u8 = 8;
+// The source is removed once it has faded out.
+const FADING: u8 = 16;
 
-    // The pending sounds.
-    input: Arc<DynamicMixerController<S>>,
+// State of a source shared between the mixer and the handle.
+struct SourceState {
+    flags: AtomicU8,
+    // The send amount, as the bits of an `f32`.
+    send: AtomicU32,
+    // How long the source fades out for before it is removed, in nanoseconds.
+    fade_out: AtomicU64,
+    // Called when the source finishes.
+    on_done: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+}
 
-    // The number of samples produced so far.
-    sample_count: usize,
+impl fmt::Debug for SourceState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SourceState")
+            .field("flags", &self.flags)
+            .field("send", &self.send)
+            .finish_non_exhaustive()
+    }
+}
 
-    // A temporary vec used in start_pending_sources.
-    still_pending: Vec<Box<dyn Source<Item = S> + Send>>,
+/// Controls a source that was added to a mixer.
+///
+/// Changes are applied at the start of the next frame, so every channel of a frame is
+/// affected at the same time.
+#[derive(Clone, Debug)]
+pub struct SourceHandle {
+    state: Arc<SourceState>,
+    solo_count: Arc<AtomicIsize>,
+}
 
-    // A temporary vec used in sum_current_sources.
-    still_current: Vec<Box<dyn Source<Item = S> + Send>>,
+impl SourceHandle {
+    /// Silences the source, or lets it be heard again. A muted source keeps playing.
+    #[inline]
+    pub fn set_muted(&self, muted: bool) {
+        self.set_flag(MUTED, muted);
+    }
+
+    /// Returns true if the source is muted.
+    #[inline]
+    pub fn is_muted(&self) -> bool {
+        self.state.flags.load(Ordering::SeqCst) & MUTED != 0
+    }
+
+    /// Solos the source, or stops soloing it.
+    ///
+    /// While at least one source of the mixer is soloed, only the soloed sources that are not
+    /// muted can be heard.
+    #[inline]
+    pub fn set_solo(&self, solo: bool) {
+        if self.set_flag(SOLO, solo) {
+            self.solo_count
+                .fetch_add(if solo { 1 } else { -1 }, Ordering::SeqCst);
+        }
+    }
+
+    /// Returns true if the source is soloed.
+    #[inline]
+    pub fn is_solo(&self) -> bool {
+        self.state.flags.load(Ordering::SeqCst) & SOLO != 0
+    }
+
+    /// Sets how much of the source is fed to the send effect of the mixer, see
+    /// [`DynamicMixerController::set_send_effect`]. The default is `0.0`.
+    ///
+    /// This does not change how loud the source itself is in the mix.
+    #[inline]
+    pub fn set_reverb_send(&self, amount: f32) {
+        self.state.send.store(amount.to_bits(), Ordering::SeqCst);
+    }
+
+    /// Returns how much of the source is fed to the send effect of the mixer.
+    #[inline]
+    pub fn reverb_send(&self) -> f32 {
+        f32::from_bits(self.state.send.load(Ordering::SeqCst))
+    }
+
+    /// Fades the source out over `fade`, then removes it from the mixer as if it had
+    /// finished, without the click of cutting it off.
+    ///
+    /// The volume goes down linearly, frame by frame, from the start of the next frame. A
+    /// `fade` of zero removes the source at the start of the next frame. Once the fade has
+    /// started, calling this again does not change it.
+    #[inline]
+    pub fn remove_faded(&self, fade: Duration) {
+        let nanos = u64::try_from(fade.as_nanos()).unwrap_or(u64::MAX);
+        self.state.fade_out.store(nanos, Ordering::SeqCst);
+        self.set_flag(FADING, true);
+    }
+
+    /// Returns true if the source has finished playing.
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.state.flags.load(Ordering::SeqCst) & DONE != 0
+    }
+
+    /// Returns true if the mixer stopped the source to make room for another one, see
+    /// [`DynamicMixer::with_voice_limit`]. It is then also done.
+    #[inline]
+    pub fn was_stolen(&self) -> bool {
+        self.state.flags.load(Ordering::SeqCst) & STOLEN != 0
+    }
+
+    /// Calls `callback` once the source has finished playing and is removed from the mixer.
+    ///
+    /// The callback runs on the audio thread, right when the source ends, so it must be quick
+    /// and must not block: no locks that can be held for a while, no I/O, no allocation if it
+    /// can be avoided. Sending a message on a channel is fine. If the source has already
+    /// finished, it is called immediately on the current thread instead. This replaces the
+    /// previous callback, if any.
+    pub fn on_done<F>(&self, callback: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let mut on_done = self.state.on_done.lock().unwrap();
+        if self.is_done() {
+            drop(on_done);
+            callback();
+        } else {
+            *on_done = Some(Box::new(callback));
+        }
+    }
+
+    // Returns true if the flag changed.
+    fn set_flag(&self, flag: u8, value: bool) -> bool {
+        let previous = self
+            .state
+            .flags
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |flags| {
+                if flags & DONE != 0 {
+                    None
+                } else if value {
+                    Some(flags | flag)
+                } else {
+                    Some(flags & !flag)
+                }
+            });
+        matches!(previous, Ok(flags) if (flags & flag != 0) != value)
+    }
 }
 
-impl<S> Source for DynamicMixer<S>
+/// Controls a bus that was added to a mixer.
+///
+/// Dropping the controller lets the bus end once its sources have finished.
+pub struct MixerBusController<S> {
+    mixer: Arc<DynamicMixerController<S>>,
+    // The volume of the bus, as the bits of an `f32`. Also tells the bus that the controller
+    // still exists.
+    volume: Arc<AtomicU32>,
+    handle: SourceHandle,
+}
+
+impl<S> MixerBusController<S>
 where
     S: Sample + Send + 'static,
 {
+    /// Adds a new source to the bus.
     #[inline]
-    fn current_frame_len(&self) -> Option<usize> {
-        None
+    pub fn add<T>(&self, source: T) -> SourceHandle
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        self.mixer.add(source)
     }
 
+    /// Adds a bus inside of this one, see [`DynamicMixerController::add_bus`].
     #[inline]
-    fn channels(&self) -> u16 {
-        self.input.channels
+    pub fn add_bus(&self) -> MixerBusController<S> {
+        self.mixer.add_bus()
     }
 
+    /// Adds a bus inside of this one, see [`DynamicMixerController::add_bus_with_effect`].
     #[inline]
-    fn sample_rate(&self) -> u32 {
-        self.input.sample_rate
+    pub fn add_bus_with_effect<F, E>(&self, effect: F) -> MixerBusController<S>
+    where
+        F: FnOnce(Box<dyn Source<Item = S> + Send>) -> E,
+        E: Source<Item = S> + Send + 'static,
+    {
+        self.mixer.add_bus_with_effect(effect)
     }
 
+    /// Changes the volume of the bus. It is applied at the start of the next frame.
     #[inline]
-    fn total_duration(&self) -> Option<Duration> {
-        None
+    pub fn set_volume(&self, value: f32) {
+        self.volume.store(value.to_bits(), Ordering::SeqCst);
     }
 
+    /// Returns the volume of the bus.
     #[inline]
-    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
-        Err(SeekError::NotSupported {
-            underlying_source: std::any::type_name::<Self>(),
-        })
-
-        // uncomment when #510 is implemented (query position of playback)
+    pub fn volume(&self) -> f32 {
+        f32::from_bits(self.volume.load(Ordering::SeqCst))
+    }
 
-        // let mut org_positions = Vec::with_capacity(self.current_sources.len());
-        // let mut encounterd_err = None;
-        //
-        // for source in &mut self.current_sources {
-        //     let pos = /* source.playback_pos() */ todo!();
-        //     if let Err(e) = source.try_seek(pos) {
-        //         encounterd_err = Some(e);
-        //         break;
-        //     } else {
-        //         // store pos in case we need to roll back
-        //         org_positions.push(pos);
-        //     }
-        // }
-        //
-        // if let Some(e) = encounterd_err {
-        //     // rollback seeks that happend before err
-        //     for (pos, source) in org_positions
-        //         .into_iter()
-        //         .zip(self.current_sources.iter_mut())
-        //     {
-        //         source.try_seek(pos)?;
-        //     }
-        //     Err(e)
-        // } else {
-        //     Ok(())
-        // }
+    /// Returns the handle of the bus in the mixer it was added to, to mute or solo it.
+    #[inline]
+    pub fn handle(&self) -> &SourceHandle {
+        &self.handle
     }
 }
 
-impl<S> Iterator for DynamicMixer<S>
+// The sum of the sources of a bus, silence while the bus has no sources but its controller
+// exists.
+struct BusMix<S> {
+    mix: DynamicMixer<S>,
+    volume: Arc<AtomicU32>,
+}
+
+impl<S> Iterator for BusMix<S>
 where
     S: Sample + Send + 'static,
 {
@@ -152,18 +623,11 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<S> {
-        if self.input.has_pending.load(Ordering::SeqCst) {
-            self.start_pending_sources();
-        }
-
-        self.sample_count += 1;
-
-        let sum = self.sum_current_sources();
-
-        if self.current_sources.is_empty() {
-            None
-        } else {
-            Some(sum)
+        match self.mix.next() {
+            Some(sample) => Some(sample),
+            // the bus is the only other owner of the volume
+            None if Arc::strong_count(&self.volume) > 2 => Some(S::zero_value()),
+            None => None,
         }
     }
 
@@ -173,131 +637,1754 @@ where
     }
 }
 
-impl<S> DynamicMixer<S>
+impl<S> Source for BusMix<S>
 where
     S: Sample + Send + 'static,
 {
-    // Samples from the #next() function are interlaced for each of the channels.
-    // We need to ensure we start playing sources so that their samples are
-    // in-step with the modulo of the samples produced so far. Otherwise, the
-    // sound will play on the wrong channels, e.g. left / right will be reversed.
-    fn start_pending_sources(&mut self) {
-        let mut pending = self.input.pending_sources.lock().unwrap(); // TODO: relax ordering?
-
-        for source in pending.drain(..) {
-            let in_step = self.sample_count % source.channels() as usize == 0;
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
 
-            if in_step {
-                self.current_sources.push(source);
-            } else {
-                self.still_pending.push(source);
-            }
-        }
-        std::mem::swap(&mut self.still_pending, &mut pending);
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.mix.channels()
+    }
 
-        let has_pending = !pending.is_empty();
-        self.input.has_pending.store(has_pending, Ordering::SeqCst); // TODO: relax ordering?
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.mix.sample_rate()
     }
 
-    fn sum_current_sources(&mut self) -> S {
-        let mut sum = S::zero_value();
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
 
-        for mut source in self.current_sources.drain(..) {
-            if let Some(value) = source.next() {
-                sum = sum.saturating_add(value);
-                self.still_current.push(source);
-            }
-        }
-        std::mem::swap(&mut self.still_current, &mut self.current_sources);
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.mix.is_seekable()
+    }
 
-        sum
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.mix.try_seek(pos)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::buffer::SamplesBuffer;
-    use crate::dynamic_mixer;
-    use crate::source::Source;
+// The output of a bus, added to the mixer as a regular source.
+struct MixerBus<S> {
+    input: Box<dyn Source<Item = S> + Send>,
+    volume: Arc<AtomicU32>,
+    // Volume applied to the current frame.
+    gain: f32,
+    current_channel: u16,
+}
 
-    #[test]
-    fn basic() {
-        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+impl<S> Iterator for MixerBus<S>
+where
+    S: Sample + Send + 'static,
+{
+    type Item = S;
 
-        tx.add(SamplesBuffer::new(1, 48000, vec![10i16, -10, 10, -10]));
-        tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5, 5, 5]));
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        // The volume only changes between frames so all channels stay in sync.
+        if self.current_channel == 0 {
+            self.gain = f32::from_bits(self.volume.load(Ordering::SeqCst));
+        }
+        self.current_channel += 1;
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
 
-        assert_eq!(rx.channels(), 1);
-        assert_eq!(rx.sample_rate(), 48000);
-        assert_eq!(rx.next(), Some(15));
-        assert_eq!(rx.next(), Some(-5));
-        assert_eq!(rx.next(), Some(15));
-        assert_eq!(rx.next(), Some(-5));
-        assert_eq!(rx.next(), None);
+        self.input.next().map(|sample| sample.amplify(self.gain))
     }
 
-    #[test]
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<S> Source for MixerBus<S>
+where
+    S: Sample + Send + 'static,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+// The send effect, along with the end of the ring that feeds it.
+struct SendEffect<S> {
+    effect: Box<dyn Source<Item = S> + Send>,
+    output: RingWriter<S>,
+    // The sends of the frame being played, handed over once the frame is whole.
+    frame: Vec<S>,
+    channels: usize,
+}
+
+impl<S> SendEffect<S>
+where
+    S: Sample,
+{
+    // Takes the sum of the sends for one sample, and returns the output of the effect.
+    #[inline]
+    fn next(&mut self, send: S) -> Option<S> {
+        let wet = self.effect.next();
+        self.frame.push(send);
+        if self.frame.len() == self.channels {
+            self.output.push(&self.frame);
+            self.frame.clear();
+        }
+        wet
+    }
+}
+
+// The input of the send effect, silence when the mixer has not produced anything new.
+struct SendInput<S> {
+    input: RingReader<S>,
+    frame: Vec<S>,
+    frame_pos: usize,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<S> Iterator for SendInput<S>
+where
+    S: Sample,
+{
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        if self.frame_pos >= self.frame.len() {
+            self.frame_pos = 0;
+            let channels = self.channels as usize;
+            if !self.input.pop_frame(&mut self.frame, channels) {
+                self.frame.clear();
+                self.frame.resize(channels, S::zero_value());
+            }
+        }
+        let sample = self.frame[self.frame_pos];
+        self.frame_pos += 1;
+        Some(sample)
+    }
+}
+
+impl<S> Source for SendInput<S>
+where
+    S: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// A source of the mixer along with the state shared with its handle.
+struct Track<S> {
+    source: Box<dyn Source<Item = S> + Send>,
+    state: Arc<SourceState>,
+    // Whether the source can be heard during the current frame.
+    audible: bool,
+    // The send amount for the current frame.
+    send: f32,
+    // The source is not played before the mixer reaches this sample.
+    start_sample: usize,
+    // Sources of a lower priority are stopped first when there are too many.
+    priority: i32,
+    // The frames left to play while fading out, and how many frames the fade lasts.
+    fade_left: Option<u64>,
+    fade_len: u64,
+    // The volume for the current frame while fading out.
+    gain: f32,
+}
+
+impl<S> Track<S> {
+    fn update(&mut self, solo_active: bool) {
+        let flags = self.state.flags.load(Ordering::SeqCst);
+        self.audible = flags & MUTED == 0 && (!solo_active || flags & SOLO != 0);
+        self.send = f32::from_bits(self.state.send.load(Ordering::SeqCst));
+    }
+
+    // Moves the fade out on to the next frame. Returns false once the source has faded out.
+    fn advance_fade(&mut self, sample_rate: u32) -> bool {
+        let left = match self.fade_left {
+            Some(left) => left,
+            None if self.state.flags.load(Ordering::SeqCst) & FADING == 0 => return true,
+            None => {
+                let fade = Duration::from_nanos(self.state.fade_out.load(Ordering::SeqCst));
+                self.fade_len = (fade.as_secs_f64() * sample_rate as f64).round() as u64;
+                self.fade_len
+            }
+        };
+        if left == 0 {
+            return false;
+        }
+        self.gain = left as f32 / self.fade_len as f32;
+        self.fade_left = Some(left - 1);
+        true
+    }
+
+    // Wraps the source so that it plays in another format.
+    fn convert(&mut self, channels: u16, sample_rate: u32)
+    where
+        S: Sample + Send + 'static,
+    {
+        let source = std::mem::replace(&mut self.source, Box::new(Empty::new()));
+        self.source = Box::new(UniformSourceIterator::new(source, channels, sample_rate));
+    }
+
+    fn steal(&self, solo_count: &AtomicIsize) {
+        self.state.flags.fetch_or(STOLEN, Ordering::SeqCst);
+        self.finish(solo_count);
+    }
+
+    fn finish(&self, solo_count: &AtomicIsize) {
+        let flags = self.state.flags.fetch_or(DONE, Ordering::SeqCst);
+        if flags & SOLO != 0 {
+            solo_count.fetch_sub(1, Ordering::SeqCst);
+        }
+        let on_done = self.state.on_done.lock().unwrap().take();
+        if let Some(callback) = on_done {
+            callback();
+        }
+    }
+}
+
+/// The output of the mixer. Implements `Source`.
+pub struct DynamicMixer<S> {
+    // The current iterator that produces samples.
+    current_sources: Vec<Track<S>>,
+
+    // The pending sounds.
+    input: Arc<DynamicMixerController<S>>,
+
+    // The format of the output.
+    channels: u16,
+    sample_rate: u32,
+
+    // The number of samples produced so far.
+    sample_count: usize,
+
+    // The sample at which the output switched to its current format, frames start from there.
+    frame_origin: usize,
+
+    // The sources sent by the controller.
+    pending_sources: Receiver<Track<S>>,
+
+    // The sources received that are waiting to start.
+    pending: Vec<Track<S>>,
+
+    // A temporary vec used in start_pending_sources.
+    still_pending: Vec<Track<S>>,
+
+    // A temporary vec used in sum_current_sources.
+    still_current: Vec<Track<S>>,
+
+    // The samples of the current sources, a temporary vec used in sum_current_sources.
+    values: Vec<S>,
+
+    // The effect fed by the sends of the sources.
+    send_effect: Option<SendEffect<S>>,
+
+    // How the samples of the sources are added together.
+    mode: MixMode,
+
+    // Adds up the sources in the wide type of the samples, for `MixMode::Wide`.
+    sum_wide: Option<fn(&mut DynamicMixer<S>) -> S>,
+
+    // Whether the output plays silence instead of ending when there is nothing to play.
+    silence_when_idle: bool,
+
+    // Counts the samples that clipped, if the caller asked for it.
+    clip_counter: Option<Arc<AtomicUsize>>,
+
+    // Where the last frame collapsed to one channel is published, as the bits of an f32.
+    mono_sum: Option<Arc<AtomicU32>>,
+
+    // Sum of the channels of the current frame so far.
+    mono_acc: f32,
+
+    // The number of sources that can play at once, if limited.
+    voice_limit: Option<usize>,
+}
+
+impl<S> DynamicMixer<S> {
+    /// Makes the mixer play silence instead of ending when there is nothing to play, so that
+    /// whatever drives it, such as a device callback, never sees it end. Sources added later
+    /// are played as usual.
+    ///
+    /// Use [`is_idle`](DynamicMixer::is_idle) to know whether the silence is all there is.
+    #[inline]
+    pub fn with_silence_when_idle(mut self) -> Self {
+        self.silence_when_idle = true;
+        self
+    }
+
+    /// Counts in `counter` the output samples for which the sum of the sources does not fit in
+    /// the range of the samples, before it is handled according to the [`MixMode`].
+    ///
+    /// The counter can be read from another thread, for example to light up a clip indicator.
+    /// Mixers that are not given a counter do not check for clipping.
+    #[inline]
+    pub fn with_clip_counter(mut self, counter: Arc<AtomicUsize>) -> Self {
+        self.clip_counter = Some(counter);
+        self
+    }
+
+    /// Publishes in `meter` every frame of the output collapsed to one channel, as the bits of
+    /// an `f32`. The channels of the frame are averaged, so the value has the range of a mono
+    /// sample.
+    ///
+    /// This is the output that is already mixed, the sources are not played a second time.
+    /// Another thread, for example the one drawing a master meter, reads it with
+    /// `f32::from_bits(meter.load(Ordering::Relaxed))` without ever waiting on the mixer.
+    #[inline]
+    pub fn with_mono_sum(mut self, meter: Arc<AtomicU32>) -> Self {
+        self.mono_sum = Some(meter);
+        self
+    }
+
+    /// Limits the number of sources that play at once to `limit`, a bus counting as one.
+    ///
+    /// When a source starts while `limit` sources are playing, the playing source with the
+    /// lowest priority is stopped to make room for it, the oldest one if several have that
+    /// priority. If that source has a higher priority than the new one, the new one is stopped
+    /// instead and never plays. The source that is stopped is finished: its handle reports
+    /// it as done and [stolen](SourceHandle::was_stolen), and its
+    /// [`on_done`](SourceHandle::on_done) callback runs. Priorities are given with
+    /// [`add_with_priority`](DynamicMixerController::add_with_priority).
+    ///
+    /// # Panic
+    ///
+    /// Panics if `limit` is 0.
+    #[inline]
+    pub fn with_voice_limit(mut self, limit: usize) -> Self {
+        assert!(limit >= 1, "the mixer needs at least one voice");
+        self.voice_limit = Some(limit);
+        self
+    }
+
+    /// Returns true if the mixer has no source playing and none waiting to start.
+    ///
+    /// A mixer with no source playing returns `None`, but it can start playing again when a
+    /// source is added to it: `None` only means there was nothing to play at that moment,
+    /// while an idle mixer stays idle until something is added.
+    #[inline]
+    pub fn is_idle(&self) -> bool {
+        self.current_sources.is_empty() && !self.input.has_pending.load(Ordering::SeqCst)
+    }
+}
+
+impl<S> Source for DynamicMixer<S>
+where
+    S: Sample + Send + 'static,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Returns whether all of the sources playing now can seek. Sources added but not mixed
+    /// in yet are not taken into account.
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.current_sources
+            .iter()
+            .all(|track| track.source.is_seekable())
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+
+        // uncomment when #510 is implemented (query position of playback)
+
+        // let mut org_positions = Vec::with_capacity(self.current_sources.len());
+        // let mut encounterd_err = None;
+        //
+        // for source in &mut self.current_sources {
+        //     let pos = /* source.playback_pos() */ todo!();
+        //     if let Err(e) = source.try_seek(pos) {
+        //         encounterd_err = Some(e);
+        //         break;
+        //     } else {
+        //         // store pos in case we need to roll back
+        //         org_positions.push(pos);
+        //     }
+        // }
+        //
+        // if let Some(e) = encounterd_err {
+        //     // rollback seeks that happend before err
+        //     for (pos, source) in org_positions
+        //         .into_iter()
+        //         .zip(self.current_sources.iter_mut())
+        //     {
+        //         source.try_seek(pos)?;
+        //     }
+        //     Err(e)
+        // } else {
+        //     Ok(())
+        // }
+    }
+}
+
+impl<S> Iterator for DynamicMixer<S>
+where
+    S: Sample + Send + 'static,
+{
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        if self.input.has_pending.load(Ordering::SeqCst) {
+            self.start_pending_sources();
+        }
+        let sample = self.mix_sample();
+        self.input
+            .sample_count
+            .store(self.sample_count, Ordering::SeqCst);
+        sample
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<S> DynamicMixer<S>
+where
+    S: Sample + Send + 'static,
+{
+    /// Plays up to `buf.len()` samples into `buf`, for callbacks that take a whole buffer at
+    /// once. Returns the number of samples written, fewer when the mixer ends.
+    ///
+    /// The same as calling `next` for every sample, except that the sources added while the
+    /// buffer is being filled start with the next call instead of in the middle of this one.
+    /// This saves checking for them on every sample. Sources that were already added start on
+    /// their sample as usual.
+    pub fn fill(&mut self, buf: &mut [S]) -> usize {
+        let mut waiting = true;
+        for (written, slot) in buf.iter_mut().enumerate() {
+            if waiting {
+                if self.input.has_pending.load(Ordering::SeqCst) {
+                    self.start_pending_sources();
+                }
+                // Still set when a source received is waiting for a later sample.
+                waiting = self.input.has_pending.load(Ordering::SeqCst);
+            }
+            match self.mix_sample() {
+                Some(sample) => *slot = sample,
+                None => {
+                    self.input
+                        .sample_count
+                        .store(self.sample_count, Ordering::SeqCst);
+                    return written;
+                }
+            }
+        }
+        self.input
+            .sample_count
+            .store(self.sample_count, Ordering::SeqCst);
+        buf.len()
+    }
+
+    // Plays the next sample of the sources that are already playing.
+    #[inline]
+    fn mix_sample(&mut self) -> Option<S> {
+        let frame_start = self.at_frame_start();
+        self.sample_count += 1;
+
+        let sum = self.sum_current_sources(frame_start);
+
+        if self.current_sources.is_empty() && !self.silence_when_idle {
+            None
+        } else {
+            if let Some(meter) = &self.mono_sum {
+                self.mono_acc += sum.to_f32();
+                if self.at_frame_start() {
+                    let mono = self.mono_acc / self.channels as f32;
+                    meter.store(mono.to_bits(), Ordering::Relaxed);
+                    self.mono_acc = 0.0;
+                }
+            }
+            Some(sum)
+        }
+    }
+
+    /// Plays up to `frames` frames and appends each of their channels to its own buffer, for
+    /// APIs and file writers that take planar audio instead of interleaved samples.
+    ///
+    /// Returns the number of frames added, by which every buffer grows. Fewer are added when
+    /// the mixer ends, or when its output format is about to change: check
+    /// [`channels`](Source::channels) before calling it again. Only whole frames are added.
+    /// The samples missing from a frame, because the mixer ended in the middle of it or
+    /// because its start was already played with `next`, are silent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one buffer per channel of the output.
+    pub fn deinterleave_into(&mut self, buffers: &mut [Vec<S>], frames: usize) -> usize {
+        let channels = self.channels as usize;
+        assert_eq!(
+            buffers.len(),
+            channels,
+            "the mixer needs one buffer per channel"
+        );
+
+        for added in 0..frames {
+            let first = (self.sample_count - self.frame_origin) % channels;
+            // The new format starts with the next frame, which would not fit in the buffers.
+            if first == 0 && self.input.format_changed.load(Ordering::SeqCst) {
+                return added;
+            }
+            let Some(sample) = self.next() else {
+                return added;
+            };
+            for buffer in &mut buffers[..first] {
+                buffer.push(S::zero_value());
+            }
+            buffers[first].push(sample);
+            for buffer in &mut buffers[first + 1..] {
+                buffer.push(self.next().unwrap_or(S::zero_value()));
+            }
+        }
+        frames
+    }
+
+    /// Removes all of the sources from the mixer, those playing and those waiting to start, so
+    /// that they can be moved to another mixer with [`adopt_sources`](Self::adopt_sources),
+    /// for example one built for a new output device.
+    ///
+    /// The sources are returned in the format of this mixer, where they left off. If the mixer
+    /// is in the middle of a frame, the rest of that frame is skipped, so that every source
+    /// hands over whole frames. The sources waiting to start lose their start time.
+    ///
+    /// For this mixer the sources are finished: their handles report them as done and their
+    /// [`on_done`](SourceHandle::on_done) callbacks run. The send effect stays.
+    pub fn take_sources(&mut self) -> Vec<Box<dyn Source<Item = S> + Send>> {
+        let channels = self.channels as usize;
+        let in_frame = (self.sample_count - self.frame_origin) % channels;
+        let mut playing = std::mem::take(&mut self.current_sources);
+        if in_frame != 0 {
+            for track in &mut playing {
+                for _ in in_frame..channels {
+                    track.source.next();
+                }
+            }
+        }
+        // Cleared first, so that a source sent from now on stays in this mixer.
+        self.input.has_pending.store(false, Ordering::SeqCst);
+        self.pending.extend(self.pending_sources.try_iter());
+        let effect_pending = match self.input.pending_send_effect.try_lock() {
+            Ok(pending_send_effect) => pending_send_effect.is_some(),
+            Err(_) => true,
+        };
+        if self.input.format_changed.load(Ordering::SeqCst) || effect_pending {
+            self.input.has_pending.store(true, Ordering::SeqCst);
+        }
+        playing
+            .into_iter()
+            .chain(self.pending.drain(..))
+            .map(|track| {
+                track.finish(&self.input.solo_count);
+                track.source
+            })
+            .collect()
+    }
+
+    /// Adds sources taken from another mixer with [`take_sources`](Self::take_sources).
+    ///
+    /// They start together, on the next frame. The sources that are not in the format of this
+    /// mixer are converted to it with a [`UniformSourceIterator`]. They are not muted, soloed
+    /// or sent to the send effect, whatever they were in the other mixer; the new handles
+    /// returned, in the same order, control them from now on.
+    ///
+    /// # Panic
+    ///
+    /// Panics if a source has 0 channels or a sample rate of 0.
+    pub fn adopt_sources<I>(&mut self, sources: I) -> Vec<SourceHandle>
+    where
+        I: IntoIterator<Item = Box<dyn Source<Item = S> + Send>>,
+    {
+        let handles = sources
+            .into_iter()
+            .map(|source| {
+                assert!(
+                    source.channels() >= 1,
+                    "a source added to the mixer needs at least one channel"
+                );
+                assert!(
+                    source.sample_rate() >= 1,
+                    "a source added to the mixer can not have a sample rate of 0"
+                );
+                let state = Arc::new(SourceState {
+                    flags: AtomicU8::new(0),
+                    send: AtomicU32::new(0.0f32.to_bits()),
+                    fade_out: AtomicU64::new(0),
+                    on_done: Mutex::new(None),
+                });
+                let mut track = Track {
+                    source,
+                    state: state.clone(),
+                    audible: true,
+                    send: 0.0,
+                    // The first frame from now.
+                    start_sample: self.sample_count,
+                    priority: 0,
+                    fade_left: None,
+                    fade_len: 0,
+                    gain: 1.0,
+                };
+                if track.source.channels() != self.channels
+                    || track.source.sample_rate() != self.sample_rate
+                {
+                    track.convert(self.channels, self.sample_rate);
+                }
+                self.pending.push(track);
+                SourceHandle {
+                    state,
+                    solo_count: self.input.solo_count.clone(),
+                }
+            })
+            .collect();
+        self.input.has_pending.store(true, Ordering::SeqCst);
+        handles
+    }
+
+    // Samples from the #next() function are interlaced for each of the channels.
+    // We need to ensure we start playing sources so that their samples are
+    // in-step with the modulo of the samples produced so far. Otherwise, the
+    // sound will play on the wrong channels, e.g. left / right will be reversed.
+    fn start_pending_sources(&mut self) {
+        // Cleared first, so that a source sent from now on is picked up next time.
+        self.input.has_pending.store(false, Ordering::SeqCst); // TODO: relax ordering?
+        self.pending.extend(self.pending_sources.try_iter());
+
+        // Sources added for the new format can not start before switching.
+        if self.input.format_changed.load(Ordering::SeqCst) {
+            if !self.at_frame_start() {
+                self.input.has_pending.store(true, Ordering::SeqCst);
+                return;
+            }
+            self.input.format_changed.store(false, Ordering::SeqCst);
+            (self.channels, self.sample_rate) = self.input.output_format();
+            for track in &mut self.current_sources {
+                track.convert(self.channels, self.sample_rate);
+            }
+            self.send_effect = None;
+            self.frame_origin = self.sample_count;
+        }
+
+        let position = self.sample_count - self.frame_origin;
+        // Taken out while the tracks start, and put back to keep its capacity.
+        let mut pending = std::mem::take(&mut self.pending);
+        for mut track in pending.drain(..) {
+            // Added for another format, while the output format was changing.
+            if track.source.channels() != self.channels
+                || track.source.sample_rate() != self.sample_rate
+            {
+                track.convert(self.channels, self.sample_rate);
+            }
+            let channels = track.source.channels() as usize;
+            let in_step = position.is_multiple_of(channels);
+            // The first frame at or after the start of the track.
+            let first_frame = track
+                .start_sample
+                .saturating_sub(self.frame_origin)
+                .next_multiple_of(channels);
+
+            // A track the mixer picks up in the middle of its first frame waits for the next
+            // one, so that none of its samples are lost.
+            if position >= first_frame && in_step {
+                self.start_track(track);
+            } else {
+                self.still_pending.push(track);
+            }
+        }
+        self.pending = pending;
+        std::mem::swap(&mut self.still_pending, &mut self.pending);
+
+        // Only replaced between frames, the new effect starts on the first channel. If the
+        // controller is replacing it right now, it is picked up on a later sample instead of
+        // waiting.
+        let effect_pending = match self.input.pending_send_effect.try_lock() {
+            Ok(mut pending_send_effect) => {
+                if self.at_frame_start() {
+                    if let Some(effect) = pending_send_effect.take() {
+                        self.send_effect = Some(effect);
+                    }
+                }
+                pending_send_effect.is_some()
+            }
+            Err(_) => true,
+        };
+
+        if !self.pending.is_empty() || effect_pending {
+            self.input.has_pending.store(true, Ordering::SeqCst); // TODO: relax ordering?
+        }
+    }
+
+    // Starts playing a track, stealing a voice for it if there are too many.
+    fn start_track(&mut self, track: Track<S>) {
+        if let Some(limit) = self.voice_limit {
+            if self.current_sources.len() >= limit {
+                // The sources are in the order they started, the first one is the oldest.
+                let (index, lowest) = self
+                    .current_sources
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, playing)| playing.priority)
+                    .expect("the mixer has at least one voice");
+                if lowest.priority > track.priority {
+                    track.steal(&self.input.solo_count);
+                    return;
+                }
+                let stolen = self.current_sources.remove(index);
+                stolen.steal(&self.input.solo_count);
+            }
+        }
+        self.current_sources.push(track);
+    }
+
+    #[inline]
+    fn at_frame_start(&self) -> bool {
+        (self.sample_count - self.frame_origin).is_multiple_of(self.channels as usize)
+    }
+
+    fn sum_current_sources(&mut self, frame_start: bool) -> S {
+        self.values.clear();
+        for mut track in self.current_sources.drain(..) {
+            // Removed between frames, like the sources that end.
+            if frame_start && !track.advance_fade(self.sample_rate) {
+                track.finish(&self.input.solo_count);
+                continue;
+            }
+            if let Some(value) = track.source.next() {
+                self.values.push(match track.fade_left {
+                    Some(_) => value.amplify(track.gain),
+                    None => value,
+                });
+                self.still_current.push(track);
+            } else {
+                track.finish(&self.input.solo_count);
+            }
+        }
+        std::mem::swap(&mut self.still_current, &mut self.current_sources);
+
+        // Muting and soloing only take effect between frames, to keep the channels in sync.
+        // This is done once the sources that ended are removed, so that they no longer count
+        // as soloed.
+        if frame_start {
+            let solo_active = self.input.solo_count.load(Ordering::SeqCst) > 0;
+            for track in &mut self.current_sources {
+                track.update(solo_active);
+            }
+        }
+
+        if let Some(sum_wide) = self.sum_wide {
+            return sum_wide(self);
+        }
+
+        let mut sum = S::zero_value();
+        let mut send = S::zero_value();
+        let mut clipped = false;
+        for (track, &value) in self.current_sources.iter().zip(&self.values) {
+            if track.audible {
+                sum = self.add_to_output(sum, value, &mut clipped);
+                if track.send != 0.0 && self.send_effect.is_some() {
+                    send = self.add(send, value.amplify(track.send));
+                }
+            }
+        }
+
+        if let Some(effect) = &mut self.send_effect {
+            match effect.next(send) {
+                Some(wet) => sum = self.add_to_output(sum, wet, &mut clipped),
+                None => self.send_effect = None,
+            }
+        }
+
+        if clipped {
+            if let Some(counter) = &self.clip_counter {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        sum
+    }
+
+    // Same as `add`, also tells whether the sum clips when the mixer counts clipping.
+    #[inline]
+    fn add_to_output(&self, a: S, b: S, clipped: &mut bool) -> S {
+        if self.clip_counter.is_some() && a.checked_add(b).is_none() {
+            *clipped = true;
+        }
+        self.add(a, b)
+    }
+
+    #[inline]
+    fn add(&self, a: S, b: S) -> S {
+        match self.mode {
+            MixMode::Saturate => a.saturating_add(b),
+            MixMode::Wrap => a.wrapping_add(b),
+            MixMode::DebugPanic => a
+                .checked_add(b)
+                .unwrap_or_else(|| panic!("the mixer clipped at sample {}", self.sample_count)),
+            MixMode::Wide => unreachable!("the wide sum is made by `sum_wide`"),
+        }
+    }
+}
+
+impl<S> DynamicMixer<S>
+where
+    S: WideSample + Send + 'static,
+{
+    // The end of `sum_current_sources` for `MixMode::Wide`.
+    fn sum_wide(&mut self) -> S {
+        let mut sum = S::zero_value().to_wide();
+        let mut send = S::zero_value().to_wide();
+        for (track, &value) in self.current_sources.iter().zip(&self.values) {
+            if track.audible {
+                sum = sum + value.to_wide();
+                if track.send != 0.0 && self.send_effect.is_some() {
+                    send = send + value.amplify(track.send).to_wide();
+                }
+            }
+        }
+
+        if let Some(effect) = &mut self.send_effect {
+            match effect.next(S::from_wide(send)) {
+                Some(wet) => sum = sum + wet.to_wide(),
+                None => self.send_effect = None,
+            }
+        }
+
+        let output = S::from_wide(sum);
+        if let Some(counter) = &self.clip_counter {
+            // Integers are clamped by `from_wide`, floats only leave their range.
+            let clamped = output.to_wide() != sum;
+            if clamped || output.checked_add(S::zero_value()).is_none() {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::dynamic_mixer::{self, MixMode};
+    use crate::source::{Source, Zero};
+
+    #[test]
+    fn basic() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![10i16, -10, 10, -10]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5, 5, 5]));
+
+        assert_eq!(rx.channels(), 1);
+        assert_eq!(rx.sample_rate(), 48000);
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(-5));
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(-5));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn capacity_is_reserved() {
+        let (tx, mut rx) = dynamic_mixer::mixer_with_capacity::<i16>(1, 48000, 32);
+        let capacities = |rx: &dynamic_mixer::DynamicMixer<i16>| {
+            [
+                rx.current_sources.capacity(),
+                rx.still_pending.capacity(),
+                rx.still_current.capacity(),
+                rx.values.capacity(),
+                rx.pending.capacity(),
+            ]
+        };
+        let reserved = capacities(&rx);
+        assert!(reserved.iter().all(|&capacity| capacity >= 32));
+
+        for _ in 0..32 {
+            tx.add(SamplesBuffer::new(1, 48000, vec![1i16; 4]));
+        }
+        assert_eq!(rx.next(), Some(32));
+        // the lists are only swapped around, none had to grow
+        let mut after = capacities(&rx);
+        let mut before = reserved;
+        after.sort();
+        before.sort();
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn concurrent_adds() {
+        const THREADS: usize = 8;
+        const SOURCES: usize = 200;
+        let (tx, mut rx) = dynamic_mixer::mixer_keep_alive(2, 48000);
+
+        let adders: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for _ in 0..SOURCES {
+                        tx.add(SamplesBuffer::new(2, 48000, vec![1i16, -1, 1, -1]));
+                    }
+                })
+            })
+            .collect();
+
+        let mut frames = Vec::new();
+        let mut play_frame = |rx: &mut dynamic_mixer::DynamicMixer<i16>| {
+            frames.push((rx.next().unwrap(), rx.next().unwrap()));
+        };
+        while adders.iter().any(|adder| !adder.is_finished()) {
+            play_frame(&mut rx);
+        }
+        adders.into_iter().for_each(|adder| adder.join().unwrap());
+        while !rx.is_idle() {
+            play_frame(&mut rx);
+        }
+
+        // every source started in step with the frames, and played to its end
+        assert!(frames.iter().all(|&(left, right)| left == -right));
+        let played: i16 = frames.iter().map(|&(left, _)| left).sum();
+        assert_eq!(played as usize, THREADS * SOURCES * 2);
+    }
+
+    #[test]
     fn channels_conv() {
         let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
 
-        tx.add(SamplesBuffer::new(1, 48000, vec![10i16, -10, 10, -10]));
-        tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5, 5, 5]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![10i16, -10, 10, -10]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5, 5, 5]));
+
+        assert_eq!(rx.channels(), 2);
+        assert_eq!(rx.sample_rate(), 48000);
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(-5));
+        assert_eq!(rx.next(), Some(-5));
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(-5));
+        assert_eq!(rx.next(), Some(-5));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn rate_conv() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 96000);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![10i16, -10, 10, -10]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5, 5, 5]));
+
+        assert_eq!(rx.channels(), 1);
+        assert_eq!(rx.sample_rate(), 96000);
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(5));
+        assert_eq!(rx.next(), Some(-5));
+        assert_eq!(rx.next(), Some(5));
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(5));
+        assert_eq!(rx.next(), Some(-5));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn start_afterwards() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![10i16, -10, 10, -10]));
+
+        assert_eq!(rx.next(), Some(10));
+        assert_eq!(rx.next(), Some(-10));
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5, 6, 6, 7, 7, 7]));
+
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(-5));
+
+        assert_eq!(rx.next(), Some(6));
+        assert_eq!(rx.next(), Some(6));
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![2i16]));
+
+        assert_eq!(rx.next(), Some(9));
+        assert_eq!(rx.next(), Some(7));
+        assert_eq!(rx.next(), Some(7));
+
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn basic_f64() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        tx.add(SamplesBuffer::new(
+            1,
+            48000,
+            vec![0.5f64, -0.25, 0.75, 0.125],
+        ));
+        tx.add(SamplesBuffer::new(
+            1,
+            48000,
+            vec![0.25f64, 0.25, 0.25, 0.25],
+        ));
+
+        assert_eq!(rx.channels(), 1);
+        assert_eq!(rx.sample_rate(), 48000);
+        assert_eq!(rx.next(), Some(0.75));
+        assert_eq!(rx.next(), Some(0.0));
+        assert_eq!(rx.next(), Some(1.0));
+        assert_eq!(rx.next(), Some(0.375));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn basic_u8() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![138u8, 118, 250, 10]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![138u8, 138, 200, 50]));
+
+        assert_eq!(rx.next(), Some(148));
+        assert_eq!(rx.next(), Some(128));
+        // saturates at the top and the bottom of the range
+        assert_eq!(rx.next(), Some(255));
+        assert_eq!(rx.next(), Some(0));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn basic_i8() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![10i8, -10, 100, -100]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![5i8, 5, 100, -100]));
 
-        assert_eq!(rx.channels(), 2);
-        assert_eq!(rx.sample_rate(), 48000);
-        assert_eq!(rx.next(), Some(15));
         assert_eq!(rx.next(), Some(15));
         assert_eq!(rx.next(), Some(-5));
-        assert_eq!(rx.next(), Some(-5));
-        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), Some(127));
+        assert_eq!(rx.next(), Some(-128));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn wrap_mode() {
+        let (tx, mut rx) = dynamic_mixer::mixer_with_mode(1, 48000, MixMode::Wrap);
+
+        tx.add(SamplesBuffer::new(
+            1,
+            48000,
+            vec![10i16, i16::MAX, i16::MIN],
+        ));
+        tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 1, -1]));
+
         assert_eq!(rx.next(), Some(15));
-        assert_eq!(rx.next(), Some(-5));
-        assert_eq!(rx.next(), Some(-5));
+        assert_eq!(rx.next(), Some(i16::MIN));
+        assert_eq!(rx.next(), Some(i16::MAX));
         assert_eq!(rx.next(), None);
     }
 
     #[test]
-    fn rate_conv() {
-        let (tx, mut rx) = dynamic_mixer::mixer(1, 96000);
+    fn wrap_mode_f32() {
+        let (tx, mut rx) = dynamic_mixer::mixer_with_mode(1, 48000, MixMode::Wrap);
 
-        tx.add(SamplesBuffer::new(1, 48000, vec![10i16, -10, 10, -10]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![0.5f32, 0.75]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![0.5f32, 0.5]));
+
+        assert_eq!(rx.next(), Some(1.0));
+        assert_eq!(rx.next(), Some(-0.75));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn debug_panic_mode_allows_headroom() {
+        let (tx, rx) = dynamic_mixer::mixer_with_mode(1, 48000, MixMode::DebugPanic);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![0.5f32, -0.5]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![0.5f32, -0.25]));
+
+        assert_eq!(rx.collect::<Vec<_>>(), vec![1.0, -0.75]);
+    }
+
+    #[test]
+    #[should_panic(expected = "clipped")]
+    fn debug_panic_mode_panics() {
+        let (tx, rx) = dynamic_mixer::mixer_with_mode(1, 48000, MixMode::DebugPanic);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![100u8, 250]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![150u8, 200]));
+
+        rx.for_each(drop);
+    }
+
+    #[test]
+    fn mute() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        let first = tx.add(SamplesBuffer::new(1, 48000, vec![10i16, 10, 10, 10]));
         tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5, 5, 5]));
 
-        assert_eq!(rx.channels(), 1);
-        assert_eq!(rx.sample_rate(), 96000);
         assert_eq!(rx.next(), Some(15));
+        first.set_muted(true);
+        assert!(first.is_muted());
         assert_eq!(rx.next(), Some(5));
-        assert_eq!(rx.next(), Some(-5));
         assert_eq!(rx.next(), Some(5));
+        first.set_muted(false);
+        assert_eq!(rx.next(), Some(15));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn solo() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        let first = tx.add(SamplesBuffer::new(1, 48000, vec![10i16, 10, 10, 10]));
+        let second = tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5, 5, 5, 5]));
+        let third = tx.add(SamplesBuffer::new(1, 48000, vec![1i16, 1, 1, 1, 1]));
+
+        first.set_solo(true);
+        assert_eq!(rx.next(), Some(10));
+        second.set_solo(true);
+        assert_eq!(rx.next(), Some(15));
+        // muted sources are never heard, even when soloed
+        second.set_muted(true);
+        assert_eq!(rx.next(), Some(10));
+        second.set_muted(false);
         assert_eq!(rx.next(), Some(15));
+        // the first source has finished, only the second one is still soloed
         assert_eq!(rx.next(), Some(5));
-        assert_eq!(rx.next(), Some(-5));
+        assert!(first.is_done());
         assert_eq!(rx.next(), None);
+        assert!(third.is_done());
     }
 
     #[test]
-    fn start_afterwards() {
+    fn finished_solo_source_unsilences_others() {
         let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
 
-        tx.add(SamplesBuffer::new(1, 48000, vec![10i16, -10, 10, -10]));
+        let first = tx.add(SamplesBuffer::new(1, 48000, vec![10i16]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5, 5]));
 
+        first.set_solo(true);
         assert_eq!(rx.next(), Some(10));
-        assert_eq!(rx.next(), Some(-10));
+        assert_eq!(rx.next(), Some(5));
+        // changing the flags of a finished source has no effect
+        first.set_solo(true);
+        assert!(first.is_solo());
+        first.set_solo(false);
+        first.set_solo(true);
+        assert_eq!(rx.next(), Some(5));
+        assert_eq!(rx.next(), None);
+    }
 
-        tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5, 6, 6, 7, 7, 7]));
+    #[test]
+    fn toggling_keeps_frames_in_sync() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+
+        let first = tx.add(SamplesBuffer::new(2, 48000, vec![10i16, -10, 10, -10]));
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16, 2, 1, 2]));
+
+        assert_eq!(rx.next(), Some(11));
+        // applied on the next frame only
+        first.set_muted(true);
+        assert_eq!(rx.next(), Some(-8));
+        assert_eq!(rx.next(), Some(1));
+        assert_eq!(rx.next(), Some(2));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn bus() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![1i16, 1, 1, 1]));
+        let bus = tx.add_bus();
+        bus.add(SamplesBuffer::new(1, 48000, vec![10i16, 10, 10, 10]));
+        bus.add(SamplesBuffer::new(1, 48000, vec![20i16, 20, 20, 20]));
+
+        assert_eq!(rx.next(), Some(31));
+        bus.set_volume(0.5);
+        assert_eq!(bus.volume(), 0.5);
+        assert_eq!(rx.next(), Some(16));
+        bus.handle().set_muted(true);
+        assert_eq!(rx.next(), Some(1));
+        bus.handle().set_muted(false);
+        bus.set_volume(0.0);
+        assert_eq!(rx.next(), Some(1));
+    }
+
+    #[test]
+    fn bus_ends_once_dropped() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        let bus = tx.add_bus();
+        bus.add(SamplesBuffer::new(1, 48000, vec![10i16]));
+
+        assert_eq!(rx.next(), Some(10));
+        // the bus is waiting for more sources
+        assert_eq!(rx.next(), Some(0));
+        bus.add(SamplesBuffer::new(1, 48000, vec![5i16]));
+        assert_eq!(rx.next(), Some(5));
+
+        drop(bus);
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn nested_bus_with_effect() {
+        let (tx, rx) = dynamic_mixer::mixer(1, 48000);
+
+        let bus = tx.add_bus_with_effect(|mix| mix.amplify(2.0));
+        bus.set_volume(0.5);
+        let inner = bus.add_bus_with_effect(|mix| mix.amplify(3.0));
+        inner.add(SamplesBuffer::new(1, 48000, vec![10i16, 20]));
+        drop((bus, inner));
+
+        assert_eq!(rx.collect::<Vec<_>>(), vec![30, 60]);
+    }
+
+    #[test]
+    fn send_effect() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        tx.set_send_effect(|send| send.amplify(2.0));
+        let first = tx.add(SamplesBuffer::new(1, 48000, vec![10i16, 10, 10]));
+        let second = tx.add(SamplesBuffer::new(1, 48000, vec![20i16, 20, 20]));
+        first.set_reverb_send(0.5);
+        assert_eq!(first.reverb_send(), 0.5);
+
+        // the effect hears the frame before, silence at first
+        assert_eq!(rx.next(), Some(30));
+        second.set_reverb_send(0.25);
+        // the dry sources, plus twice half of the first one
+        assert_eq!(rx.next(), Some(40));
+        first.set_reverb_send(0.0);
+        second.set_reverb_send(0.0);
+        // plus twice the sends of both
+        assert_eq!(rx.next(), Some(50));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn send_effect_gets_whole_frames() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+
+        tx.set_send_effect(|send| send.amplify(1.0));
+        let source = tx.add(SamplesBuffer::new(2, 48000, vec![10i16, 20, 30, 40, 0, 0]));
+        source.set_reverb_send(0.5);
+
+        let output: Vec<i16> = rx.by_ref().take(6).collect();
+        assert_eq!(output, vec![10, 20, 35, 50, 15, 20]);
+    }
+
+    #[test]
+    fn muted_source_does_not_feed_send() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        tx.set_send_effect(|send| send.amplify(1.0));
+        let first = tx.add(SamplesBuffer::new(1, 48000, vec![10i16, 10, 10]));
+        first.set_reverb_send(1.0);
+
+        assert_eq!(rx.next(), Some(10));
+        first.set_muted(true);
+        // what the effect heard before the source was muted
+        assert_eq!(rx.next(), Some(10));
+        assert_eq!(rx.next(), Some(0));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn add_at() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16; 8]));
+        tx.add_at(SamplesBuffer::new(2, 48000, vec![10i16, 20]), 4);
+        // not on a frame boundary, starts with the next frame
+        tx.add_at(SamplesBuffer::new(2, 48000, vec![100i16, 200]), 5);
+
+        let output: Vec<i16> = rx.by_ref().take(4).collect();
+        assert_eq!(output, vec![1, 1, 1, 1]);
+        assert_eq!(rx.next(), Some(11));
+        assert_eq!(rx.next(), Some(21));
+        assert_eq!(rx.next(), Some(101));
+        assert_eq!(rx.next(), Some(201));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn add_starts_where_the_mixer_was() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16; 16]));
+        assert_eq!(rx.by_ref().take(4).collect::<Vec<_>>(), vec![1; 4]);
+        assert_eq!(tx.sample_count(), 4);
+
+        // every sample of the source at its place from sample 4 on
+        tx.add(SamplesBuffer::new(2, 48000, vec![10i16, 20, 30, 40]));
+        assert_eq!(rx.by_ref().take(4).collect::<Vec<_>>(), [11, 21, 31, 41]);
+
+        // added in the middle of a frame, starts with the next one
+        assert_eq!(rx.next(), Some(1));
+        tx.add(SamplesBuffer::new(2, 48000, vec![100i16, 200]));
+        assert_eq!(tx.sample_count(), 9);
+        assert_eq!(rx.by_ref().take(3).collect::<Vec<_>>(), [1, 101, 201]);
+
+        // the mixer plays a sample before it sees the source, it still plays whole, a frame
+        // later. `add` is `add_at` the count of the mixer when it is called.
+        let added_at = tx.sample_count();
+        assert_eq!(rx.next(), Some(1));
+        tx.add_at(
+            SamplesBuffer::new(2, 48000, vec![10i16, 20, 30, 40]),
+            added_at,
+        );
+        assert_eq!(rx.by_ref().collect::<Vec<_>>(), [1, 11, 21, 30, 40]);
+    }
+
+    #[test]
+    fn add_at_in_the_past() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![1i16; 4]));
+        assert_eq!(rx.next(), Some(1));
+        assert_eq!(rx.next(), Some(1));
+
+        tx.add_at(SamplesBuffer::new(1, 48000, vec![10i16]), 1);
+        assert_eq!(rx.next(), Some(11));
+        assert_eq!(rx.next(), Some(1));
+    }
+
+    #[test]
+    fn on_done() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+
+        let (done_tx, done_rx) = mpsc::channel();
+        let first = tx.add(SamplesBuffer::new(1, 48000, vec![10i16]));
+        let second = tx.add(SamplesBuffer::new(1, 48000, vec![5i16, 5]));
+        let sender = done_tx.clone();
+        first.on_done(move || sender.send(1).unwrap());
+        second.on_done(move || done_tx.send(2).unwrap());
 
         assert_eq!(rx.next(), Some(15));
-        assert_eq!(rx.next(), Some(-5));
+        assert!(done_rx.try_recv().is_err());
+        assert_eq!(rx.next(), Some(5));
+        assert_eq!(done_rx.try_recv(), Ok(1));
+        assert!(first.is_done());
+        assert_eq!(rx.next(), None);
+        assert_eq!(done_rx.try_recv(), Ok(2));
 
-        assert_eq!(rx.next(), Some(6));
-        assert_eq!(rx.next(), Some(6));
+        // already finished, called right away
+        let (done_tx, done_rx) = mpsc::channel();
+        first.on_done(move || done_tx.send(3).unwrap());
+        assert_eq!(done_rx.try_recv(), Ok(3));
+    }
 
-        tx.add(SamplesBuffer::new(1, 48000, vec![2i16]));
+    #[test]
+    fn set_output_format() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
 
-        assert_eq!(rx.next(), Some(9));
-        assert_eq!(rx.next(), Some(7));
-        assert_eq!(rx.next(), Some(7));
+        tx.add(SamplesBuffer::new(1, 48000, vec![10i16, 20, 30, 40]));
+        assert_eq!(rx.next(), Some(10));
+
+        tx.set_output_format(2, 48000);
+        assert_eq!(tx.output_format(), (2, 48000));
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16, 2, 1, 2]));
+        assert_eq!(rx.next(), Some(21));
+        assert_eq!(rx.channels(), 2);
+        assert_eq!(rx.next(), Some(22));
+        assert_eq!(rx.next(), Some(31));
+        assert_eq!(rx.next(), Some(32));
+        assert_eq!(rx.next(), Some(40));
+        assert_eq!(rx.next(), Some(40));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn set_output_format_waits_for_frame() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16, 2, 3, 4]));
+        assert_eq!(rx.next(), Some(1));
+
+        tx.set_output_format(1, 48000);
+        // the frame is completed first
+        assert_eq!(rx.channels(), 2);
+        assert_eq!(rx.next(), Some(2));
+        assert_eq!(rx.next(), Some(3));
+        assert_eq!(rx.channels(), 1);
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn is_idle() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        assert!(rx.is_idle());
+
+        tx.add_at(SamplesBuffer::new(1, 48000, vec![10i16]), 2);
+        assert!(!rx.is_idle());
+        // nothing to play yet, but the mixer has not ended
+        assert_eq!(rx.next(), None);
+        assert_eq!(rx.next(), None);
+        assert!(!rx.is_idle());
+        assert_eq!(rx.next(), Some(10));
+        assert_eq!(rx.next(), None);
+        assert!(rx.is_idle());
+    }
+
+    #[test]
+    fn silence_when_idle() {
+        let (tx, rx) = dynamic_mixer::mixer(1, 48000);
+        let mut rx = rx.with_silence_when_idle();
+
+        assert_eq!(rx.next(), Some(0));
+        tx.add(SamplesBuffer::new(1, 48000, vec![10i16, 10]));
+        assert_eq!(rx.next(), Some(10));
+        assert_eq!(rx.next(), Some(10));
+        assert_eq!(rx.next(), Some(0));
+        assert_eq!(rx.next(), Some(0));
+        assert!(rx.is_idle());
+    }
+
+    #[test]
+    fn keep_alive_resumes_in_step() {
+        let (tx, mut rx) = dynamic_mixer::mixer_keep_alive(2, 48000);
+
+        assert_eq!(rx.next(), Some(0));
+        assert_eq!(rx.next(), Some(0));
+        assert_eq!(rx.next(), Some(0));
+        // added in the middle of a frame, starts with the next one
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16, 2, 1, 2]));
+        assert_eq!(rx.next(), Some(0));
+        assert_eq!(rx.next(), Some(1));
+        assert_eq!(rx.next(), Some(2));
+        assert_eq!(rx.next(), Some(1));
+        assert_eq!(rx.next(), Some(2));
+        assert_eq!(rx.next(), Some(0));
+    }
+
+    #[test]
+    fn mono_sum() {
+        let (tx, rx) = dynamic_mixer::mixer(2, 48000);
+        let meter = Arc::new(AtomicU32::new(0));
+        let mut rx = rx.with_mono_sum(meter.clone());
+        let read = || f32::from_bits(meter.load(Ordering::Relaxed));
+
+        tx.add(SamplesBuffer::new(2, 48000, vec![0.5f32, 0.25, -0.5, 0.0]));
+        tx.add(SamplesBuffer::new(2, 48000, vec![0.0f32, 0.25, 0.0, -0.5]));
+        rx.next();
+        // published once the frame is complete
+        assert_eq!(read(), 0.0);
+        rx.next();
+        assert_eq!(read(), 0.5);
+        rx.next();
+        rx.next();
+        assert_eq!(read(), -0.5);
+    }
+
+    #[test]
+    fn fill() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16, -1, 2, -2, 3, -3]));
+        // starts in the middle of the first block
+        tx.add_at(SamplesBuffer::new(2, 48000, vec![10i16, 20]), 2);
+        let mut buf = [0i16; 4];
+        assert_eq!(rx.fill(&mut buf), 4);
+        assert_eq!(buf, [1, -1, 12, 18]);
+        assert_eq!(tx.sample_count(), 4);
+        // ends before the buffer is full
+        assert_eq!(rx.fill(&mut buf), 2);
+        assert_eq!(buf[..2], [3, -3]);
+    }
+
+    #[test]
+    fn fill_starts_sources_added_after_the_block() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        tx.add(SamplesBuffer::new(1, 48000, vec![1i16; 4]));
+        let mut buf = [0i16; 2];
+        assert_eq!(rx.fill(&mut buf), 2);
+        tx.add(SamplesBuffer::new(1, 48000, vec![5i16; 2]));
+        assert_eq!(rx.fill(&mut buf), 2);
+        assert_eq!(buf, [6, 6]);
+    }
 
+    #[test]
+    fn voice_stealing() {
+        let (tx, rx) = dynamic_mixer::mixer(1, 48000);
+        let mut rx = rx.with_voice_limit(2);
+        let oldest = tx.add(SamplesBuffer::new(1, 48000, vec![1i16; 10]));
+        let (done_tx, done_rx) = mpsc::channel();
+        oldest.on_done(move || done_tx.send(()).unwrap());
+        let newer = tx.add(SamplesBuffer::new(1, 48000, vec![2i16; 10]));
+        assert_eq!(rx.next(), Some(3));
+
+        // the oldest of the lowest priority makes room
+        let high = tx.add_with_priority(SamplesBuffer::new(1, 48000, vec![10i16; 10]), 1);
+        assert_eq!(rx.next(), Some(12));
+        assert!(oldest.is_done() && oldest.was_stolen());
+        assert!(done_rx.try_recv().is_ok());
+        assert!(!newer.is_done() && !high.is_done());
+
+        // a source of a lower priority than all of the playing ones does not play
+        let low = tx.add_with_priority(SamplesBuffer::new(1, 48000, vec![100i16; 10]), -1);
+        assert_eq!(rx.next(), Some(12));
+        assert!(low.is_done() && low.was_stolen());
+        assert!(!newer.was_stolen());
+    }
+
+    #[test]
+    fn remove_faded() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 10);
+        let handle = tx.add(SamplesBuffer::new(2, 10, vec![1.0f32; 40]));
+        tx.add(SamplesBuffer::new(2, 10, vec![0.5f32; 40]));
+        assert_eq!(rx.next(), Some(1.5));
+        handle.remove_faded(Duration::from_millis(400));
+        // the end of the frame is not faded
+        assert_eq!(rx.next(), Some(1.5));
+
+        let output: Vec<f32> = rx.by_ref().take(10).collect();
+        assert_eq!(
+            output,
+            [1.5, 1.5, 1.25, 1.25, 1.0, 1.0, 0.75, 0.75, 0.5, 0.5]
+        );
+        assert!(handle.is_done());
+        assert!(!handle.was_stolen());
+    }
+
+    #[test]
+    fn remove_faded_without_a_fade() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 10);
+        let handle = tx.add(SamplesBuffer::new(1, 10, vec![1i16; 10]));
+        let (done_tx, done_rx) = mpsc::channel();
+        handle.on_done(move || done_tx.send(()).unwrap());
+        assert_eq!(rx.next(), Some(1));
+        handle.remove_faded(Duration::ZERO);
         assert_eq!(rx.next(), None);
+        assert!(handle.is_done());
+        assert!(done_rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn deinterleave_into() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16, -1, 2, -2, 3, -3]));
+        let mut buffers = vec![Vec::new(), Vec::new()];
+        assert_eq!(rx.deinterleave_into(&mut buffers, 2), 2);
+        assert_eq!(buffers, vec![vec![1, 2], vec![-1, -2]]);
+        // ends before the frames asked for
+        assert_eq!(rx.deinterleave_into(&mut buffers, 2), 1);
+        assert_eq!(buffers, vec![vec![1, 2, 3], vec![-1, -2, -3]]);
+    }
+
+    #[test]
+    fn deinterleave_into_after_half_a_frame() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16, -1, 2, -2]));
+        assert_eq!(rx.next(), Some(1));
+
+        let mut buffers = vec![Vec::new(), Vec::new()];
+        assert_eq!(rx.deinterleave_into(&mut buffers, 4), 2);
+        assert_eq!(buffers, vec![vec![0, 2], vec![-1, -2]]);
+    }
+
+    #[test]
+    fn deinterleave_into_stops_when_the_format_changes() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16; 20]));
+        let mut buffers = vec![Vec::new(), Vec::new()];
+        assert_eq!(rx.deinterleave_into(&mut buffers, 2), 2);
+        tx.set_output_format(1, 48000);
+        assert_eq!(rx.deinterleave_into(&mut buffers, 2), 0);
+        assert_eq!(rx.channels(), 2);
+
+        rx.next();
+        assert_eq!(rx.channels(), 1);
+        let mut mono = vec![Vec::new()];
+        assert_eq!(rx.deinterleave_into(&mut mono, 3), 3);
+    }
+
+    #[test]
+    fn clip_counter() {
+        let (tx, rx) = dynamic_mixer::mixer(1, 48000);
+        let clips = Arc::new(AtomicUsize::new(0));
+        let mut rx = rx.with_clip_counter(clips.clone());
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![10000i16, 20000, 5000]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![20000i16, 20000, 20000]));
+        assert_eq!(rx.next(), Some(30000));
+        assert_eq!(clips.load(Ordering::Relaxed), 0);
+        assert_eq!(rx.next(), Some(i16::MAX));
+        assert_eq!(clips.load(Ordering::Relaxed), 1);
+        assert_eq!(rx.next(), Some(25000));
+        assert_eq!(clips.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "the mixer needs at least one channel")]
+    fn zero_channels_panics_at_construction() {
+        dynamic_mixer::mixer::<i16>(0, 48000);
+    }
+
+    #[test]
+    #[should_panic(expected = "the sample rate of the mixer can not be 0")]
+    fn zero_sample_rate_panics_at_construction() {
+        dynamic_mixer::mixer::<i16>(2, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "a source added to the mixer needs at least one channel")]
+    fn add_rejects_zero_channels() {
+        let (tx, _rx) = dynamic_mixer::mixer::<i16>(2, 48000);
+        tx.add(Zero::new(0, 48000));
+    }
+
+    #[test]
+    #[should_panic(expected = "a source added to the mixer can not have a sample rate of 0")]
+    fn add_rejects_zero_sample_rate() {
+        let (tx, _rx) = dynamic_mixer::mixer::<i16>(2, 48000);
+        tx.add(Zero::new(2, 0));
+    }
+
+    #[test]
+    fn wide_mode_clamps_only_the_total() {
+        let (tx, rx) = dynamic_mixer::mixer_with_mode(1, 48000, MixMode::Wide);
+        let clips = Arc::new(AtomicUsize::new(0));
+        let mut rx = rx.with_clip_counter(clips.clone());
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![30000i16, 30000, -30000]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![30000i16, 30000, -30000]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![-30000i16, 10000, 10000]));
+        // saturating as it goes would give 2767
+        assert_eq!(rx.next(), Some(30000));
+        assert_eq!(clips.load(Ordering::Relaxed), 0);
+        assert_eq!(rx.next(), Some(i16::MAX));
+        assert_eq!(rx.next(), Some(-i16::MAX - 1));
+        assert_eq!(clips.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn wide_mode_keeps_floats_as_they_are() {
+        let (tx, rx) = dynamic_mixer::mixer_with_mode(1, 48000, MixMode::Wide);
+        let clips = Arc::new(AtomicUsize::new(0));
+        let mut rx = rx.with_clip_counter(clips.clone());
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![0.75f32, 0.25]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![0.75f32, 0.25]));
+        assert_eq!(rx.next(), Some(1.5));
+        assert_eq!(rx.next(), Some(0.5));
+        assert_eq!(clips.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn seekable_once_every_source_is() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        tx.add(SamplesBuffer::new(1, 48000, vec![1i16, 2, 3, 4]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![1i16]).buffered());
+
+        assert_eq!(rx.next(), Some(2));
+        assert!(!rx.is_seekable());
+        assert_eq!(rx.next(), Some(2));
+        assert!(rx.is_seekable());
+    }
+
+    #[test]
+    fn sources_move_to_another_mixer() {
+        let (tx, mut old) = dynamic_mixer::mixer(1, 48000);
+        let handle = tx.add(SamplesBuffer::new(1, 48000, vec![1i16, 2, 3, 4]));
+        tx.add_at(SamplesBuffer::new(1, 48000, vec![10i16]), 100);
+        assert_eq!(old.next(), Some(1));
+
+        let sources = old.take_sources();
+        assert_eq!(sources.len(), 2);
+        assert!(handle.is_done());
+        assert!(old.is_idle());
+        assert_eq!(old.next(), None);
+
+        // the new device has two channels
+        let (_, mut new) = dynamic_mixer::mixer(2, 48000);
+        let handles = new.adopt_sources(sources);
+        assert_eq!(handles.len(), 2);
+        assert!(!new.is_idle());
+        let output: Vec<i16> = new.by_ref().collect();
+        assert_eq!(output, [12, 12, 3, 3, 4, 4]);
+        assert!(handles.iter().all(|handle| handle.is_done()));
+    }
+
+    #[test]
+    fn taken_sources_hand_over_whole_frames() {
+        let (tx, mut old) = dynamic_mixer::mixer(2, 48000);
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16, -1, 2, -2, 3, -3]));
+        assert_eq!(old.next(), Some(1));
+
+        let (_, mut new) = dynamic_mixer::mixer(2, 48000);
+        new.adopt_sources(old.take_sources());
+        assert_eq!(new.collect::<Vec<_>>(), [2, -2, 3, -3]);
     }
 }
@@ -115,6 +115,11 @@
 //! The "noise" feature adds support for white and pink noise sources. This feature requires the
 //! "rand" crate.
 //!
+//! ### Feature "opus"
+//!
+//! The "opus" feature adds support for Opus in Ogg files. The decoding is done by libopus,
+//! through the "opus" crate, so the library needs to be available when building.
+//!
 //! ## How it works under the hood
 //!
 //! Rodio spawns a background thread that is dedicated to reading from the sources and sending
@@ -129,7 +134,7 @@
 #![cfg_attr(test, deny(missing_docs))]
 pub use cpal::{
     self, traits::DeviceTrait, Device, Devices, DevicesError, InputDevices, OutputDevices,
-    SupportedStreamConfig,
+    SampleFormat, SupportedStreamConfig,
 };
 
 mod conversions;
@@ -144,9 +149,11 @@ pub mod queue;
 pub mod source;
 pub mod static_buffer;
 
-pub use crate::conversions::Sample;
+pub use crate::conversions::{Sample, WideSample};
 pub use crate::decoder::Decoder;
 pub use crate::sink::Sink;
 pub use crate::source::Source;
 pub use crate::spatial_sink::SpatialSink;
-pub use crate::stream::{OutputStream, OutputStreamHandle, PlayError, StreamError};
+pub use crate::stream::{
+    OutputStream, OutputStreamHandle, OutputStreamOptions, PlayError, StreamError,
+};
@@ -182,6 +182,11 @@ where
     //
     // A final pain point is that we would need the total duration for the
     // next few songs.
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.current.is_seekable()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.current.try_seek(pos)
@@ -14,7 +14,7 @@
 //
 
 use super::SeekError;
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 #[cfg(feature = "experimental")]
 use atomic_float::AtomicF32;
 #[cfg(feature = "experimental")]
@@ -483,8 +483,23 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.input.playback_pos()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
 }
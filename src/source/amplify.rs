@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 use super::SeekError;
 
@@ -96,8 +96,28 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.input.playback_pos()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
 }
@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Balance` object.
+pub fn balance<I>(input: I, balance: f32) -> Balance<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert_eq!(
+        input.channels(),
+        2,
+        "the balance can only be changed on a stereo source"
+    );
+    assert_balance(balance);
+    Balance {
+        input,
+        balance,
+        current_channel: 0,
+    }
+}
+
+fn assert_balance(balance: f32) {
+    assert!(
+        (-1.0..=1.0).contains(&balance),
+        "the balance must be between -1.0 and 1.0, got {balance}"
+    );
+}
+
+/// Filter that turns down one channel of a stereo source, see [`Source::balance`].
+#[derive(Clone, Debug)]
+pub struct Balance<I> {
+    input: I,
+    balance: f32,
+    // The channel of the next sample, 0 for the left one.
+    current_channel: u16,
+}
+
+impl<I> Balance<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies the balance, from `-1.0` to `1.0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `balance` is not between `-1.0` and `1.0`.
+    #[inline]
+    pub fn set_balance(&mut self, balance: f32) {
+        assert_balance(balance);
+        self.balance = balance;
+    }
+
+    /// Returns the balance.
+    #[inline]
+    pub fn balance(&self) -> f32 {
+        self.balance
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Balance<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let sample = self.input.next()?;
+        let gain = match self.current_channel {
+            // turned down when the balance leans right
+            0 => 1.0 - self.balance.max(0.0),
+            _ => 1.0 + self.balance.min(0.0),
+        };
+        self.current_channel = 1 - self.current_channel;
+        Some(sample.amplify(gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Balance<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Balance<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    fn centered() -> SamplesBuffer<f32> {
+        SamplesBuffer::new(2, 44100, [0.5f32, 0.5].repeat(4))
+    }
+
+    #[test]
+    fn full_left_mutes_the_right_channel() {
+        let output: Vec<f32> = centered().balance(-1.0).collect();
+        assert_eq!(output, [0.5, 0.0].repeat(4));
+    }
+
+    #[test]
+    fn only_one_side_is_turned_down() {
+        assert_eq!(
+            centered().balance(0.0).collect::<Vec<_>>(),
+            [0.5, 0.5].repeat(4)
+        );
+        assert_eq!(
+            centered().balance(0.5).collect::<Vec<_>>(),
+            [0.25, 0.5].repeat(4)
+        );
+        assert_eq!(
+            centered().balance(-0.5).collect::<Vec<_>>(),
+            [0.5, 0.25].repeat(4)
+        );
+    }
+
+    #[test]
+    fn seeking_keeps_the_channels() {
+        let mut balanced = SamplesBuffer::new(2, 10, vec![1i16, 2, 3, 4, 5, 6]).balance(1.0);
+        assert_eq!(balanced.next(), Some(0));
+        balanced.try_seek(Duration::from_millis(100)).unwrap();
+        assert_eq!(balanced.collect::<Vec<_>>(), vec![0, 4, 0, 6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "stereo source")]
+    fn needs_two_channels() {
+        SamplesBuffer::new(1, 44100, vec![0.0f32; 4]).balance(0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "between -1.0 and 1.0")]
+    fn rejects_out_of_range() {
+        centered().balance(1.5);
+    }
+}
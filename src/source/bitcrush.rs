@@ -0,0 +1,236 @@
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Bitcrush` object.
+pub fn bitcrush<I>(input: I, bits: u8, downsample: u32) -> Bitcrush<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    assert!(bits >= 1, "samples need at least one bit");
+    assert!(downsample >= 1, "the samples can not be held for 0 frames");
+    let full_bits = std::mem::size_of::<I::Item>() * 8;
+    Bitcrush {
+        input,
+        quantize: (bits as usize) < full_bits,
+        levels: 2f32.powi(bits as i32 - 1),
+        downsample,
+        held: Vec::new(),
+        frames: 0,
+        current_channel: 0,
+    }
+}
+
+/// Filter that lowers the bit depth and the sample rate of a source without smoothing, see
+/// [`Source::bitcrush`].
+#[derive(Clone, Debug)]
+pub struct Bitcrush<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // Whether the samples are quantized, not when they already have `bits` or less.
+    quantize: bool,
+    // Half the number of levels, the positive samples have one less.
+    levels: f32,
+    downsample: u32,
+    // The samples repeated until the next frame that is kept, one per channel.
+    held: Vec<I::Item>,
+    frames: u64,
+    current_channel: u16,
+}
+
+impl<I> Bitcrush<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    #[inline]
+    fn crush(&self, sample: I::Item) -> I::Item {
+        if !self.quantize {
+            return sample;
+        }
+        let level = (sample.to_f32() * self.levels)
+            .round()
+            .clamp(-self.levels, self.levels - 1.0);
+        I::Item::from_sample_(level / self.levels)
+    }
+}
+
+impl<I> Iterator for Bitcrush<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let channels = self.input.channels().max(1);
+        if self.current_channel == 0 && self.held.len() != channels as usize {
+            // The format can only change between frames, a new frame is kept then.
+            self.held.clear();
+            self.frames = 0;
+        }
+
+        let sample = self.input.next()?;
+        let channel = self.current_channel as usize;
+        if self.frames.is_multiple_of(self.downsample as u64) {
+            let crushed = self.crush(sample);
+            match self.held.get_mut(channel) {
+                Some(held) => *held = crushed,
+                None => self.held.push(crushed),
+            }
+        }
+        let output = self.held[channel];
+
+        self.current_channel += 1;
+        if self.current_channel >= channels {
+            self.current_channel = 0;
+            self.frames += 1;
+        }
+        Some(output)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Bitcrush<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample + FromSample<f32>,
+{
+}
+
+impl<I> Source for Bitcrush<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // The first frame after the seek is kept.
+        self.held.clear();
+        self.frames = 0;
+        self.current_channel = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::f32::consts::TAU;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    fn sine() -> SamplesBuffer<f32> {
+        let samples: Vec<f32> = (0..1000)
+            .map(|i| (TAU * 3.0 * i as f32 / 1000.0).sin())
+            .collect();
+        SamplesBuffer::new(1, 1000, samples)
+    }
+
+    #[test]
+    fn takes_as_many_levels_as_bits_allow() {
+        for bits in [1, 2, 3, 4] {
+            let step = 1.0 / (1 << (bits - 1)) as f32;
+            let levels: BTreeSet<i32> = sine()
+                .bitcrush(bits, 1)
+                .map(|sample| {
+                    assert!((-1.0..1.0).contains(&sample), "{sample}");
+                    let level = sample / step;
+                    assert_eq!(level.fract(), 0.0, "{sample}");
+                    level as i32
+                })
+                .collect();
+            assert_eq!(levels.len(), 1 << bits, "{bits} bits");
+        }
+    }
+
+    #[test]
+    fn full_depth_passes_through() {
+        let data: Vec<i16> = (0..100).map(|i| i * 320 - 16000).collect();
+        let source = SamplesBuffer::new(2, 1000, data.clone());
+        assert_eq!(source.bitcrush(16, 1).collect::<Vec<_>>(), data);
+        let output: Vec<f32> = sine().bitcrush(32, 1).collect();
+        assert_eq!(output, sine().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn holds_each_channel() {
+        let source = SamplesBuffer::new(2, 1000, (1i16..=10).collect::<Vec<_>>());
+        let output: Vec<i16> = source.bitcrush(16, 3).collect();
+        assert_eq!(output, [1, 2, 1, 2, 1, 2, 7, 8, 7, 8]);
+    }
+
+    #[test]
+    #[should_panic(expected = "0 frames")]
+    fn rejects_no_downsampling() {
+        sine().bitcrush(8, 0);
+    }
+}
@@ -1,7 +1,7 @@
 use std::f32::consts::PI;
 use std::time::Duration;
 
-use crate::Source;
+use crate::{SampleFormat, Source};
 
 use super::SeekError;
 
@@ -177,20 +177,43 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.input.playback_pos()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
 }
 
 #[derive(Clone, Debug)]
-enum BltFormula {
+pub(super) enum BltFormula {
     LowPass { freq: u32, q: f32 },
     HighPass { freq: u32, q: f32 },
+    LowShelf { freq: u32, gain_db: f32 },
+    Peaking { freq: u32, q: f32, gain_db: f32 },
+    HighShelf { freq: u32, gain_db: f32 },
 }
 
 impl BltFormula {
-    fn to_applier(&self, sampling_frequency: u32) -> BltApplier {
+    pub(super) fn to_applier(&self, sampling_frequency: u32) -> BltApplier {
         match *self {
             BltFormula::LowPass { freq, q } => {
                 let w0 = 2.0 * PI * freq as f32 / sampling_frequency as f32;
@@ -223,6 +246,72 @@ impl BltFormula {
                 let a1 = -2.0 * cos_w0;
                 let a2 = 1.0 - alpha;
 
+                BltApplier {
+                    b0: b0 / a0,
+                    b1: b1 / a0,
+                    b2: b2 / a0,
+                    a1: a1 / a0,
+                    a2: a2 / a0,
+                }
+            }
+            BltFormula::LowShelf { freq, gain_db } => {
+                let a = 10f32.powf(gain_db / 40.0);
+                let w0 = 2.0 * PI * freq as f32 / sampling_frequency as f32;
+                let cos_w0 = w0.cos();
+                // shelf slope of 1
+                let alpha = w0.sin() / 2.0 * 2f32.sqrt();
+                let two_sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+                let b0 = a * ((a + 1.0) - (a - 1.0) * cos_w0 + two_sqrt_a_alpha);
+                let b1 = 2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0);
+                let b2 = a * ((a + 1.0) - (a - 1.0) * cos_w0 - two_sqrt_a_alpha);
+                let a0 = (a + 1.0) + (a - 1.0) * cos_w0 + two_sqrt_a_alpha;
+                let a1 = -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0);
+                let a2 = (a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha;
+
+                BltApplier {
+                    b0: b0 / a0,
+                    b1: b1 / a0,
+                    b2: b2 / a0,
+                    a1: a1 / a0,
+                    a2: a2 / a0,
+                }
+            }
+            BltFormula::Peaking { freq, q, gain_db } => {
+                let a = 10f32.powf(gain_db / 40.0);
+                let w0 = 2.0 * PI * freq as f32 / sampling_frequency as f32;
+                let alpha = w0.sin() / (2.0 * q);
+
+                let b0 = 1.0 + alpha * a;
+                let b1 = -2.0 * w0.cos();
+                let b2 = 1.0 - alpha * a;
+                let a0 = 1.0 + alpha / a;
+                let a1 = b1;
+                let a2 = 1.0 - alpha / a;
+
+                BltApplier {
+                    b0: b0 / a0,
+                    b1: b1 / a0,
+                    b2: b2 / a0,
+                    a1: a1 / a0,
+                    a2: a2 / a0,
+                }
+            }
+            BltFormula::HighShelf { freq, gain_db } => {
+                let a = 10f32.powf(gain_db / 40.0);
+                let w0 = 2.0 * PI * freq as f32 / sampling_frequency as f32;
+                let cos_w0 = w0.cos();
+                // shelf slope of 1
+                let alpha = w0.sin() / 2.0 * 2f32.sqrt();
+                let two_sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+                let b0 = a * ((a + 1.0) + (a - 1.0) * cos_w0 + two_sqrt_a_alpha);
+                let b1 = -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0);
+                let b2 = a * ((a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha);
+                let a0 = (a + 1.0) - (a - 1.0) * cos_w0 + two_sqrt_a_alpha;
+                let a1 = 2.0 * ((a - 1.0) - (a + 1.0) * cos_w0);
+                let a2 = (a + 1.0) - (a - 1.0) * cos_w0 - two_sqrt_a_alpha;
+
                 BltApplier {
                     b0: b0 / a0,
                     b1: b1 / a0,
@@ -236,7 +325,7 @@ impl BltFormula {
 }
 
 #[derive(Clone, Debug)]
-struct BltApplier {
+pub(super) struct BltApplier {
     b0: f32,
     b1: f32,
     b2: f32,
@@ -246,7 +335,7 @@ struct BltApplier {
 
 impl BltApplier {
     #[inline]
-    fn apply(&self, x_n: f32, x_n1: f32, x_n2: f32, y_n1: f32, y_n2: f32) -> f32 {
+    pub(super) fn apply(&self, x_n: f32, x_n1: f32, x_n2: f32, y_n1: f32, y_n2: f32) -> f32 {
         self.b0 * x_n + self.b1 * x_n1 + self.b2 * x_n2 - self.a1 * y_n1 - self.a2 * y_n2
     }
 }
@@ -3,7 +3,7 @@ use std::mem;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 use super::SeekError;
 
@@ -15,12 +15,14 @@ where
     I::Item: Sample,
 {
     let total_duration = input.total_duration();
+    let sample_format = input.sample_format();
     let first_frame = extract(input);
 
     Buffered {
         current_frame: first_frame,
         position_in_frame: 0,
         total_duration,
+        sample_format,
     }
 }
 
@@ -38,6 +40,9 @@ where
 
     /// Obtained once at creation and never modified again.
     total_duration: Option<Duration>,
+
+    /// Obtained once at creation and never modified again.
+    sample_format: SampleFormat,
 }
 
 enum Frame<I>
@@ -250,6 +255,11 @@ where
             underlying_source: std::any::type_name::<Self>(),
         })
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.sample_format
+    }
 }
 
 impl<I> Clone for Buffered<I>
@@ -263,6 +273,7 @@ where
             current_frame: self.current_frame.clone(),
             position_in_frame: self.position_in_frame,
             total_duration: self.total_duration,
+            sample_format: self.sample_format,
         }
     }
 }
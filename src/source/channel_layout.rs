@@ -0,0 +1,43 @@
+/// Describes which speaker each channel of an interleaved frame is meant for.
+///
+/// The channel orders follow the WAV/SMPTE convention used by most decoders.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelLayout {
+    /// A single channel.
+    Mono,
+    /// Front left, front right.
+    Stereo,
+    /// Front left, front right, back left, back right.
+    Quad,
+    /// Front left, front right, center, low frequency effects, side left, side right.
+    Surround5_1,
+    /// Front left, front right, center, low frequency effects, back left, back right, side left,
+    /// side right.
+    Surround7_1,
+}
+
+impl ChannelLayout {
+    /// Returns the usual layout for a channels count, if there is one.
+    pub fn from_channels(channels: u16) -> Option<ChannelLayout> {
+        match channels {
+            1 => Some(ChannelLayout::Mono),
+            2 => Some(ChannelLayout::Stereo),
+            4 => Some(ChannelLayout::Quad),
+            6 => Some(ChannelLayout::Surround5_1),
+            8 => Some(ChannelLayout::Surround7_1),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of channels in a frame of this layout.
+    pub fn channels(&self) -> u16 {
+        match self {
+            ChannelLayout::Mono => 1,
+            ChannelLayout::Stereo => 2,
+            ChannelLayout::Quad => 4,
+            ChannelLayout::Surround5_1 => 6,
+            ChannelLayout::Surround7_1 => 8,
+        }
+    }
+}
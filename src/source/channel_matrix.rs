@@ -0,0 +1,206 @@
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `ChannelMatrix` object.
+pub fn channel_matrix<I>(input: I, matrix: Vec<Vec<f32>>) -> ChannelMatrix<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    ChannelMatrix::new(input, matrix)
+}
+
+/// Maps the channels of a source to new output channels, through a matrix of gains.
+#[derive(Clone, Debug)]
+pub struct ChannelMatrix<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // For each output channel, the weight of every input channel.
+    coefficients: Vec<Vec<f32>>,
+    // Input frame the output channels are computed from.
+    frame: Vec<I::Item>,
+    // Next output channel to be produced, the next frame is read from the input at 0.
+    current_channel: usize,
+}
+
+impl<I> ChannelMatrix<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Builds a new `ChannelMatrix`, with a gain for every input channel in each output
+    /// channel.
+    pub(super) fn new(input: I, coefficients: Vec<Vec<f32>>) -> ChannelMatrix<I> {
+        let in_channels = input.channels() as usize;
+        assert!(
+            !coefficients.is_empty(),
+            "the matrix needs at least one output channel"
+        );
+        assert!(
+            coefficients.iter().all(|gains| gains.len() == in_channels),
+            "every output channel needs a gain for each of the {} input channels",
+            in_channels
+        );
+
+        ChannelMatrix {
+            input,
+            coefficients,
+            frame: vec![I::Item::zero_value(); in_channels],
+            current_channel: 0,
+        }
+    }
+
+    // Reads the next frame of the input, returns false if the input has ended.
+    fn next_frame(&mut self) -> bool {
+        for (i, sample) in self.frame.iter_mut().enumerate() {
+            match self.input.next() {
+                Some(s) => *sample = s,
+                // A frame cut short is completed with silence.
+                None if i > 0 => *sample = I::Item::zero_value(),
+                None => return false,
+            }
+        }
+        true
+    }
+
+    // Output samples left in the frame that was read from the input.
+    #[inline]
+    fn buffered(&self) -> usize {
+        match self.current_channel {
+            0 => 0,
+            channel => self.coefficients.len() - channel,
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for ChannelMatrix<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel == 0 && !self.next_frame() {
+            return None;
+        }
+
+        let sample = self.coefficients[self.current_channel]
+            .iter()
+            .zip(&self.frame)
+            .filter(|(weight, _)| **weight != 0.0)
+            .fold(I::Item::zero_value(), |acc, (weight, s)| {
+                acc.saturating_add(s.amplify(*weight))
+            });
+
+        self.current_channel = (self.current_channel + 1) % self.coefficients.len();
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.input.size_hint();
+        let in_channels = self.frame.len();
+        let out_channels = self.coefficients.len();
+        let buffered = self.buffered();
+        (
+            min / in_channels * out_channels + buffered,
+            max.map(|max| max.div_ceil(in_channels) * out_channels + buffered),
+        )
+    }
+}
+
+impl<I> Source for ChannelMatrix<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input
+            .current_frame_len()
+            .map(|len| len / self.frame.len() * self.coefficients.len() + self.buffered())
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.coefficients.len() as u16
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.current_channel = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn routes_channels() {
+        // the first channel goes to outputs 0 and 2, the second one to output 1
+        let source = SamplesBuffer::new(2, 48000, vec![0.5f32, 0.25, -0.5, 1.0]);
+        let routed = source.channel_matrix(vec![vec![1.0, 0.0], vec![0.0, 0.5], vec![0.5, 0.0]]);
+        assert_eq!(routed.channels(), 3);
+        assert_eq!(
+            routed.collect::<Vec<_>>(),
+            vec![0.5, 0.125, 0.25, -0.5, 0.5, -0.25]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "needs a gain for each of the 2 input channels")]
+    fn matrix_must_match_the_channels() {
+        let source = SamplesBuffer::new(2, 48000, vec![0i16; 4]);
+        let _ = source.channel_matrix(vec![vec![1.0]]);
+    }
+}
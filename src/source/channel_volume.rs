@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 use super::SeekError;
 
@@ -28,27 +28,19 @@ where
     /// Wrap the input source and make it mono. Play that mono sound to each
     /// channel at the volume set by the user. The volume can be changed using
     /// [`ChannelVolume::set_volume`].
-    pub fn new(mut input: I, channel_volumes: Vec<f32>) -> ChannelVolume<I>
+    pub fn new(input: I, channel_volumes: Vec<f32>) -> ChannelVolume<I>
     where
         I: Source,
         I::Item: Sample,
     {
-        let mut sample = None;
-        for _ in 0..input.channels() {
-            if let Some(s) = input.next() {
-                sample = Some(
-                    sample
-                        .get_or_insert_with(I::Item::zero_value)
-                        .saturating_add(s),
-                );
-            }
-        }
-        ChannelVolume {
+        let mut ret = ChannelVolume {
             input,
             channel_volumes,
             current_channel: 0,
-            current_sample: sample,
-        }
+            current_sample: None,
+        };
+        ret.next_input_frame();
+        ret
     }
 
     /// Sets the volume for a given channel number. Will panic if channel number
@@ -74,6 +66,20 @@ where
     pub fn into_inner(self) -> I {
         self.input
     }
+
+    // Mixes the next frame of the input into `current_sample`.
+    fn next_input_frame(&mut self) {
+        self.current_sample = None;
+        for _ in 0..self.input.channels() {
+            if let Some(s) = self.input.next() {
+                self.current_sample = Some(
+                    self.current_sample
+                        .get_or_insert_with(I::Item::zero_value)
+                        .saturating_add(s),
+                );
+            }
+        }
+    }
 }
 
 impl<I> Iterator for ChannelVolume<I>
@@ -91,16 +97,7 @@ where
         self.current_channel += 1;
         if self.current_channel >= self.channel_volumes.len() {
             self.current_channel = 0;
-            self.current_sample = None;
-            for _ in 0..self.input.channels() {
-                if let Some(s) = self.input.next() {
-                    self.current_sample = Some(
-                        self.current_sample
-                            .get_or_insert_with(I::Item::zero_value)
-                            .saturating_add(s),
-                    );
-                }
-            }
+            self.next_input_frame();
         }
         ret
     }
@@ -143,8 +140,41 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
-        self.input.try_seek(pos)
+        self.input.try_seek(pos)?;
+        // The sample mixed before the seek is dropped, the output starts a new frame.
+        self.current_channel = 0;
+        self.next_input_frame();
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ChannelVolume;
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    #[test]
+    fn seek_in_the_middle_of_a_frame() {
+        let input = SamplesBuffer::new(2, 1, vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let mut source = ChannelVolume::new(input, vec![1.0, 0.5]);
+        assert_eq!(source.next(), Some(3.0));
+
+        source.try_seek(Duration::from_secs(2)).unwrap();
+        assert_eq!(source.collect::<Vec<_>>(), vec![11.0, 5.5]);
     }
 }
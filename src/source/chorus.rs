@@ -0,0 +1,235 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::delay_line::DelayLine;
+use super::SeekError;
+
+/// Delay of the voices when they are not modulated, long enough for them to be heard apart
+/// from the dry sound but too short to be heard as an echo.
+const BASE_DELAY: Duration = Duration::from_millis(15);
+
+/// Internal function that builds a `Chorus` object.
+pub fn chorus<I>(input: I, rate_hz: f32, depth: Duration, voices: u16) -> Chorus<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    assert!(voices >= 1, "a chorus needs at least one voice");
+    let mut chorus = Chorus {
+        input,
+        rate_hz,
+        depth,
+        voices,
+        phase: 0.0,
+        delay: DelayLine::new(0, 0.0),
+        delays: vec![0.0; voices as usize],
+        current_channel: 0,
+    };
+    chorus.reset();
+    chorus
+}
+
+/// Filter that thickens a source with copies of it played through delays that keep
+/// changing, see [`Source::chorus`].
+#[derive(Clone, Debug)]
+pub struct Chorus<I> {
+    input: I,
+    rate_hz: f32,
+    depth: Duration,
+    voices: u16,
+    // Phase of the oscillator of the first voice, in cycles.
+    phase: f32,
+    delay: DelayLine,
+    // Delay of each voice for the current frame, in frames.
+    delays: Vec<f32>,
+    current_channel: usize,
+}
+
+impl<I> Chorus<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    // Forgets the previous samples, and adapts to the format of the input.
+    fn reset(&mut self) {
+        let channels = self.input.channels().max(1) as usize;
+        let longest = (BASE_DELAY + self.depth).as_secs_f32() * self.input.sample_rate() as f32;
+        self.delay = DelayLine::new(channels, longest);
+        self.current_channel = 0;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Chorus<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel == 0 {
+            // The format can only change between frames.
+            if self.delay.channels() != self.input.channels().max(1) as usize {
+                self.reset();
+            }
+            let sample_rate = self.input.sample_rate() as f32;
+            let base = BASE_DELAY.as_secs_f32() * sample_rate;
+            let depth = self.depth.as_secs_f32() * sample_rate;
+            // The oscillators of the voices are spread evenly over a cycle.
+            for (voice, delay) in self.delays.iter_mut().enumerate() {
+                let phase = self.phase + voice as f32 / self.voices as f32;
+                *delay = base + depth * (1.0 - (TAU * phase).cos()) / 2.0;
+            }
+            self.phase = (self.phase + self.rate_hz / sample_rate).fract();
+        }
+
+        let sample = self.input.next()?.to_f32();
+        let channel = self.current_channel;
+        self.delay.write(channel, sample);
+        let wet: f32 = self
+            .delays
+            .iter()
+            .map(|&delay| self.delay.read(channel, delay))
+            .sum();
+        let output = (sample + wet) / (self.voices as f32 + 1.0);
+
+        self.current_channel += 1;
+        if self.current_channel >= self.delay.channels() {
+            self.current_channel = 0;
+            self.delay.advance();
+        }
+        Some(I::Item::from_sample_(output))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Chorus<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample + FromSample<f32>,
+{
+}
+
+impl<I> Source for Chorus<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.reset();
+        self.phase = (pos.as_secs_f32() * self.rate_hz).fract();
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn voices_are_delayed_copies() {
+        const RATE: u32 = 1000;
+        let mut impulse = vec![0.0f32; 100];
+        impulse[0] = 1.0;
+        let output: Vec<f32> = SamplesBuffer::new(1, RATE, impulse)
+            .chorus(1.0, Duration::ZERO, 3)
+            .collect();
+
+        // the voices come 15 ms late
+        assert_eq!(output[0], 0.25);
+        assert!((output[15] - 0.75).abs() < 1e-6, "{}", output[15]);
+        assert!(output
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != 0 && i != 15)
+            .all(|(_, &sample)| sample == 0.0));
+    }
+
+    #[test]
+    fn keeps_the_channels_apart() {
+        let data: Vec<f32> = [1.0, 0.0].repeat(50);
+        let output: Vec<f32> = SamplesBuffer::new(2, 1000, data)
+            .chorus(2.0, Duration::from_millis(5), 2)
+            .collect();
+        assert_eq!(output.len(), 100);
+        assert!(output
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .all(|&sample| sample == 0.0));
+        assert!(output.iter().step_by(2).all(|&sample| sample > 0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one voice")]
+    fn needs_a_voice() {
+        SamplesBuffer::new(1, 1000, vec![0.0f32; 4]).chorus(1.0, Duration::ZERO, 0);
+    }
+}
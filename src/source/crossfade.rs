@@ -1,8 +1,10 @@
+use std::collections::VecDeque;
+use std::f32::consts::FRAC_PI_2;
 use std::time::Duration;
 
 use cpal::FromSample;
 
-use crate::source::{FadeIn, Mix, TakeDuration};
+use crate::source::{FadeIn, Mix, TakeDuration, UniformSourceIterator};
 use crate::{Sample, Source};
 
 /// Mixes one sound fading out with another sound fading in for the given
@@ -10,7 +12,7 @@ use crate::{Sample, Source};
 ///
 /// Only the crossfaded portion (beginning of fadeout, beginning of fadein) is
 /// returned.
-pub fn crossfade<I1, I2>(
+pub fn take_crossfade<I1, I2>(
     input_fadeout: I1,
     input_fadein: I2,
     duration: Duration,
@@ -34,6 +36,161 @@ where
 /// covered.
 pub type Crossfade<I1, I2> = Mix<TakeDuration<I1>, FadeIn<TakeDuration<I2>>>;
 
+/// Plays `outgoing`, then transitions to `incoming` by fading the end of the first one out
+/// while the second one fades in over `duration`.
+///
+/// The curves are equal-power (a quarter of a cosine and of a sine), so there is no dip in
+/// loudness in the middle of the transition. Both sources are converted to the channels
+/// count and sample rate of `outgoing`.
+///
+/// The end of `outgoing` is found by reading `duration` ahead of what is played, so that
+/// part of it is held in memory. If one source is shorter than `duration`, the transition
+/// lasts as long as the shorter one.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use rodio::source::{crossfade, SineWave, Source};
+///
+/// let first = SineWave::new(440.0).take_duration(Duration::from_secs(3));
+/// let second = SineWave::new(660.0).take_duration(Duration::from_secs(3));
+/// // Plays for 5 seconds in total.
+/// let both = crossfade(first, second, Duration::from_secs(1));
+/// ```
+pub fn crossfade<I1, I2>(
+    outgoing: I1,
+    incoming: I2,
+    duration: Duration,
+) -> CrossfadeTransition<I1, I2>
+where
+    I1: Source,
+    I1::Item: FromSample<I2::Item> + Sample,
+    I2: Source,
+    I2::Item: Sample,
+{
+    let channels = outgoing.channels();
+    let sample_rate = outgoing.sample_rate();
+    let overlap_frames = (duration.as_secs_f64() * sample_rate as f64).round() as usize;
+
+    let total_duration = match (outgoing.total_duration(), incoming.total_duration()) {
+        (Some(out), Some(in_)) => Some(out + in_ - duration.min(out).min(in_)),
+        _ => None,
+    };
+
+    CrossfadeTransition {
+        outgoing: UniformSourceIterator::new(outgoing, channels, sample_rate),
+        incoming: UniformSourceIterator::new(incoming, channels, sample_rate),
+        lookahead: VecDeque::with_capacity(overlap_frames * channels as usize + 1),
+        overlap: overlap_frames * channels as usize,
+        fade: None,
+        channels,
+        sample_rate,
+        total_duration,
+    }
+}
+
+/// Transition from one source to another with an equal-power crossfade.
+///
+/// Built with [`crossfade`].
+#[derive(Clone)]
+pub struct CrossfadeTransition<I1, I2>
+where
+    I1: Source,
+    I1::Item: FromSample<I2::Item> + Sample,
+    I2: Source,
+    I2::Item: Sample,
+{
+    outgoing: UniformSourceIterator<I1, I1::Item>,
+    incoming: UniformSourceIterator<I2, I1::Item>,
+    // The last samples read from `outgoing`, not played yet.
+    lookahead: VecDeque<I1::Item>,
+    // Number of samples the transition lasts.
+    overlap: usize,
+    // Once `outgoing` has ended: number of samples faded so far and in total.
+    fade: Option<(usize, usize)>,
+    channels: u16,
+    sample_rate: u32,
+    total_duration: Option<Duration>,
+}
+
+impl<I1, I2> Iterator for CrossfadeTransition<I1, I2>
+where
+    I1: Source,
+    I1::Item: FromSample<I2::Item> + Sample,
+    I2: Source,
+    I2::Item: Sample,
+{
+    type Item = I1::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I1::Item> {
+        if self.fade.is_none() {
+            while self.lookahead.len() <= self.overlap {
+                match self.outgoing.next() {
+                    Some(sample) => self.lookahead.push_back(sample),
+                    None => {
+                        self.fade = Some((0, self.lookahead.len()));
+                        break;
+                    }
+                }
+            }
+            if self.fade.is_none() {
+                return self.lookahead.pop_front();
+            }
+        }
+
+        let Some(outgoing) = self.lookahead.pop_front() else {
+            return self.incoming.next();
+        };
+
+        let (faded, total) = self.fade.as_mut().unwrap();
+        // Both curves only move from one frame to the next.
+        let frame = (*faded / self.channels as usize) as f32;
+        let progress = frame / (*total / self.channels as usize) as f32 * FRAC_PI_2;
+        *faded += 1;
+
+        let outgoing = outgoing.amplify(progress.cos());
+        match self.incoming.next() {
+            Some(incoming) => Some(outgoing.saturating_add(incoming.amplify(progress.sin()))),
+            None => Some(outgoing),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.lookahead.len(), None)
+    }
+}
+
+impl<I1, I2> Source for CrossfadeTransition<I1, I2>
+where
+    I1: Source,
+    I1::Item: FromSample<I2::Item> + Sample,
+    I2: Source,
+    I2::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -47,7 +204,7 @@ mod tests {
     fn test_crossfade_with_self() {
         let source1 = dummysource(10);
         let source2 = dummysource(10);
-        let mut mixed = crossfade(
+        let mut mixed = take_crossfade(
             source1,
             source2,
             Duration::from_secs(5) + Duration::from_nanos(1),
@@ -64,7 +221,7 @@ mod tests {
     fn test_crossfade() {
         let source1 = dummysource(10);
         let source2 = dummysource(10).amplify(0.0);
-        let mut mixed = crossfade(
+        let mut mixed = take_crossfade(
             source1,
             source2,
             Duration::from_secs(5) + Duration::from_nanos(1),
@@ -76,4 +233,33 @@ mod tests {
         assert_eq!(mixed.next(), Some(5.0 * 0.2));
         assert_eq!(mixed.next(), None);
     }
+
+    fn dc(value: f32) -> SamplesBuffer<f32> {
+        SamplesBuffer::new(1, 10, vec![value; 10])
+    }
+
+    #[test]
+    fn test_crossfade_transition() {
+        let overlap = Duration::from_millis(500);
+        let transition = crossfade(dc(1.0), dc(1.0), overlap);
+        assert_eq!(
+            transition.total_duration(),
+            Some(Duration::from_millis(1500))
+        );
+
+        let mixed: Vec<f32> = transition.collect();
+        assert_eq!(mixed.len(), 15);
+        assert_eq!(&mixed[..5], &[1.0; 5]);
+        assert_eq!(&mixed[10..], &[1.0; 5]);
+
+        let fade_out: Vec<f32> = crossfade(dc(1.0), dc(0.0), overlap).collect();
+        let fade_in: Vec<f32> = crossfade(dc(0.0), dc(1.0), overlap).collect();
+        for i in 5..10 {
+            // the power stays the same and the level does not dip
+            let power = fade_out[i] * fade_out[i] + fade_in[i] * fade_in[i];
+            assert!((power - 1.0).abs() < 1e-5, "power {} at {}", power, i);
+            assert!(mixed[i] >= 1.0 - 1e-5, "level {} at {}", mixed[i], i);
+        }
+        assert!(fade_out[5] > fade_out[9]);
+    }
 }
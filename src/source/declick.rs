@@ -0,0 +1,243 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Declick` object.
+pub fn declick<I>(input: I, fade: Duration) -> Declick<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Declick {
+        input,
+        fade,
+        tail: VecDeque::new(),
+        crossfade: false,
+        fade_len: 0,
+        channels: 1,
+    }
+}
+
+/// Source that crossfades over the jump when it seeks, so that the jump does not click.
+///
+/// Before seeking, the samples that would have been played next are read from the inner
+/// source. They fade out with a Hann window while the ones after the seek fade in.
+#[derive(Clone, Debug)]
+pub struct Declick<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    fade: Duration,
+    // The samples of the inner source from before the last seek that are still to be played.
+    tail: VecDeque<I::Item>,
+    // Whether the tail is faded into the inner source, or played as is because the seek failed.
+    crossfade: bool,
+    // Number of samples in the tail when the crossfade started.
+    fade_len: usize,
+    channels: u16,
+}
+
+impl<I> Declick<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the duration of the crossfades.
+    #[inline]
+    pub fn fade(&self) -> Duration {
+        self.fade
+    }
+
+    /// Changes the duration of the crossfades, from the next seek on.
+    #[inline]
+    pub fn set_fade(&mut self, fade: Duration) {
+        self.fade = fade;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Declick<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let Some(old) = self.tail.pop_front() else {
+            return self.input.next();
+        };
+        if !self.crossfade {
+            return Some(old);
+        }
+
+        // The samples of a frame share the same gain.
+        let played = self.fade_len - self.tail.len() - 1;
+        let frames = self.fade_len as f32 / self.channels as f32;
+        let progress = (played / self.channels as usize) as f32 / frames;
+        let gain = 0.5 - 0.5 * (PI * progress).cos();
+
+        match self.input.next() {
+            Some(new) => Some(old.amplify(1.0 - gain).saturating_add(new.amplify(gain))),
+            // The source ended right after the seek, the tail only fades out.
+            None => Some(old.amplify(1.0 - gain)),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.input.size_hint();
+        let tail = self.tail.len();
+        if self.crossfade {
+            (lower.max(tail), upper.map(|upper| upper.max(tail)))
+        } else {
+            (lower + tail, upper.map(|upper| upper + tail))
+        }
+    }
+}
+
+impl<I> Source for Declick<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        match self.tail.len() {
+            0 => self.input.current_frame_len(),
+            // The format of the source may only change once the tail has played.
+            tail => Some(tail),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        match self.tail.is_empty() {
+            true => self.input.channels(),
+            false => self.channels,
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        // What is left of a previous crossfade is mixed in the new one.
+        let channels = self.channels();
+        let frames = (self.fade.as_secs_f32() * self.sample_rate() as f32) as usize;
+        let len = frames.max(1) * channels as usize;
+        let tail: VecDeque<I::Item> = self.by_ref().take(len).collect();
+
+        self.channels = channels;
+        let result = self.input.try_seek(pos);
+        // A failed seek still plays the samples read ahead, then the rest of the source.
+        self.crossfade = result.is_ok() && frames > 0;
+        self.fade_len = tail.len();
+        self.tail = tail;
+        result
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    const RATE: u32 = 48000;
+
+    fn sine(freq: f32) -> SamplesBuffer<f32> {
+        let samples = (0..RATE)
+            .map(|i| (std::f32::consts::TAU * freq * i as f32 / RATE as f32).sin())
+            .collect::<Vec<_>>();
+        SamplesBuffer::new(1, RATE, samples)
+    }
+
+    fn largest_step(samples: &[f32]) -> f32 {
+        samples
+            .windows(2)
+            .fold(0.0f32, |max, pair| max.max((pair[1] - pair[0]).abs()))
+    }
+
+    #[test]
+    fn plays_unchanged_without_seeking() {
+        let original: Vec<f32> = sine(440.0).collect();
+        let declicked: Vec<f32> = sine(440.0).declick(Duration::from_millis(5)).collect();
+        assert_eq!(declicked, original);
+    }
+
+    #[test]
+    fn seeking_does_not_jump() {
+        let mut source = sine(440.0);
+        let mut declicked = sine(440.0).declick(Duration::from_millis(5));
+
+        // the sine is at its peak before the seek, and crosses zero after it
+        let before: Vec<f32> = declicked.by_ref().take(RATE as usize / 1760).collect();
+        declicked.try_seek(Duration::from_millis(500)).unwrap();
+        let after: Vec<f32> = declicked.by_ref().take(RATE as usize / 10).collect();
+        let played = [before, after].concat();
+        assert!(largest_step(&played) < 0.1, "{}", largest_step(&played));
+
+        // without the crossfade, the seek jumps by the whole amplitude
+        let before: Vec<f32> = source.by_ref().take(RATE as usize / 1760).collect();
+        source.try_seek(Duration::from_millis(500)).unwrap();
+        let after: Vec<f32> = source.by_ref().take(RATE as usize / 10).collect();
+        assert!(largest_step(&[before, after].concat()) > 0.9);
+    }
+
+    #[test]
+    fn plays_the_new_position_after_the_fade() {
+        let expected: Vec<f32> = sine(440.0)
+            .skip(RATE as usize / 2 + 240)
+            .take(100)
+            .collect();
+
+        let mut declicked = sine(440.0).declick(Duration::from_millis(5));
+        declicked.by_ref().take(10).count();
+        declicked.try_seek(Duration::from_millis(500)).unwrap();
+        let played: Vec<f32> = declicked.skip(240).take(100).collect();
+        assert_eq!(played, expected);
+    }
+}
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 use super::SeekError;
 
@@ -111,6 +111,11 @@ where
             .map(|val| val + self.requested_duration)
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
     /// Pos is seen from the perspective of the api user.
     ///
     /// # Example
@@ -135,4 +140,9 @@ where
         let compensated_for_delay = pos.saturating_sub(self.requested_duration);
         self.input.try_seek(compensated_for_delay)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
 }
@@ -0,0 +1,57 @@
+/// The last frames of a source, read back at a delay that can fall between two frames.
+///
+/// Shared by the effects that play a sound through a delay that keeps changing.
+#[derive(Clone, Debug)]
+pub(crate) struct DelayLine {
+    samples: Vec<f32>,
+    frames: usize,
+    channels: usize,
+    write_frame: usize,
+}
+
+impl DelayLine {
+    /// Builds a silent delay line of `channels` channels, that can be read up to
+    /// `max_delay_frames` frames back.
+    pub(crate) fn new(channels: usize, max_delay_frames: f32) -> DelayLine {
+        // The sample being written, the two read around the delay, and one to spare.
+        let frames = max_delay_frames.max(0.0).ceil() as usize + 2;
+        DelayLine {
+            samples: vec![0.0; frames * channels],
+            frames,
+            channels,
+            write_frame: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Stores the sample of `channel` for the current frame.
+    #[inline]
+    pub(crate) fn write(&mut self, channel: usize, sample: f32) {
+        self.samples[self.write_frame * self.channels + channel] = sample;
+    }
+
+    /// Returns the sample of `channel` from `delay` frames before the current one, with linear
+    /// interpolation between the two frames around it.
+    ///
+    /// A delay of 0 is the sample written for the current frame. Before it is written, the
+    /// delay must be at least 1.
+    #[inline]
+    pub(crate) fn read(&self, channel: usize, delay: f32) -> f32 {
+        let whole = delay.floor();
+        let fraction = delay - whole;
+        let newer = (self.write_frame + self.frames - whole as usize) % self.frames;
+        let older = (newer + self.frames - 1) % self.frames;
+        self.samples[newer * self.channels + channel] * (1.0 - fraction)
+            + self.samples[older * self.channels + channel] * fraction
+    }
+
+    /// Moves on to the next frame, once every channel of the current one is written.
+    #[inline]
+    pub(crate) fn advance(&mut self) {
+        self.write_frame = (self.write_frame + 1) % self.frames;
+    }
+}
@@ -0,0 +1,302 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Dither` object.
+pub fn dither<I, D>(input: I, seed: u64, kind: DitherKind) -> Dither<I, D>
+where
+    I: Source,
+    I::Item: Sample,
+    D: Sample,
+{
+    // Integer samples use every bit for the amplitude, so one step of the
+    // target type is 1 / 2^(bits - 1) in the [-1.0, 1.0] range.
+    let bits = (std::mem::size_of::<D>() * 8) as i32;
+    let steps = 2f32.powi(bits - 1);
+
+    Dither {
+        input,
+        steps,
+        rng: XorShift::new(seed),
+        kind,
+        errors: Vec::new(),
+        current_channel: 0,
+        marker: PhantomData,
+    }
+}
+
+/// The noise added by [`Source::dither_with_kind`](crate::Source::dither_with_kind) before
+/// rounding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DitherKind {
+    /// Triangular dither of one least significant bit, spread evenly over all frequencies.
+    #[default]
+    Tpdf,
+    /// Triangular dither whose rounding error is fed back, through a second order filter, into
+    /// the next samples of the same channel. The noise is as loud overall, a bit louder
+    /// actually, but moved towards the highest frequencies where the ear is the least
+    /// sensitive, which makes it quieter to listen to. Best at sample rates of 44.1 kHz and
+    /// above.
+    NoiseShaped,
+}
+
+/// Converts the samples of a source to another type, adding triangular (TPDF) dither of one
+/// least significant bit of the target type before rounding.
+///
+/// This decorrelates the quantization error from the signal, which is most audible on quiet
+/// passages when reducing the bit depth, for example from `f32` to `i16`.
+#[derive(Clone, Debug)]
+pub struct Dither<I, D> {
+    input: I,
+    // Number of quantization steps of the target type between 0.0 and 1.0.
+    steps: f32,
+    rng: XorShift,
+    kind: DitherKind,
+    // The last two rounding errors of each channel, in steps, for noise shaping.
+    errors: Vec<[f32; 2]>,
+    current_channel: u16,
+    marker: PhantomData<D>,
+}
+
+impl<I, D> Dither<I, D> {
+    /// Returns the kind of dither added.
+    #[inline]
+    pub fn kind(&self) -> DitherKind {
+        self.kind
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I, D> Iterator for Dither<I, D>
+where
+    I: Source,
+    I::Item: Sample,
+    D: Sample + FromSample<f32>,
+{
+    type Item = D;
+
+    #[inline]
+    fn next(&mut self) -> Option<D> {
+        let channels = self.input.channels().max(1);
+        if self.current_channel == 0 && self.errors.len() != channels as usize {
+            // The format can only change between frames.
+            self.errors = vec![[0.0; 2]; channels as usize];
+        }
+
+        let mut sample = self.input.next()?.to_f32() * self.steps;
+        let errors = &mut self.errors[self.current_channel as usize];
+        if self.kind == DitherKind::NoiseShaped {
+            // The error ends up filtered by (1 - z^-1)^2, which rises towards the highest
+            // frequencies.
+            sample -= 2.0 * errors[0] - errors[1];
+        }
+        // The sum of two uniform distributions is triangular, it spans [-1, 1] LSB.
+        let noise = self.rng.next_f32() + self.rng.next_f32();
+        let rounded = (sample + noise).round();
+        // Taken before clamping, so that a clipped sample does not feed back.
+        *errors = [rounded - sample, errors[0]];
+
+        self.current_channel += 1;
+        if self.current_channel >= channels {
+            self.current_channel = 0;
+        }
+        let dithered = rounded / self.steps;
+        Some(D::from_sample(dithered.clamp(-1.0, 1.0)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, D> ExactSizeIterator for Dither<I, D>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    D: Sample + FromSample<f32>,
+{
+}
+
+impl<I, D> Source for Dither<I, D>
+where
+    I: Source,
+    I::Item: Sample,
+    D: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // The errors of the samples before the seek are not fed into the ones after.
+        self.errors.clear();
+        self.current_channel = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        // Dithered down to the output type, whatever the input was.
+        D::FORMAT
+    }
+}
+
+/// Small and fast pseudo random generator, good enough for dither noise.
+#[derive(Clone, Debug)]
+struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // A zero state would only ever produce zeros.
+        let state = seed ^ 0x9E37_79B9_7F4A_7C15;
+        XorShift {
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+
+    /// Returns a uniformly distributed value in [-0.5, 0.5).
+    #[inline]
+    fn next_f32(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        // The upper 24 bits fit exactly in the mantissa of an f32.
+        (self.state >> 40) as f32 / (1u32 << 24) as f32 - 0.5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DitherKind;
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    // Power of the rounding error in steps, after averaging it over 64 samples, which leaves
+    // the lowest frequencies.
+    fn low_frequency_noise(kind: DitherKind) -> f32 {
+        let steps = 32768.0;
+        let input: Vec<f32> = (0..48000)
+            .map(|i| (i as f32 / 48000.0 * 200.0).sin() * 10.0 / steps)
+            .collect();
+        let output: Vec<i16> = SamplesBuffer::new(1, 48000, input.clone())
+            .dither_with_kind(3, kind)
+            .collect();
+        let errors: Vec<f32> = output
+            .iter()
+            .zip(&input)
+            .map(|(&out, &sample)| out as f32 - sample * steps)
+            .collect();
+        let averages: Vec<f32> = errors
+            .chunks(64)
+            .map(|chunk| chunk.iter().sum::<f32>() / 64.0)
+            .collect();
+        averages
+            .iter()
+            .map(|average| average * average)
+            .sum::<f32>()
+            / averages.len() as f32
+    }
+
+    #[test]
+    fn noise_shaping_moves_the_noise_up() {
+        let flat = low_frequency_noise(DitherKind::Tpdf);
+        let shaped = low_frequency_noise(DitherKind::NoiseShaped);
+        assert!(shaped < flat / 8.0, "{shaped} {flat}");
+    }
+
+    #[test]
+    fn noise_shaping_keeps_each_channel() {
+        // a quarter of a step up on the left, down on the right
+        let input: Vec<f32> = [0.25f32 / 32768.0, -0.25 / 32768.0].repeat(2000);
+        let output: Vec<i16> = SamplesBuffer::new(2, 48000, input)
+            .dither_with_kind(5, DitherKind::NoiseShaped)
+            .collect();
+        let left = output.iter().step_by(2).map(|&s| s as f32).sum::<f32>() / 2000.0;
+        let right = output
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .map(|&s| s as f32)
+            .sum::<f32>()
+            / 2000.0;
+        assert!((left - 0.25).abs() < 0.01, "{left}");
+        assert!((right + 0.25).abs() < 0.01, "{right}");
+    }
+
+    #[test]
+    fn silence_is_dithered_by_one_lsb() {
+        let silence = SamplesBuffer::new(1, 48000, vec![0.0f32; 1000]);
+        let output: Vec<i16> = silence.dither(42).collect();
+
+        assert!(output.iter().all(|s| (-1..=1).contains(s)));
+        assert!(output.iter().any(|s| *s != 0));
+    }
+
+    #[test]
+    fn same_seed_is_reproducible() {
+        let data = (0..1000).map(|i| (i as f32 / 1000.0).sin() * 0.01);
+        let data: Vec<f32> = data.collect();
+
+        let first: Vec<i16> = SamplesBuffer::new(1, 48000, data.clone())
+            .dither(7)
+            .collect();
+        let second: Vec<i16> = SamplesBuffer::new(1, 48000, data).dither(7).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn stays_close_to_input() {
+        let input = SamplesBuffer::new(1, 48000, vec![0.5f32, -0.5, 0.25, -0.25]);
+        for (dithered, exact) in input.dither::<i16>(1).zip([16384i16, -16384, 8192, -8192]) {
+            assert!((dithered - exact).abs() <= 1);
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 use super::SeekError;
 
@@ -92,8 +92,23 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.input.playback_pos()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
 }
@@ -0,0 +1,112 @@
+use crate::{Sample, Source};
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use super::{ChannelLayout, ChannelMatrix};
+
+/// Internal function that builds a `Downmix` object that outputs stereo.
+pub fn downmix_to_stereo<I>(input: I, layout: ChannelLayout) -> Downmix<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    downmix(input, layout, stereo_coefficients(layout).to_vec())
+}
+
+/// Internal function that builds a `Downmix` object that outputs mono.
+pub fn downmix_to_mono<I>(input: I, layout: ChannelLayout) -> Downmix<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let [left, right] = stereo_coefficients(layout);
+    let mono = left.iter().zip(right).map(|(l, r)| (l + r) * 0.5).collect();
+    downmix(input, layout, vec![mono])
+}
+
+/// Weights of each input channel in the left and right output channels, following
+/// ITU-R BS.775: the center and the surrounds are attenuated by 3 dB and the low frequency
+/// effects channel is dropped.
+fn stereo_coefficients(layout: ChannelLayout) -> [Vec<f32>; 2] {
+    const H: f32 = FRAC_1_SQRT_2;
+    match layout {
+        ChannelLayout::Mono => [vec![1.0], vec![1.0]],
+        ChannelLayout::Stereo => [vec![1.0, 0.0], vec![0.0, 1.0]],
+        ChannelLayout::Quad => [vec![1.0, 0.0, H, 0.0], vec![0.0, 1.0, 0.0, H]],
+        ChannelLayout::Surround5_1 => [
+            vec![1.0, 0.0, H, 0.0, H, 0.0],
+            vec![0.0, 1.0, H, 0.0, 0.0, H],
+        ],
+        ChannelLayout::Surround7_1 => [
+            vec![1.0, 0.0, H, 0.0, H, 0.0, H, 0.0],
+            vec![0.0, 1.0, H, 0.0, 0.0, H, 0.0, H],
+        ],
+    }
+}
+
+/// Mixes the channels of a source down to fewer channels, weighting each of them according
+/// to the speaker it belongs to.
+pub type Downmix<I> = ChannelMatrix<I>;
+
+fn downmix<I>(input: I, layout: ChannelLayout, coefficients: Vec<Vec<f32>>) -> Downmix<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert_eq!(
+        input.channels(),
+        layout.channels(),
+        "the source channels count does not match the layout {:?}",
+        layout
+    );
+    ChannelMatrix::new(input, coefficients)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{from_iter, ChannelLayout, Source};
+
+    #[test]
+    fn surround_to_stereo() {
+        // Front left, front right, center, LFE, side left, side right.
+        let frame = vec![0.4f32, 0.1, 0.2, 0.9, 0.3, 0.05];
+        let source = SamplesBuffer::new(6, 48000, frame);
+        let output: Vec<f32> = source
+            .downmix_to_stereo(ChannelLayout::Surround5_1)
+            .collect();
+
+        assert_eq!(output.len(), 2);
+        let h = FRAC_1_SQRT_2;
+        assert!((output[0] - (0.4 + 0.2 * h + 0.3 * h)).abs() < 1e-6);
+        assert!((output[1] - (0.1 + 0.2 * h + 0.05 * h)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reads_the_input_when_played() {
+        // two frames, whose length is known
+        let source = from_iter(vec![SamplesBuffer::new(6, 48000, vec![0.5f32; 12])]);
+        let mut downmixed = source.downmix_to_stereo(ChannelLayout::Surround5_1);
+        assert_eq!(downmixed.current_frame_len(), Some(4));
+        assert_eq!(downmixed.size_hint().0, 4);
+
+        downmixed.next();
+        assert_eq!(downmixed.current_frame_len(), Some(3));
+        assert_eq!(downmixed.by_ref().count(), 3);
+    }
+
+    #[test]
+    fn stereo_to_mono() {
+        let source = SamplesBuffer::new(2, 48000, vec![100i16, 200, -50, 50]);
+        let output: Vec<i16> = source.downmix_to_mono(ChannelLayout::Stereo).collect();
+        assert_eq!(output, vec![150, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn layout_must_match() {
+        let source = SamplesBuffer::new(2, 48000, vec![0i16; 4]);
+        let _ = source.downmix_to_mono(ChannelLayout::Surround5_1);
+    }
+}
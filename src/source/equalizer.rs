@@ -0,0 +1,341 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{SampleFormat, Source};
+
+use super::blt::{BltApplier, BltFormula};
+use super::SeekError;
+
+/// Number of frames during which the coefficients stay the same.
+const BLOCK_FRAMES: u64 = 64;
+
+/// Part of the distance to the requested gains that is covered at every block, about 15 ms
+/// to get most of the way there at 44.1 kHz.
+const SMOOTHING: f32 = 0.1;
+
+/// The frequencies around which the three bands are turned up or down, in Hz.
+const LOW_HZ: f32 = 250.0;
+const MID_HZ: f32 = 1000.0;
+const HIGH_HZ: f32 = 4000.0;
+
+/// The q of the mid band, about two octaves wide.
+const MID_Q: f32 = 0.7;
+
+/// Internal function that builds an `Equalizer` object.
+pub fn equalizer<I>(input: I, low_db: f32, mid_db: f32, high_db: f32) -> Equalizer<I>
+where
+    I: Source<Item = f32>,
+{
+    let gains = [low_db, mid_db, high_db];
+    Equalizer {
+        input,
+        targets: Arc::new(gains.map(|gain| AtomicU32::new(gain.to_bits()))),
+        gains,
+        appliers: None,
+        states: Vec::new(),
+        frames: 0,
+        current_channel: 0,
+    }
+}
+
+/// Three band tone control, see [`Source::equalizer`].
+#[derive(Debug)]
+pub struct Equalizer<I> {
+    input: I,
+    // The gains asked for, as the bits of an f32, shared with the controllers.
+    targets: Arc<[AtomicU32; 3]>,
+    // The gains used for the current block, on their way to the targets.
+    gains: [f32; 3],
+    appliers: Option<[BltApplier; 3]>,
+    // The last two inputs and outputs of each band, for each channel.
+    states: Vec<[[f32; 4]; 3]>,
+    frames: u64,
+    current_channel: u16,
+}
+
+/// Changes the gains of an [`Equalizer`] while it plays, from any thread.
+#[derive(Clone, Debug)]
+pub struct EqualizerController {
+    targets: Arc<[AtomicU32; 3]>,
+}
+
+impl EqualizerController {
+    /// Sets the gains of the low, mid and high bands, in decibels.
+    ///
+    /// The equalizer moves to them over a few milliseconds, so that the change does not click.
+    #[inline]
+    pub fn set_eq(&self, low_db: f32, mid_db: f32, high_db: f32) {
+        for (target, gain) in self.targets.iter().zip([low_db, mid_db, high_db]) {
+            target.store(gain.to_bits(), Ordering::Relaxed);
+        }
+    }
+}
+
+impl<I> Equalizer<I>
+where
+    I: Source<Item = f32>,
+{
+    /// Returns a controller that changes the gains of this equalizer, for example from the
+    /// thread of a user interface once the source is playing.
+    #[inline]
+    pub fn controller(&self) -> EqualizerController {
+        EqualizerController {
+            targets: self.targets.clone(),
+        }
+    }
+
+    /// Sets the gains of the low, mid and high bands, in decibels, see
+    /// [`EqualizerController::set_eq`].
+    #[inline]
+    pub fn set_eq(&self, low_db: f32, mid_db: f32, high_db: f32) {
+        self.controller().set_eq(low_db, mid_db, high_db);
+    }
+
+    /// Returns the gains of the low, mid and high bands for the current block, in decibels.
+    ///
+    /// After a change, these are somewhere between the old and the new gains.
+    #[inline]
+    pub fn gains(&self) -> [f32; 3] {
+        self.gains
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    fn update_appliers(&mut self) {
+        let mut changed = self.appliers.is_none();
+        for (gain, target) in self.gains.iter_mut().zip(self.targets.iter()) {
+            let target = f32::from_bits(target.load(Ordering::Relaxed));
+            if *gain == target {
+                continue;
+            }
+            *gain += (target - *gain) * SMOOTHING;
+            if (target - *gain).abs() < 0.01 {
+                *gain = target;
+            }
+            changed = true;
+        }
+        if !changed {
+            return;
+        }
+
+        let sample_rate = self.input.sample_rate().max(1);
+        // Kept below the Nyquist frequency of low sample rates.
+        let freq = |hz: f32| hz.min(sample_rate as f32 * 0.45) as u32;
+        let [low_db, mid_db, high_db] = self.gains;
+        let formulas = [
+            BltFormula::LowShelf {
+                freq: freq(LOW_HZ),
+                gain_db: low_db,
+            },
+            BltFormula::Peaking {
+                freq: freq(MID_HZ),
+                q: MID_Q,
+                gain_db: mid_db,
+            },
+            BltFormula::HighShelf {
+                freq: freq(HIGH_HZ),
+                gain_db: high_db,
+            },
+        ];
+        self.appliers = Some(formulas.map(|formula| formula.to_applier(sample_rate)));
+    }
+}
+
+impl<I> Iterator for Equalizer<I>
+where
+    I: Source<Item = f32>,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let channels = self.input.channels().max(1);
+        if self.current_channel == 0 {
+            if self.frames.is_multiple_of(BLOCK_FRAMES) || self.appliers.is_none() {
+                self.update_appliers();
+            }
+            if self.states.len() != channels as usize {
+                self.states = vec![[[0.0; 4]; 3]; channels as usize];
+            }
+        }
+
+        let mut sample = self.input.next()?;
+        let appliers = self
+            .appliers
+            .as_ref()
+            .expect("computed at the start of the frame");
+        let states = &mut self.states[self.current_channel as usize];
+        for (applier, state) in appliers.iter().zip(states.iter_mut()) {
+            let [x_n1, x_n2, y_n1, y_n2] = *state;
+            let result = applier.apply(sample, x_n1, x_n2, y_n1, y_n2);
+            *state = [sample, x_n1, result, y_n1];
+            sample = result;
+        }
+
+        self.current_channel += 1;
+        if self.current_channel >= channels {
+            self.current_channel = 0;
+            self.frames += 1;
+        }
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Equalizer<I> where I: Source<Item = f32> + ExactSizeIterator {}
+
+impl<I> Source for Equalizer<I>
+where
+    I: Source<Item = f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // The gains are kept, only the history of the filters is from before the seek.
+        self.states.clear();
+        self.current_channel = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    const RATE: u32 = 44100;
+
+    fn sine(freq: f32, len: usize) -> SamplesBuffer<f32> {
+        let samples = (0..len)
+            .map(|i| (TAU * freq * i as f32 / RATE as f32).sin())
+            .collect::<Vec<_>>();
+        SamplesBuffer::new(1, RATE, samples)
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples
+            .iter()
+            .fold(0.0, |peak, sample| sample.abs().max(peak))
+    }
+
+    #[test]
+    fn flat_leaves_the_sound_unchanged() {
+        let input: Vec<f32> = sine(440.0, 4410).collect();
+        let output: Vec<f32> = sine(440.0, 4410).equalizer(0.0, 0.0, 0.0).collect();
+        for (a, b) in input.iter().zip(&output) {
+            assert!((a - b).abs() < 1e-4, "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn bands_are_turned_down_apart() {
+        let low: Vec<f32> = sine(60.0, RATE as usize)
+            .equalizer(-12.0, 0.0, 0.0)
+            .collect();
+        let high: Vec<f32> = sine(8000.0, RATE as usize)
+            .equalizer(-12.0, 0.0, 0.0)
+            .collect();
+        let settled = RATE as usize / 2..;
+        assert!(
+            peak(&low[settled.clone()]) < 0.35,
+            "{}",
+            peak(&low[settled.clone()])
+        );
+        assert!(
+            peak(&high[settled.clone()]) > 0.9,
+            "{}",
+            peak(&high[settled])
+        );
+    }
+
+    #[test]
+    fn changes_are_smoothed() {
+        let mut equalizer = sine(60.0, RATE as usize).equalizer(0.0, 0.0, 0.0);
+        let controller = equalizer.controller();
+        let before: Vec<f32> = equalizer.by_ref().take(RATE as usize / 4).collect();
+        controller.set_eq(-12.0, 0.0, 0.0);
+
+        let mut last = *before.last().unwrap();
+        let after: Vec<f32> = equalizer
+            .by_ref()
+            .inspect(|&sample| {
+                // a 60 Hz sine moves by less than 0.01 from a sample to the next
+                assert!((sample - last).abs() < 0.02, "{last} {sample}");
+                last = sample;
+            })
+            .take(RATE as usize / 2)
+            .collect();
+        assert!(peak(&after[RATE as usize / 4..]) < 0.35);
+        assert_eq!(equalizer.gains(), [-12.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn gains_move_step_by_step() {
+        let mut equalizer = sine(60.0, 1000).equalizer(0.0, 0.0, 0.0);
+        equalizer.next();
+        equalizer.set_eq(-12.0, 6.0, 0.0);
+        equalizer.by_ref().take(64).count();
+        let [low, mid, high] = equalizer.gains();
+        assert!(-12.0 < low && low < 0.0, "{low}");
+        assert!(0.0 < mid && mid < 6.0, "{mid}");
+        assert_eq!(high, 0.0);
+    }
+}
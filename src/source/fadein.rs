@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 use super::{linear_ramp::linear_gain_ramp, LinearGainRamp, SeekError};
 
@@ -95,8 +95,28 @@ where
         self.inner().total_duration()
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.inner().samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.inner().is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.inner().playback_pos()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.inner_mut().try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.inner().sample_format()
+    }
 }
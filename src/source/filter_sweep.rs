@@ -0,0 +1,292 @@
+use std::time::Duration;
+
+use crate::{SampleFormat, Source};
+
+use super::blt::{BltApplier, BltFormula};
+use super::SeekError;
+
+/// Number of frames during which the cutoff stays the same.
+const BLOCK_FRAMES: u64 = 64;
+
+/// The q of the filter, the same as for [`Source::low_pass`].
+const Q: f32 = 0.5;
+
+/// Internal function that builds a `FilterSweep` object.
+pub fn filter_sweep<I>(
+    input: I,
+    start_hz: u32,
+    end_hz: u32,
+    duration: Duration,
+    kind: FilterKind,
+) -> FilterSweep<I>
+where
+    I: Source<Item = f32>,
+{
+    assert!(
+        start_hz > 0 && end_hz > 0,
+        "the cutoff of a filter sweep can not be 0 Hz"
+    );
+    FilterSweep {
+        input,
+        start_hz,
+        end_hz,
+        duration,
+        kind,
+        applier: None,
+        states: Vec::new(),
+        frames: 0,
+        current_channel: 0,
+    }
+}
+
+/// The kind of filter swept by [`Source::filter_sweep`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterKind {
+    /// Lets through what is below the cutoff.
+    LowPass,
+    /// Lets through what is above the cutoff.
+    HighPass,
+}
+
+/// Filter whose cutoff moves from one frequency to another over time, then stays there.
+#[derive(Clone, Debug)]
+pub struct FilterSweep<I> {
+    input: I,
+    start_hz: u32,
+    end_hz: u32,
+    duration: Duration,
+    kind: FilterKind,
+    // The coefficients for the current block, computed when it starts.
+    applier: Option<BltApplier>,
+    // The last two inputs and outputs of each channel, kept when the coefficients change.
+    states: Vec<[f32; 4]>,
+    // Frames played since the start of the source.
+    frames: u64,
+    current_channel: u16,
+}
+
+impl<I> FilterSweep<I>
+where
+    I: Source<Item = f32>,
+{
+    /// Returns the cutoff of the filter for the current block, in Hz.
+    ///
+    /// It goes from the start to the end frequency evenly in pitch, so that the sweep sounds
+    /// as fast at the top as at the bottom.
+    pub fn cutoff(&self) -> u32 {
+        let block_start = self.frames - self.frames % BLOCK_FRAMES;
+        let elapsed = block_start as f64 / self.input.sample_rate().max(1) as f64;
+        let progress = match self.duration.is_zero() {
+            true => 1.0,
+            false => (elapsed / self.duration.as_secs_f64()).min(1.0),
+        };
+        let ratio = self.end_hz as f64 / self.start_hz as f64;
+        (self.start_hz as f64 * ratio.powf(progress)).round() as u32
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    fn update_applier(&mut self) {
+        let freq = self.cutoff();
+        let formula = match self.kind {
+            FilterKind::LowPass => BltFormula::LowPass { freq, q: Q },
+            FilterKind::HighPass => BltFormula::HighPass { freq, q: Q },
+        };
+        self.applier = Some(formula.to_applier(self.input.sample_rate()));
+    }
+}
+
+impl<I> Iterator for FilterSweep<I>
+where
+    I: Source<Item = f32>,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let channels = self.input.channels().max(1);
+        if self.current_channel == 0 {
+            if self.frames.is_multiple_of(BLOCK_FRAMES) || self.applier.is_none() {
+                self.update_applier();
+            }
+            if self.states.len() != channels as usize {
+                self.states = vec![[0.0; 4]; channels as usize];
+            }
+        }
+
+        let sample = self.input.next()?;
+        let state = &mut self.states[self.current_channel as usize];
+        let [x_n1, x_n2, y_n1, y_n2] = *state;
+        let result = self
+            .applier
+            .as_ref()
+            .expect("computed at the start of the frame")
+            .apply(sample, x_n1, x_n2, y_n1, y_n2);
+        *state = [sample, x_n1, result, y_n1];
+
+        self.current_channel += 1;
+        if self.current_channel >= channels {
+            self.current_channel = 0;
+            // A new sample rate is picked up with the next block.
+            self.frames += 1;
+        }
+        Some(result)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for FilterSweep<I> where I: Source<Item = f32> + ExactSizeIterator {}
+
+impl<I> Source for FilterSweep<I>
+where
+    I: Source<Item = f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // The sweep follows the position in the source.
+        self.frames = (pos.as_secs_f64() * self.input.sample_rate() as f64) as u64;
+        self.applier = None;
+        self.states.clear();
+        self.current_channel = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+    use std::time::Duration;
+
+    use super::FilterKind;
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    const RATE: u32 = 44100;
+
+    fn noise(len: usize) -> Vec<f32> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 8) as f32 / (1 << 23) as f32 - 1.0
+            })
+            .collect()
+    }
+
+    // Power weighted mean frequency of the samples.
+    fn spectral_centroid(samples: &[f32]) -> f32 {
+        let len = samples.len();
+        let (weighted, total) = (1..len / 2).fold((0.0, 0.0), |(weighted, total), bin| {
+            let (re, im) = samples
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(re, im), (i, x)| {
+                    let phase = TAU * (bin * i % len) as f32 / len as f32;
+                    (re + x * phase.cos(), im - x * phase.sin())
+                });
+            let power = re * re + im * im;
+            let freq = bin as f32 * RATE as f32 / len as f32;
+            (weighted + freq * power, total + power)
+        });
+        weighted / total
+    }
+
+    #[test]
+    fn low_pass_sweep_darkens_noise() {
+        let input = SamplesBuffer::new(1, RATE, noise(RATE as usize * 3 / 2));
+        let mut sweep = input.filter_sweep(16000, 200, Duration::from_secs(1), FilterKind::LowPass);
+        assert_eq!(sweep.cutoff(), 16000);
+        let output: Vec<f32> = sweep.by_ref().collect();
+        // held at the end
+        assert_eq!(sweep.cutoff(), 200);
+
+        let centroid = |start: usize| spectral_centroid(&output[start..start + 1024]);
+        let early = centroid(2048);
+        let middle = centroid(RATE as usize / 2);
+        let late = centroid(RATE as usize * 5 / 4);
+        assert!(early > 2.0 * middle, "{early} {middle}");
+        assert!(middle > 2.0 * late, "{middle} {late}");
+        assert!(late < 1000.0, "{late}");
+    }
+
+    #[test]
+    fn cutoff_moves_evenly_in_pitch() {
+        let input = SamplesBuffer::new(1, 6400, vec![0.0f32; 6400]);
+        let mut sweep = input.filter_sweep(100, 1600, Duration::from_secs(1), FilterKind::HighPass);
+        sweep.by_ref().take(3200).count();
+        assert_eq!(sweep.cutoff(), 400);
+    }
+
+    #[test]
+    fn channels_are_filtered_apart() {
+        let mut input = vec![0.0f32; 2 * 4096];
+        for (i, sample) in noise(4096).into_iter().enumerate() {
+            input[2 * i + 1] = sample;
+        }
+        let output: Vec<f32> = SamplesBuffer::new(2, RATE, input)
+            .filter_sweep(8000, 500, Duration::from_millis(50), FilterKind::LowPass)
+            .collect();
+        assert!(output.iter().step_by(2).all(|&sample| sample == 0.0));
+        assert!(output
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .any(|&sample| sample != 0.0));
+    }
+}
@@ -0,0 +1,257 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::delay_line::DelayLine;
+use super::SeekError;
+
+/// Internal function that builds a `Flanger` object.
+pub fn flanger<I>(input: I, rate_hz: f32, depth: Duration, feedback: f32) -> Flanger<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    assert!(
+        feedback > -1.0 && feedback < 1.0,
+        "the feedback of a flanger must be between -1.0 and 1.0 excluded, got {feedback}"
+    );
+    let mut flanger = Flanger {
+        input,
+        rate_hz,
+        depth,
+        feedback,
+        phase: 0.0,
+        delay: DelayLine::new(0, 0.0),
+        current_channel: 0,
+        delay_frames: 0.0,
+    };
+    flanger.reset();
+    flanger
+}
+
+/// Filter that sums a source with a copy of itself played through a short delay that keeps
+/// changing, see [`Source::flanger`].
+#[derive(Clone, Debug)]
+pub struct Flanger<I> {
+    input: I,
+    rate_hz: f32,
+    depth: Duration,
+    feedback: f32,
+    // Phase of the oscillator, in cycles.
+    phase: f32,
+    // What went into the delay: the input and the fed back output.
+    delay: DelayLine,
+    current_channel: usize,
+    // Delay of the current frame, in frames.
+    delay_frames: f32,
+}
+
+impl<I> Flanger<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    // Forgets the previous samples, and adapts to the format of the input.
+    fn reset(&mut self) {
+        let channels = self.input.channels().max(1) as usize;
+        let depth_frames = self.depth.as_secs_f32() * self.input.sample_rate() as f32;
+        // The delay is never shorter than a frame, as it is read before the frame is written.
+        self.delay = DelayLine::new(channels, depth_frames.max(1.0));
+        self.current_channel = 0;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Flanger<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel == 0 {
+            // The format can only change between frames.
+            if self.delay.channels() != self.input.channels().max(1) as usize {
+                self.reset();
+            }
+            let sample_rate = self.input.sample_rate() as f32;
+            // Goes from no delay to `depth` and back once per cycle.
+            let swing = (1.0 - (TAU * self.phase).cos()) / 2.0;
+            self.delay_frames = (self.depth.as_secs_f32() * sample_rate * swing).max(1.0);
+            self.phase = (self.phase + self.rate_hz / sample_rate).fract();
+        }
+
+        let sample = self.input.next()?.to_f32();
+        let delayed = self.delay.read(self.current_channel, self.delay_frames);
+        self.delay
+            .write(self.current_channel, sample + self.feedback * delayed);
+        let output = (sample + delayed) / 2.0;
+
+        self.current_channel += 1;
+        if self.current_channel >= self.delay.channels() {
+            self.current_channel = 0;
+            self.delay.advance();
+        }
+        Some(I::Item::from_sample_(output))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Flanger<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample + FromSample<f32>,
+{
+}
+
+impl<I> Source for Flanger<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.reset();
+        self.phase = (pos.as_secs_f32() * self.rate_hz).fract();
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    const RATE: u32 = 48000;
+
+    // The loudest sample of every 4 ms of a second of a sine going through a flanger.
+    fn levels(freq: f32) -> Vec<f32> {
+        let sine: Vec<f32> = (0..RATE)
+            .map(|i| (TAU * freq * i as f32 / RATE as f32).sin())
+            .collect();
+        let output: Vec<f32> = SamplesBuffer::new(1, RATE, sine)
+            .flanger(1.0, Duration::from_millis(2), 0.0)
+            .collect();
+        output
+            .chunks(RATE as usize / 250)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0.0, |peak, sample| sample.abs().max(peak))
+            })
+            .collect()
+    }
+
+    // When the first notch of the comb filter reaches the frequency, in seconds.
+    fn first_notch(levels: &[f32]) -> f32 {
+        let mut chunk = levels
+            .iter()
+            .position(|&level| level < 0.1)
+            .expect("the frequency is cancelled");
+        // the bottom of the notch
+        while levels[chunk + 1] < levels[chunk] {
+            chunk += 1;
+        }
+        chunk as f32 / 250.0
+    }
+
+    #[test]
+    fn notches_sweep() {
+        let high = levels(1000.0);
+        let low = levels(500.0);
+        // both go through untouched while the delay is short
+        assert!(high[1] > 0.95 && low[1] > 0.95, "{} {}", high[1], low[1]);
+
+        // a delay of 0.5 ms cancels 1 kHz, it is reached after 1/6 s
+        let high_notch = first_notch(&high);
+        assert!((high_notch - 1.0 / 6.0).abs() < 0.01, "{high_notch}");
+        // and 1 ms cancels 500 Hz, after 1/4 s
+        let low_notch = first_notch(&low);
+        assert!((low_notch - 0.25).abs() < 0.01, "{low_notch}");
+    }
+
+    #[test]
+    fn feedback_deepens_the_peaks() {
+        let impulse = |feedback: f32| -> Vec<f32> {
+            let mut data = vec![0.0f32; 10];
+            data[0] = 1.0;
+            SamplesBuffer::new(1, 1000, data)
+                .flanger(0.0, Duration::ZERO, feedback)
+                .collect()
+        };
+        // the delay is as short as possible, a frame
+        assert_eq!(impulse(0.0)[..4], [0.5, 0.5, 0.0, 0.0]);
+        assert_eq!(impulse(0.5)[..4], [0.5, 0.5, 0.25, 0.125]);
+    }
+
+    #[test]
+    #[should_panic(expected = "between -1.0 and 1.0")]
+    fn rejects_runaway_feedback() {
+        SamplesBuffer::new(1, 1000, vec![0.0f32; 4]).flanger(1.0, Duration::ZERO, 1.0);
+    }
+}
@@ -0,0 +1,232 @@
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+const MAX_CHANNELS: usize = 8;
+
+/// Internal function that builds a `Frames` object.
+pub fn frames<I>(input: I) -> Frames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert_channels(input.channels());
+    Frames { input }
+}
+
+fn assert_channels(channels: u16) {
+    assert!(
+        channels as usize <= MAX_CHANNELS,
+        "frames have at most {} channels, the source has {}",
+        MAX_CHANNELS,
+        channels
+    );
+}
+
+/// The samples of all the channels at one point in time, see [`Source::frames`].
+///
+/// The samples are kept inline, without allocating. A frame derefs to a slice of its
+/// samples, one per channel in the order of the source.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame<S> {
+    samples: [S; MAX_CHANNELS],
+    channels: usize,
+}
+
+impl<S> Frame<S>
+where
+    S: Sample,
+{
+    /// The most channels a frame can hold, enough for 7.1 sound.
+    pub const MAX_CHANNELS: usize = MAX_CHANNELS;
+
+    /// Builds a frame out of one sample per channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `samples` is empty or longer than [`Frame::MAX_CHANNELS`].
+    pub fn new(samples: &[S]) -> Frame<S> {
+        assert!(!samples.is_empty(), "a frame needs at least one channel");
+        assert_channels(samples.len().try_into().unwrap_or(u16::MAX));
+        let mut frame = Frame {
+            samples: [S::zero_value(); MAX_CHANNELS],
+            channels: samples.len(),
+        };
+        frame.samples[..samples.len()].copy_from_slice(samples);
+        frame
+    }
+
+    /// Returns the number of channels of the frame.
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.channels as u16
+    }
+}
+
+impl<S> Deref for Frame<S> {
+    type Target = [S];
+
+    #[inline]
+    fn deref(&self) -> &[S] {
+        &self.samples[..self.channels]
+    }
+}
+
+impl<S> DerefMut for Frame<S> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [S] {
+        &mut self.samples[..self.channels]
+    }
+}
+
+/// Iterator over the frames of a source instead of its interleaved samples, see
+/// [`Source::frames`].
+#[derive(Clone, Debug)]
+pub struct Frames<I> {
+    input: I,
+}
+
+impl<I> Frames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the sample rate of the frames to come.
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    /// Returns the duration of the source, if known.
+    #[inline]
+    pub fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    /// Seeks the source, see [`Source::try_seek`].
+    #[inline]
+    pub fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Frames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = Frame<I::Item>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Frame<I::Item>> {
+        let channels = self.input.channels().max(1);
+        assert_channels(channels);
+        let mut frame = Frame {
+            samples: [I::Item::zero_value(); MAX_CHANNELS],
+            channels: channels as usize,
+        };
+        frame[0] = self.input.next()?;
+        // A frame cut short stays silent in the channels that are missing.
+        for sample in &mut frame[1..] {
+            match self.input.next() {
+                Some(value) => *sample = value,
+                None => break,
+            }
+        }
+        Some(frame)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let channels = self.input.channels().max(1) as usize;
+        let (min, max) = self.input.size_hint();
+        (min / channels, max.map(|max| max.div_ceil(channels)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Frame;
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn whole_frames() {
+        let source = SamplesBuffer::new(2, 1000, vec![1i16, 2, 3, 4]);
+        let mut frames = source.frames();
+        assert_eq!(frames.sample_rate(), 1000);
+        assert_eq!(frames.total_duration(), Some(Duration::from_millis(2)));
+        assert_eq!(frames.size_hint(), (2, Some(2)));
+
+        let frame = frames.next().unwrap();
+        assert_eq!(frame.channels(), 2);
+        assert_eq!(*frame, [1, 2]);
+        assert_eq!(frames.next(), Some(Frame::new(&[3, 4])));
+        assert_eq!(frames.next(), None);
+    }
+
+    // Three channels, ending in the middle of the second frame.
+    struct CutShort(std::vec::IntoIter<i16>);
+
+    impl Iterator for CutShort {
+        type Item = i16;
+
+        fn next(&mut self) -> Option<i16> {
+            self.0.next()
+        }
+    }
+
+    impl Source for CutShort {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            3
+        }
+
+        fn sample_rate(&self) -> u32 {
+            1000
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn pads_a_frame_cut_short() {
+        let source = CutShort(vec![1, 2, 3, 4].into_iter());
+        let frames: Vec<_> = source.frames().collect();
+        assert_eq!(frames, [Frame::new(&[1, 2, 3]), Frame::new(&[4, 0, 0])]);
+    }
+
+    #[test]
+    #[should_panic(expected = "frames have at most 8 channels, the source has 9")]
+    fn rejects_too_many_channels() {
+        let _ = SamplesBuffer::new(9, 1000, vec![0i16; 9]).frames();
+    }
+}
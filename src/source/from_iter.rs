@@ -138,6 +138,13 @@ where
         None
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.current_source
+            .as_ref()
+            .is_none_or(|source| source.is_seekable())
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         if let Some(source) = self.current_source.as_mut() {
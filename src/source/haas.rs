@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Haas` object.
+pub fn haas<I>(input: I, delay: Duration, side: Side) -> Haas<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert_eq!(
+        input.channels(),
+        1,
+        "only a mono source can be widened with the Haas effect"
+    );
+    let delay_frames = (delay.as_secs_f64() * input.sample_rate() as f64).round() as usize;
+    Haas {
+        input,
+        side,
+        delay: delay_frames,
+        delayed: VecDeque::from(vec![I::Item::zero_value(); delay_frames]),
+        tail: delay_frames,
+        next: None,
+    }
+}
+
+/// The channel of a stereo pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// The first channel.
+    Left,
+    /// The second channel.
+    Right,
+}
+
+/// Source that turns a mono source into stereo by playing one of the channels slightly later
+/// than the other, see [`Source::haas`].
+#[derive(Clone, Debug)]
+pub struct Haas<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // The channel that lags.
+    side: Side,
+    // The lag, in frames.
+    delay: usize,
+    // The samples waiting to be played on the lagging channel.
+    delayed: VecDeque<I::Item>,
+    // Frames left to play once the input ends, so that the lagging channel finishes too.
+    tail: usize,
+    // The second sample of the frame whose first sample was just returned.
+    next: Option<I::Item>,
+}
+
+impl<I> Haas<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the channel that lags.
+    #[inline]
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Haas<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(sample) = self.next.take() {
+            return Some(sample);
+        }
+
+        let direct = match self.input.next() {
+            Some(sample) => sample,
+            None if self.tail > 0 => {
+                self.tail -= 1;
+                I::Item::zero_value()
+            }
+            None => return None,
+        };
+        self.delayed.push_back(direct);
+        let delayed = self.delayed.pop_front().unwrap_or(direct);
+
+        let (left, right) = match self.side {
+            Side::Left => (delayed, direct),
+            Side::Right => (direct, delayed),
+        };
+        self.next = Some(right);
+        Some(left)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let extra = self.tail * 2 + self.next.is_some() as usize;
+        let (lower, upper) = self.input.size_hint();
+        (
+            lower.saturating_mul(2).saturating_add(extra),
+            upper.and_then(|upper| upper.checked_mul(2)?.checked_add(extra)),
+        )
+    }
+}
+
+impl<I> Source for Haas<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let delay = Duration::from_secs_f64(self.delay as f64 / self.sample_rate() as f64);
+        self.input.total_duration().map(|duration| duration + delay)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // The lagging channel starts over from silence, like at the start.
+        self.delayed.clear();
+        self.delayed.resize(self.delay, I::Item::zero_value());
+        self.tail = self.delay;
+        self.next = None;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Side;
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    fn channels(output: Vec<i16>) -> (Vec<i16>, Vec<i16>) {
+        let left = output.iter().step_by(2).copied().collect();
+        let right = output.iter().skip(1).step_by(2).copied().collect();
+        (left, right)
+    }
+
+    #[test]
+    fn right_lags() {
+        let input: Vec<i16> = (1..=100).collect();
+        let haas =
+            SamplesBuffer::new(1, 1000, input.clone()).haas(Duration::from_millis(10), Side::Right);
+        assert_eq!(haas.channels(), 2);
+        assert_eq!(haas.total_duration(), Some(Duration::from_millis(110)));
+        assert_eq!(haas.size_hint(), (220, Some(220)));
+
+        let (left, right) = channels(haas.collect());
+        assert_eq!(left, [input.clone(), vec![0; 10]].concat());
+        assert_eq!(right, [vec![0; 10], input].concat());
+    }
+
+    #[test]
+    fn left_lags() {
+        let input: Vec<i16> = (1..=20).collect();
+        let haas =
+            SamplesBuffer::new(1, 1000, input.clone()).haas(Duration::from_millis(3), Side::Left);
+        let (left, right) = channels(haas.collect());
+        assert_eq!(left, [vec![0; 3], input.clone()].concat());
+        assert_eq!(right, [input, vec![0; 3]].concat());
+    }
+
+    #[test]
+    fn seeking_starts_the_lag_over() {
+        let input: Vec<i16> = (1..=20).collect();
+        let mut haas =
+            SamplesBuffer::new(1, 1000, input).haas(Duration::from_millis(2), Side::Right);
+        haas.by_ref().take(11).count();
+        haas.try_seek(Duration::from_millis(10)).unwrap();
+        assert_eq!(
+            haas.by_ref().take(6).collect::<Vec<_>>(),
+            vec![11, 0, 12, 0, 13, 11]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn needs_a_mono_source() {
+        SamplesBuffer::new(2, 1000, vec![0i16; 4]).haas(Duration::from_millis(1), Side::Left);
+    }
+}
@@ -0,0 +1,112 @@
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample, StreamConfig};
+
+use super::{RingBufferProducer, RingBufferSource};
+use crate::StreamError;
+
+/// Records audio from an input device, such as a microphone.
+///
+/// The recorded audio is played by the [`RingBufferSource`] returned along with the `Input`,
+/// which can be added to a mixer to monitor it, or written to a file. It has the channels and
+/// sample rate of the device.
+///
+/// Recording stops when the `Input` is dropped, the source then ends once it has played what
+/// was recorded.
+pub struct Input {
+    _stream: cpal::Stream,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Input {
+    /// Starts recording from the default input device, with its default configuration.
+    pub fn try_default() -> Result<(Input, RingBufferSource<f32>), StreamError> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or(StreamError::NoDevice)?;
+        Input::try_from_device(&device)
+    }
+
+    /// Starts recording from the given input device, with its default configuration.
+    pub fn try_from_device(
+        device: &cpal::Device,
+    ) -> Result<(Input, RingBufferSource<f32>), StreamError> {
+        let config = device
+            .default_input_config()
+            .map_err(StreamError::DefaultStreamConfigError)?;
+        let channels = config.channels();
+        let sample_rate = config.sample_rate().0;
+
+        // One second of audio can wait to be played.
+        let capacity = sample_rate as usize * channels as usize;
+        let (producer, source) = RingBufferSource::new(capacity, channels, sample_rate);
+
+        let stream_config = config.config();
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => build_input_stream::<f32>(device, &stream_config, producer),
+            cpal::SampleFormat::F64 => build_input_stream::<f64>(device, &stream_config, producer),
+            cpal::SampleFormat::I8 => build_input_stream::<i8>(device, &stream_config, producer),
+            cpal::SampleFormat::I16 => build_input_stream::<i16>(device, &stream_config, producer),
+            cpal::SampleFormat::I32 => build_input_stream::<i32>(device, &stream_config, producer),
+            cpal::SampleFormat::U8 => build_input_stream::<u8>(device, &stream_config, producer),
+            cpal::SampleFormat::U16 => build_input_stream::<u16>(device, &stream_config, producer),
+            cpal::SampleFormat::U32 => build_input_stream::<u32>(device, &stream_config, producer),
+            _ => Err(cpal::BuildStreamError::StreamConfigNotSupported),
+        }
+        .map_err(StreamError::BuildStreamError)?;
+        stream.play().map_err(StreamError::PlayStreamError)?;
+
+        let input = Input {
+            _stream: stream,
+            channels,
+            sample_rate,
+        };
+        Ok((input, source))
+    }
+
+    /// Returns the number of channels recorded.
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Returns the rate at which the device records.
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Stops recording and closes the stream, same as dropping the `Input`.
+    #[inline]
+    pub fn stop(self) {}
+}
+
+fn build_input_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    producer: RingBufferProducer<f32>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let error_callback = |err| {
+        #[cfg(feature = "tracing")]
+        tracing::error!("an error occurred on input stream: {err}");
+        #[cfg(not(feature = "tracing"))]
+        eprintln!("an error occurred on input stream: {err}");
+    };
+
+    let mut converted = Vec::new();
+    device.build_input_stream::<T, _, _>(
+        config,
+        move |data: &[T], _| {
+            converted.clear();
+            converted.extend(data.iter().map(|&sample| f32::from_sample_(sample)));
+            // When nothing plays the recording, the newest samples are dropped.
+            producer.push(&converted);
+        },
+        error_callback,
+        None,
+    )
+}
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `InspectSamples` object.
+pub fn inspect_samples<I, F>(input: I, f: F) -> InspectSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&I::Item),
+{
+    InspectSamples { input, f }
+}
+
+/// Source that calls a closure on every sample of another source and plays it unchanged, see
+/// [`Source::inspect_samples`].
+#[derive(Clone, Debug)]
+pub struct InspectSamples<I, F> {
+    input: I,
+    f: F,
+}
+
+impl<I, F> InspectSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&I::Item),
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I, F> Iterator for InspectSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&I::Item),
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let sample = self.input.next()?;
+        (self.f)(&sample);
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, F> ExactSizeIterator for InspectSamples<I, F>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    F: FnMut(&I::Item),
+{
+}
+
+impl<I, F> Source for InspectSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&I::Item),
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.input.playback_pos()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn sees_every_sample() {
+        let data = vec![1i16, -2, 3, -4];
+        let mut seen = Vec::new();
+        let output: Vec<i16> = SamplesBuffer::new(2, 44100, data.clone())
+            .inspect_samples(|&sample| seen.push(sample))
+            .collect();
+        assert_eq!(output, data);
+        assert_eq!(seen, data);
+    }
+
+    #[test]
+    fn inspects_each_sample_before_it_plays() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = seen.clone();
+        let mut inspected = SamplesBuffer::new(1, 44100, vec![1i16, 2, 3])
+            .inspect_samples(move |&sample| log.borrow_mut().push(sample));
+
+        assert!(seen.borrow().is_empty());
+        assert_eq!(inspected.next(), Some(1));
+        assert_eq!(*seen.borrow(), [1]);
+        assert_eq!(inspected.next(), Some(2));
+        assert_eq!(*seen.borrow(), [1, 2]);
+        assert_eq!(inspected.by_ref().count(), 1);
+        assert_eq!(inspected.next(), None);
+        assert_eq!(*seen.borrow(), [1, 2, 3]);
+    }
+}
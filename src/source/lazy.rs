@@ -0,0 +1,255 @@
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use super::{SeekError, UniformSourceIterator};
+use crate::{Sample, SampleFormat, Source};
+
+/// Source that opens another source on a background thread, so that building it never
+/// waits for a file to be opened or for its headers to be decoded.
+///
+/// The format has to be given up front, since it is needed before the source is opened. The
+/// source plays silence in that format until `open` returns, then plays what it returned,
+/// converted to that format if needed. If `open` returns `None`, the source ends.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::io::BufReader;
+/// use rodio::source::LazySource;
+/// use rodio::Decoder;
+///
+/// let source = LazySource::new(2, 44100, || {
+///     let file = File::open("assets/music.wav").ok()?;
+///     Decoder::new(BufReader::new(file)).ok()
+/// });
+/// ```
+pub struct LazySource<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    channels: u16,
+    sample_rate: u32,
+    state: LazyState<I>,
+    current_channel: u16,
+}
+
+// Boxed on the thread that opens it, so the audio thread never allocates.
+type Opened<I> = Box<UniformSourceIterator<I, <I as Iterator>::Item>>;
+
+enum LazyState<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Opening(Receiver<Option<Opened<I>>>),
+    Playing(Opened<I>),
+    Ended,
+}
+
+impl<I> LazySource<I>
+where
+    I: Source + Send + 'static,
+    I::Item: Sample + Send,
+{
+    /// Builds a source of `channels` channels at `sample_rate`, that plays the source
+    /// returned by `open` once it is opened.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` or `sample_rate` is 0.
+    pub fn new<F>(channels: u16, sample_rate: u32, open: F) -> LazySource<I>
+    where
+        F: FnOnce() -> Option<I> + Send + 'static,
+    {
+        assert!(channels >= 1, "a lazy source needs at least one channel");
+        assert!(
+            sample_rate >= 1,
+            "the sample rate of a lazy source can not be 0"
+        );
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let source = open()
+                .map(|source| Box::new(UniformSourceIterator::new(source, channels, sample_rate)));
+            // Nobody listening means the lazy source was dropped before it was opened.
+            let _ = tx.send(source);
+        });
+
+        LazySource {
+            channels,
+            sample_rate,
+            state: LazyState::Opening(rx),
+            current_channel: 0,
+        }
+    }
+}
+
+impl<I> LazySource<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns true once the source is opened and playing.
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        matches!(self.state, LazyState::Playing(_))
+    }
+}
+
+impl<I> Iterator for LazySource<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let LazyState::Opening(rx) = &self.state {
+            // Only switched between frames, so that the channels stay in place.
+            if self.current_channel == 0 {
+                match rx.try_recv() {
+                    Ok(Some(source)) => self.state = LazyState::Playing(source),
+                    Ok(None) | Err(TryRecvError::Disconnected) => self.state = LazyState::Ended,
+                    Err(TryRecvError::Empty) => {}
+                }
+            }
+        }
+
+        match &mut self.state {
+            LazyState::Opening(_) => {
+                self.current_channel = (self.current_channel + 1) % self.channels;
+                Some(I::Item::zero_value())
+            }
+            LazyState::Playing(source) => {
+                let sample = source.next();
+                if sample.is_none() {
+                    self.state = LazyState::Ended;
+                }
+                sample
+            }
+            LazyState::Ended => None,
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.state {
+            LazyState::Opening(_) => (0, None),
+            LazyState::Playing(source) => source.size_hint(),
+            LazyState::Ended => (0, Some(0)),
+        }
+    }
+}
+
+impl<I> Source for LazySource<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        match &self.state {
+            LazyState::Opening(_) => None,
+            LazyState::Playing(source) => source.current_frame_len(),
+            LazyState::Ended => Some(0),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The duration of the source once it is opened, without the silence played before.
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        match &self.state {
+            LazyState::Playing(source) => source.total_duration(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        match &self.state {
+            LazyState::Playing(source) => source.is_seekable(),
+            _ => false,
+        }
+    }
+
+    /// Seeking is only supported once the source is opened.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        match &mut self.state {
+            LazyState::Playing(source) => source.try_seek(pos),
+            _ => Err(SeekError::NotSupported {
+                underlying_source: std::any::type_name::<Self>(),
+            }),
+        }
+    }
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        match &self.state {
+            LazyState::Playing(source) => source.sample_format(),
+            _ => I::Item::FORMAT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use super::LazySource;
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn plays_silence_until_open() {
+        let (open_tx, open_rx) = mpsc::channel::<()>();
+        let mut source = LazySource::new(2, 1000, move || {
+            open_rx.recv().ok()?;
+            Some(SamplesBuffer::new(2, 1000, vec![1i16, 2, 3, 4]))
+        });
+        assert_eq!(source.next(), Some(0));
+        assert_eq!(source.next(), Some(0));
+        assert!(!source.is_open());
+        assert!(source.try_seek(Duration::ZERO).is_err());
+
+        open_tx.send(()).unwrap();
+        let output: Vec<i16> = source.collect();
+        // whole frames of silence, until the source is opened
+        let (silence, sound) = output.split_at(output.len() - 4);
+        assert_eq!(silence.len() % 2, 0);
+        assert!(silence.iter().all(|&sample| sample == 0));
+        assert_eq!(sound, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn converts_to_the_format_given() {
+        let source = LazySource::new(2, 1000, || Some(SamplesBuffer::new(1, 1000, vec![5i16, 6])));
+        assert_eq!(source.channels(), 2);
+        let output: Vec<i16> = source.collect();
+        assert!(output.ends_with(&[5, 5, 6, 6]), "{output:?}");
+    }
+
+    #[test]
+    fn ends_when_it_can_not_be_opened() {
+        let mut source = LazySource::new(1, 1000, || None::<SamplesBuffer<i16>>);
+        // silent until the background thread gives up
+        for sample in source.by_ref() {
+            assert_eq!(sample, 0);
+        }
+        assert!(!source.is_open());
+        assert_eq!(source.next(), None);
+    }
+}
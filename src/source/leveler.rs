@@ -0,0 +1,316 @@
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// How long the level of the source is averaged over.
+const ENVELOPE_TIME: Duration = Duration::from_millis(100);
+
+/// How long the level is averaged over to tell whether it is below the gate. Shorter, so that
+/// the gain stops changing soon after a pause starts.
+const GATE_TIME: Duration = Duration::from_millis(10);
+
+/// Level below which the gain is held, by default.
+const DEFAULT_GATE_DB: f32 = -50.0;
+
+/// Internal function that builds a `Leveler` object.
+pub fn leveler<I>(
+    input: I,
+    target_db: f32,
+    max_gain_db: f32,
+    attack: Duration,
+    release: Duration,
+) -> Leveler<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let mut leveler = Leveler {
+        input,
+        target_db,
+        max_gain_db,
+        gate: power_from_db(DEFAULT_GATE_DB),
+        attack,
+        release,
+        sample_rate: 0,
+        envelope_coeff: 0.0,
+        gate_coeff: 0.0,
+        attack_coeff: 0.0,
+        release_coeff: 0.0,
+        envelope: 0.0,
+        gate_envelope: 0.0,
+        gain_db: 0.0,
+        gain: 1.0,
+        frame_power: 0.0,
+        current_channel: 0,
+    };
+    leveler.update_coefficients();
+    leveler
+}
+
+/// Source that keeps the level of another source near a target, raising quiet parts and
+/// lowering loud ones.
+///
+/// The level is the power of the source averaged over 100 ms. The gain follows it with the
+/// attack time when the source gets louder and the release time when it gets quieter, and is
+/// the same for all the channels of a frame. Below the gate the gain is held, so that pauses
+/// keep their noise floor.
+#[derive(Clone, Debug)]
+pub struct Leveler<I> {
+    input: I,
+    target_db: f32,
+    max_gain_db: f32,
+    // The threshold and the envelopes are mean squares.
+    gate: f32,
+    attack: Duration,
+    release: Duration,
+    // The rate the coefficients were computed for.
+    sample_rate: u32,
+    envelope_coeff: f32,
+    gate_coeff: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    envelope: f32,
+    gate_envelope: f32,
+    // The gain is smoothed in dB, so that it changes evenly at any level.
+    gain_db: f32,
+    // The same gain, as an amplitude.
+    gain: f32,
+    // Sum of the squares of the current frame so far.
+    frame_power: f32,
+    current_channel: u16,
+}
+
+impl<I> Leveler<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Sets the level under which the source is considered silent, in dBFS. The gain does
+    /// not change while the source is below it. Defaults to -50 dBFS.
+    #[inline]
+    pub fn set_gate_db(&mut self, gate_db: f32) {
+        self.gate = power_from_db(gate_db);
+    }
+
+    /// Returns the gain applied to the current frame, in dB.
+    #[inline]
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // The filters run once per frame, so the number of frames per second sets their speed.
+    fn update_coefficients(&mut self) {
+        self.sample_rate = self.input.sample_rate();
+        let frames_per_sec = self.sample_rate.max(1) as f32;
+        let coeff = |time: Duration| (-1.0 / (time.as_secs_f32() * frames_per_sec)).exp();
+        self.envelope_coeff = coeff(ENVELOPE_TIME);
+        self.gate_coeff = coeff(GATE_TIME);
+        self.attack_coeff = coeff(self.attack);
+        self.release_coeff = coeff(self.release);
+    }
+
+    fn end_frame(&mut self, channels: u16) {
+        let power = self.frame_power / channels as f32;
+        self.frame_power = 0.0;
+        self.envelope = self.envelope_coeff * self.envelope + (1.0 - self.envelope_coeff) * power;
+        self.gate_envelope = self.gate_coeff * self.gate_envelope + (1.0 - self.gate_coeff) * power;
+        if self.gate_envelope < self.gate {
+            return;
+        }
+
+        let level_db = 10.0 * self.envelope.log10();
+        let desired_db = (self.target_db - level_db).min(self.max_gain_db);
+        let coeff = match desired_db < self.gain_db {
+            true => self.attack_coeff,
+            false => self.release_coeff,
+        };
+        self.gain_db = coeff * self.gain_db + (1.0 - coeff) * desired_db;
+        self.gain = amplitude_from_db(self.gain_db);
+    }
+}
+
+impl<I> Iterator for Leveler<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel == 0 && self.sample_rate != self.input.sample_rate() {
+            self.update_coefficients();
+        }
+
+        let sample = self.input.next()?;
+        let value = sample.to_f32();
+        self.frame_power += value * value;
+        let output = sample.amplify(self.gain);
+
+        let channels = self.input.channels().max(1);
+        self.current_channel += 1;
+        if self.current_channel >= channels {
+            self.current_channel = 0;
+            self.end_frame(channels);
+        }
+        Some(output)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Leveler<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Leveler<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // The gain is kept, the level is probably close at the new position.
+        self.frame_power = 0.0;
+        self.current_channel = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[inline]
+fn amplitude_from_db(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[inline]
+fn power_from_db(db: f32) -> f32 {
+    10f32.powf(db / 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    const RATE: u32 = 48000;
+
+    fn sine(amplitude: f32, seconds: u32) -> Vec<f32> {
+        (0..RATE * seconds)
+            .map(|i| amplitude * (std::f32::consts::TAU * 440.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn rms_db(samples: &[f32]) -> f32 {
+        let power = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+        10.0 * power.log10()
+    }
+
+    fn level(samples: Vec<f32>, max_gain_db: f32) -> Vec<f32> {
+        SamplesBuffer::new(1, RATE, samples)
+            .agc(
+                -18.0,
+                max_gain_db,
+                Duration::from_millis(50),
+                Duration::from_millis(500),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn raises_quiet_material() {
+        // a sine of amplitude 0.045 is about -30 dBFS
+        let output = level(sine(0.045, 5), 24.0);
+        let settled = rms_db(&output[RATE as usize * 4..]);
+        assert!((settled + 18.0).abs() < 0.5, "{settled}");
+    }
+
+    #[test]
+    fn lowers_loud_material() {
+        let output = level(sine(0.9, 3), 24.0);
+        let settled = rms_db(&output[RATE as usize * 2..]);
+        assert!((settled + 18.0).abs() < 0.5, "{settled}");
+    }
+
+    #[test]
+    fn limits_the_boost() {
+        let output = level(sine(0.045, 5), 6.0);
+        let settled = rms_db(&output[RATE as usize * 4..]);
+        assert!((settled + 24.0).abs() < 0.5, "{settled}");
+    }
+
+    #[test]
+    fn holds_the_gain_in_silence() {
+        let mut input = sine(0.125, 3);
+        // a noise floor around -60 dBFS
+        input.extend((0..RATE * 3).map(|i| if i % 2 == 0 { 0.001 } else { -0.001 }));
+        let mut leveler = SamplesBuffer::new(1, RATE, input).agc(
+            -18.0,
+            24.0,
+            Duration::from_millis(50),
+            Duration::from_millis(500),
+        );
+        leveler.by_ref().take(RATE as usize * 3).count();
+        let gain = leveler.gain_db();
+        leveler.by_ref().count();
+        assert!((leveler.gain_db() - gain).abs() < 1.0);
+    }
+}
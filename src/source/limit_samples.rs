@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `LimitSamples` object.
+pub fn limit_samples<I>(input: I, samples: usize) -> LimitSamples<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    // Rounded down to a whole number of frames.
+    let channels = input.channels().max(1) as usize;
+    let limit = samples - samples % channels;
+    LimitSamples {
+        input,
+        limit,
+        remaining: limit,
+    }
+}
+
+/// Source that plays at most a number of samples of another source, then ends.
+#[derive(Clone, Debug)]
+pub struct LimitSamples<I> {
+    input: I,
+    // The limit, a whole number of frames.
+    limit: usize,
+    remaining: usize,
+}
+
+impl<I> LimitSamples<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the number of samples that can still be played.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for LimitSamples<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let sample = self.input.next()?;
+        self.remaining -= 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.input.size_hint();
+        let upper = upper.map_or(self.remaining, |upper| upper.min(self.remaining));
+        (lower.min(self.remaining), Some(upper))
+    }
+}
+
+impl<I> ExactSizeIterator for LimitSamples<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for LimitSamples<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        match self.input.current_frame_len() {
+            Some(len) => Some(len.min(self.remaining)),
+            None => Some(self.remaining),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let frames = (self.limit / self.input.channels().max(1) as usize) as u64;
+        let nanos = frames * 1_000_000_000 / self.input.sample_rate().max(1) as u64;
+        let limit = Duration::from_nanos(nanos);
+        // A source of unknown duration is taken as endless.
+        match self.input.total_duration() {
+            Some(duration) => Some(duration.min(limit)),
+            None => Some(limit),
+        }
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        match self.input.samples_remaining() {
+            Some(remaining) => Some(remaining.min(self.remaining)),
+            None => Some(self.remaining),
+        }
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        let frames = pos.as_nanos() * self.input.sample_rate() as u128 / 1_000_000_000;
+        let played = frames.saturating_mul(self.input.channels() as u128);
+        self.remaining = (self.limit as u128).saturating_sub(played) as usize;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{SineWave, Source};
+
+    #[test]
+    fn ends_an_endless_source() {
+        let limited = SineWave::new(440.0).limit_samples(480);
+        assert_eq!(limited.total_duration(), Some(Duration::from_millis(10)));
+        assert_eq!(limited.count(), 480);
+    }
+
+    #[test]
+    fn keeps_whole_frames() {
+        let source = SamplesBuffer::new(2, 10, (0..10i16).collect::<Vec<_>>());
+        let limited = source.limit_samples(5);
+        assert_eq!(limited.total_duration(), Some(Duration::from_millis(200)));
+        assert_eq!(limited.collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn shorter_source() {
+        let source = SamplesBuffer::new(1, 10, vec![1i16, 2, 3]);
+        let limited = source.limit_samples(100);
+        assert_eq!(limited.total_duration(), Some(Duration::from_millis(300)));
+        assert_eq!(limited.collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn counts_the_samples_left() {
+        let mut limited = SineWave::new(440.0).limit_samples(10);
+        assert_eq!(limited.samples_remaining(), Some(10));
+        limited.by_ref().take(4).count();
+        assert_eq!(limited.samples_remaining(), Some(6));
+
+        // the shorter of the two
+        let mut limited = SamplesBuffer::new(1, 10, vec![1i16, 2, 3]).limit_samples(100);
+        assert_eq!(limited.samples_remaining(), Some(3));
+        limited.next();
+        assert_eq!(limited.samples_remaining(), Some(2));
+    }
+
+    #[test]
+    fn seeking_moves_the_limit() {
+        let source = SamplesBuffer::new(1, 10, (0..10i16).collect::<Vec<_>>());
+        let mut limited = source.limit_samples(6);
+        limited.try_seek(Duration::from_millis(400)).unwrap();
+        assert_eq!(limited.remaining(), 2);
+        assert_eq!(limited.collect::<Vec<_>>(), vec![4, 5]);
+    }
+}
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use super::SeekError;
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 /// Internal function that builds a `LinearRamp` object.
 pub fn linear_gain_ramp<I>(
@@ -135,11 +135,26 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.input.playback_pos()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.elapsed_ns = pos.as_nanos() as f32;
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `LoopRegion` object.
+pub fn loop_region<I>(input: I, start: Duration, end: Duration) -> LoopRegion<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert!(start < end, "the loop must start before it ends");
+    let channels = input.channels() as u64;
+    let sample_rate = input.sample_rate() as u64;
+    // Both points are rounded down to the start of a frame.
+    let to_frame = |pos: Duration| pos.as_nanos() as u64 * sample_rate / 1_000_000_000;
+    let start_frame = to_frame(start);
+    // Rounded up, so that the input seeks to this very frame.
+    let start_ns = (start_frame * 1_000_000_000).div_ceil(sample_rate);
+
+    LoopRegion {
+        start: Duration::from_nanos(start_ns),
+        start_sample: start_frame * channels,
+        end_sample: to_frame(end) * channels,
+        position: 0,
+        input,
+    }
+}
+
+/// Source that plays until the end of a region, then jumps back to its start forever.
+#[derive(Clone, Debug)]
+pub struct LoopRegion<I> {
+    input: I,
+    start: Duration,
+    start_sample: u64,
+    end_sample: u64,
+    // Number of samples of the input before the next one.
+    position: u64,
+}
+
+impl<I> LoopRegion<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    fn jump_to_start(&mut self) -> Option<()> {
+        // A source that can not seek ends here.
+        self.input.try_seek(self.start).ok()?;
+        self.position = self.start_sample;
+        Some(())
+    }
+}
+
+impl<I> Iterator for LoopRegion<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.position >= self.end_sample {
+            self.jump_to_start()?;
+        }
+
+        let sample = match self.input.next() {
+            Some(sample) => sample,
+            // The region goes past the end of the input, loops from there.
+            None if self.position > self.start_sample => {
+                self.jump_to_start()?;
+                self.input.next()?
+            }
+            None => return None,
+        };
+        self.position += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // infinite
+        (0, None)
+    }
+}
+
+impl<I> Source for LoopRegion<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        let frames = pos.as_nanos() as u64 * self.input.sample_rate() as u64 / 1_000_000_000;
+        self.position = frames * self.input.channels() as u64;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn jumps_back_to_the_start() {
+        let source = SamplesBuffer::new(1, 10, (0..10i16).collect::<Vec<_>>());
+        let looped = source.loop_region(Duration::from_millis(200), Duration::from_millis(500));
+        assert_eq!(looped.total_duration(), None);
+
+        let played: Vec<i16> = looped.take(11).collect();
+        assert_eq!(played, vec![0, 1, 2, 3, 4, 2, 3, 4, 2, 3, 4]);
+    }
+
+    #[test]
+    fn keeps_frames_aligned() {
+        let data = (0..20i16).collect::<Vec<_>>();
+        let source = SamplesBuffer::new(2, 10, data);
+        // 150 ms is in the middle of the second frame
+        let looped = source.loop_region(Duration::from_millis(150), Duration::from_millis(300));
+
+        let played: Vec<i16> = looped.take(12).collect();
+        assert_eq!(played, vec![0, 1, 2, 3, 4, 5, 2, 3, 4, 5, 2, 3]);
+    }
+
+    #[test]
+    fn region_past_the_end() {
+        let source = SamplesBuffer::new(1, 10, (0..4i16).collect::<Vec<_>>());
+        let looped = source.loop_region(Duration::from_millis(200), Duration::from_secs(10));
+
+        let played: Vec<i16> = looped.take(8).collect();
+        assert_eq!(played, vec![0, 1, 2, 3, 2, 3, 2, 3]);
+    }
+}
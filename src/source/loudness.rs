@@ -0,0 +1,374 @@
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{ChannelLayout, SeekError};
+use crate::{Sample, SampleFormat, Source};
+
+/// Loudness of the blocks that are ignored whatever the rest of the sound, in LUFS.
+const ABSOLUTE_GATE: f64 = -70.0;
+/// How far below the loudness of the sound a block is ignored, in LU.
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Internal function that builds a `LoudnessMeter` object.
+pub fn loudness_meter<I>(input: I) -> LoudnessMeter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let mut meter = LoudnessMeter {
+        input,
+        loudness: Loudness {
+            blocks: Arc::new(Mutex::new(Vec::new())),
+        },
+        filters: Vec::new(),
+        weights: Vec::new(),
+        sample_rate: 0,
+        current_channel: 0,
+        sub_block_len: 0,
+        sub_block_frames: 0,
+        energy: 0.0,
+        sub_blocks: [0.0; 4],
+        sub_blocks_filled: 0,
+    };
+    meter.reset();
+    meter
+}
+
+/// Source that measures the loudness of another one as it plays, following ITU-R BS.1770.
+///
+/// The sound itself is left untouched. The measure can be read from the [`Loudness`] returned
+/// by [`loudness`](LoudnessMeter::loudness), on any thread.
+#[derive(Clone, Debug)]
+pub struct LoudnessMeter<I> {
+    input: I,
+    loudness: Loudness,
+    // The two K-weighting stages of every channel.
+    filters: Vec<[Biquad; 2]>,
+    weights: Vec<f64>,
+    sample_rate: u32,
+    current_channel: usize,
+    // Gating blocks last 400 ms and overlap by 75%, so they are made of four 100 ms
+    // sub-blocks.
+    sub_block_len: usize,
+    sub_block_frames: usize,
+    // Weighted sum of the squared samples of the current sub-block.
+    energy: f64,
+    sub_blocks: [f64; 4],
+    sub_blocks_filled: usize,
+}
+
+/// Integrated loudness measured by a [`LoudnessMeter`], shared with it.
+#[derive(Clone, Debug)]
+pub struct Loudness {
+    // Mean square of every gating block, weighted across the channels.
+    blocks: Arc<Mutex<Vec<f64>>>,
+}
+
+impl Loudness {
+    /// Returns the integrated loudness of what was played so far, in LUFS.
+    ///
+    /// Returns `None` until a whole 400 ms block louder than -70 LUFS was played.
+    pub fn integrated_lufs(&self) -> Option<f32> {
+        let blocks = self.blocks.lock().unwrap();
+        let loud: Vec<f64> = blocks
+            .iter()
+            .copied()
+            .filter(|&power| block_loudness(power) > ABSOLUTE_GATE)
+            .collect();
+        if loud.is_empty() {
+            return None;
+        }
+
+        let mean = |powers: &mut dyn Iterator<Item = f64>| {
+            let (sum, count) = powers.fold((0.0, 0), |(sum, count), p| (sum + p, count + 1));
+            sum / count as f64
+        };
+        let gate = block_loudness(mean(&mut loud.iter().copied())) + RELATIVE_GATE;
+        // The loudest block is always above the relative gate.
+        let power = mean(&mut loud.into_iter().filter(|&p| block_loudness(p) > gate));
+        Some(block_loudness(power) as f32)
+    }
+}
+
+fn block_loudness(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+impl<I> LoudnessMeter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle to the loudness measured so far, that stays valid once this source is
+    /// dropped.
+    #[inline]
+    pub fn loudness(&self) -> Loudness {
+        self.loudness.clone()
+    }
+
+    /// Returns the integrated loudness of what was played so far, in LUFS.
+    ///
+    /// Same as [`Loudness::integrated_lufs`].
+    #[inline]
+    pub fn integrated_lufs(&self) -> Option<f32> {
+        self.loudness.integrated_lufs()
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Starts a new block, and adapts the filters to the format of the input.
+    fn reset(&mut self) {
+        let channels = self.input.channels().max(1) as usize;
+        self.sample_rate = self.input.sample_rate();
+        self.filters = vec![k_weighting(self.sample_rate as f64); channels];
+        self.weights = channel_weights(self.input.channels());
+        self.current_channel = 0;
+        self.sub_block_len = (self.sample_rate as usize / 10).max(1);
+        self.sub_block_frames = 0;
+        self.energy = 0.0;
+        self.sub_blocks_filled = 0;
+    }
+
+    fn end_sub_block(&mut self) {
+        self.sub_blocks.rotate_left(1);
+        self.sub_blocks[3] = self.energy;
+        self.energy = 0.0;
+        self.sub_block_frames = 0;
+        self.sub_blocks_filled = (self.sub_blocks_filled + 1).min(4);
+
+        if self.sub_blocks_filled == 4 {
+            let power = self.sub_blocks.iter().sum::<f64>() / (4 * self.sub_block_len) as f64;
+            self.loudness.blocks.lock().unwrap().push(power);
+        }
+    }
+}
+
+/// Weights of the channels, from BS.1770: the surround channels count more and the low
+/// frequency effects are left out.
+fn channel_weights(channels: u16) -> Vec<f64> {
+    const SURROUND: f64 = 1.41;
+    match ChannelLayout::from_channels(channels) {
+        Some(ChannelLayout::Quad) => vec![1.0, 1.0, SURROUND, SURROUND],
+        Some(ChannelLayout::Surround5_1) => vec![1.0, 1.0, 1.0, 0.0, SURROUND, SURROUND],
+        Some(ChannelLayout::Surround7_1) => {
+            vec![1.0, 1.0, 1.0, 0.0, SURROUND, SURROUND, SURROUND, SURROUND]
+        }
+        _ => vec![1.0; channels.max(1) as usize],
+    }
+}
+
+/// The high shelf that models the head, followed by the RLB high-pass, designed for the
+/// sample rate. At 48 kHz they match the coefficients given in BS.1770.
+fn k_weighting(sample_rate: f64) -> [Biquad; 2] {
+    let k = (PI * 1681.974450955533 / sample_rate).tan();
+    let q = 0.7071752369554196;
+    let vh = 10f64.powf(3.999843853973347 / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::new(
+        [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    let k = (PI * 38.13547087602444 / sample_rate).tan();
+    let q = 0.5003270373238773;
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad::new(
+        [1.0, -2.0, 1.0],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    [shelf, high_pass]
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    // Transposed direct form II state.
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Biquad {
+        Biquad { b, a, z: [0.0; 2] }
+    }
+
+    #[inline]
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+impl<I> Iterator for LoudnessMeter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        // The format can only change between frames.
+        if self.current_channel == 0
+            && (self.filters.len() != self.input.channels().max(1) as usize
+                || self.sample_rate != self.input.sample_rate())
+        {
+            self.reset();
+        }
+
+        let sample = self.input.next()?;
+        let [shelf, high_pass] = &mut self.filters[self.current_channel];
+        let weighted = high_pass.process(shelf.process(sample.to_f32() as f64));
+        self.energy += self.weights[self.current_channel] * weighted * weighted;
+
+        self.current_channel += 1;
+        if self.current_channel == self.filters.len() {
+            self.current_channel = 0;
+            self.sub_block_frames += 1;
+            if self.sub_block_frames == self.sub_block_len {
+                self.end_sub_block();
+            }
+        }
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for LoudnessMeter<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for LoudnessMeter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // What was measured before is kept.
+        self.reset();
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    fn sine(channels: u16, sample_rate: u32, amplitude: f32, seconds: u32) -> Vec<f32> {
+        (0..sample_rate * seconds)
+            .map(|i| amplitude * (TAU * 1000.0 * i as f32 / sample_rate as f32).sin())
+            .flat_map(|sample| vec![sample; channels as usize])
+            .collect()
+    }
+
+    fn measure(channels: u16, sample_rate: u32, data: Vec<f32>) -> Option<f32> {
+        let meter = SamplesBuffer::new(channels, sample_rate, data.clone()).measure_lufs();
+        let loudness = meter.loudness();
+        // the sound is not changed
+        assert_eq!(meter.collect::<Vec<_>>(), data);
+        loudness.integrated_lufs()
+    }
+
+    #[test]
+    fn full_scale_sine() {
+        // BS.1770 gives -3.01 LUFS for a 1 kHz sine at full scale on one channel
+        for sample_rate in [44100, 48000] {
+            let lufs = measure(1, sample_rate, sine(1, sample_rate, 1.0, 3)).unwrap();
+            assert!((lufs + 3.01).abs() < 0.05, "{lufs} at {sample_rate} Hz");
+        }
+        // the power of the channels adds up
+        let lufs = measure(2, 48000, sine(2, 48000, 1.0, 3)).unwrap();
+        assert!(lufs.abs() < 0.05, "{lufs}");
+    }
+
+    #[test]
+    fn silence_is_gated_out() {
+        assert_eq!(measure(1, 48000, vec![0.0; 48000]), None);
+
+        // two seconds at -9.03 LUFS, then silence
+        let mut data = sine(1, 48000, 0.5, 2);
+        data.resize(4 * 48000, 0.0);
+        let lufs = measure(1, 48000, data).unwrap();
+        // The silent blocks are ignored, but the three blocks that overlap the end of the
+        // sine are not: 18.5 blocks worth of power over 20.
+        let expected = -9.03 + 10.0 * (18.5f32 / 20.0).log10();
+        assert!((lufs - expected).abs() < 0.05, "{lufs}");
+    }
+
+    #[test]
+    fn readable_while_playing() {
+        let mut meter = SamplesBuffer::new(1, 48000, sine(1, 48000, 1.0, 1)).measure_lufs();
+        // not a whole block yet
+        meter.by_ref().take(48000 * 3 / 10).for_each(drop);
+        assert_eq!(meter.integrated_lufs(), None);
+        meter.by_ref().take(48000 / 10).for_each(drop);
+        assert!(meter.integrated_lufs().is_some());
+    }
+}
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `MapSamples` object.
+pub fn map_samples<I, F>(input: I, f: F) -> MapSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(I::Item) -> I::Item,
+{
+    MapSamples { input, f }
+}
+
+/// Source that calls a closure on every sample of another source.
+#[derive(Clone, Debug)]
+pub struct MapSamples<I, F> {
+    input: I,
+    f: F,
+}
+
+impl<I, F> MapSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(I::Item) -> I::Item,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I, F> Iterator for MapSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(I::Item) -> I::Item,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        self.input.next().map(&mut self.f)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, F> ExactSizeIterator for MapSamples<I, F>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    F: FnMut(I::Item) -> I::Item,
+{
+}
+
+impl<I, F> Source for MapSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(I::Item) -> I::Item,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.input.playback_pos()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn maps_every_sample() {
+        let source = SamplesBuffer::new(2, 44100, vec![1i16, -2, 3, i16::MAX]);
+        let mapped = source.map_samples(|sample| sample.saturating_mul(10));
+        assert_eq!(mapped.collect::<Vec<_>>(), vec![10, -20, 30, i16::MAX]);
+    }
+
+    #[test]
+    fn closure_keeps_its_state() {
+        let source = SamplesBuffer::new(1, 44100, vec![0.5f32; 4]);
+        let mut count = 0.0;
+        let mapped = source.map_samples(move |sample| {
+            count += 1.0;
+            sample * count
+        });
+        assert_eq!(mapped.collect::<Vec<_>>(), vec![0.5, 1.0, 1.5, 2.0]);
+    }
+
+    #[test]
+    fn seeks_the_inner_source() {
+        let mut mapped = SamplesBuffer::new(1, 10, vec![1i16, 2, 3]).map_samples(|s| s);
+        mapped.try_seek(Duration::from_millis(200)).unwrap();
+        assert_eq!(mapped.next(), Some(3));
+    }
+}
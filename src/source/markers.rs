@@ -0,0 +1,260 @@
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Internal function that builds a `Markers` object.
+pub fn markers<I>(input: I, markers: &[Duration], sender: Sender<usize>) -> Markers<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let mut markers: Vec<(Duration, usize)> = markers
+        .iter()
+        .enumerate()
+        .map(|(index, &time)| (time, index))
+        .collect();
+    // Stable, markers at the same time are sent in the order they were given.
+    markers.sort_by_key(|&(time, _)| time);
+    let sample_rate = input.sample_rate();
+    Markers {
+        input,
+        markers,
+        next_marker: 0,
+        sender,
+        elapsed_before_rate: Duration::ZERO,
+        frames_at_rate: 0,
+        sample_rate,
+        current_channel: 0,
+    }
+}
+
+/// Source that sends the index of a marker when its time is reached, see
+/// [`Source::markers`].
+#[derive(Clone, Debug)]
+pub struct Markers<I> {
+    input: I,
+    // The markers and their index, by time.
+    markers: Vec<(Duration, usize)>,
+    next_marker: usize,
+    sender: Sender<usize>,
+    // The time played is counted in frames since the last change of sample rate, so that
+    // it does not drift.
+    elapsed_before_rate: Duration,
+    frames_at_rate: u64,
+    sample_rate: u32,
+    current_channel: u16,
+}
+
+impl<I> Markers<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns how many markers have been sent or skipped over by seeking.
+    #[inline]
+    pub fn markers_passed(&self) -> usize {
+        self.next_marker
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // The time at which the next frame plays.
+    fn elapsed(&self) -> Duration {
+        let nanos = self.frames_at_rate as u128 * NANOS_PER_SEC / self.sample_rate.max(1) as u128;
+        self.elapsed_before_rate + Duration::from_nanos(nanos as u64)
+    }
+
+    // Sends the markers up to `time`.
+    fn send_markers(&mut self, time: Duration) {
+        while let Some(&(marker, index)) = self.markers.get(self.next_marker) {
+            if marker > time {
+                break;
+            }
+            // Nobody listening is not a reason to stop playing.
+            let _ = self.sender.send(index);
+            self.next_marker += 1;
+        }
+    }
+}
+
+impl<I> Iterator for Markers<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel == 0 {
+            // The format can only change between frames.
+            if self.sample_rate != self.input.sample_rate() {
+                self.elapsed_before_rate = self.elapsed();
+                self.frames_at_rate = 0;
+                self.sample_rate = self.input.sample_rate();
+            }
+        }
+        let sample = self.input.next()?;
+        if self.current_channel == 0 {
+            // All of the markers up to the start of this frame, however many there are.
+            self.send_markers(self.elapsed());
+        }
+
+        self.current_channel += 1;
+        if self.current_channel >= self.input.channels().max(1) {
+            self.current_channel = 0;
+            self.frames_at_rate += 1;
+        }
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Markers<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Markers<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    /// The markers jumped over are not sent, and seeking back does not send the markers that
+    /// were already sent again.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.elapsed_before_rate = pos;
+        self.frames_at_rate = 0;
+        self.sample_rate = self.input.sample_rate();
+        self.current_channel = 0;
+        // The markers exactly at `pos` are sent when its frame is played.
+        while self
+            .markers
+            .get(self.next_marker)
+            .is_some_and(|&(marker, _)| marker < pos)
+        {
+            self.next_marker += 1;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    // One second of stereo at 10 Hz, every sample numbered.
+    fn source() -> SamplesBuffer<i16> {
+        SamplesBuffer::new(2, 10, (0..20).collect::<Vec<i16>>())
+    }
+
+    #[test]
+    fn sends_markers_as_they_play() {
+        let (tx, rx) = mpsc::channel();
+        let times = [0, 300, 250, 900].map(Duration::from_millis);
+        let mut source = source().markers(&times, tx);
+
+        assert_eq!(source.next(), Some(0));
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [0]);
+        // 250 ms falls in the frame played at 300 ms
+        assert_eq!(source.by_ref().take(5).collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+        assert!(rx.try_recv().is_err());
+        assert_eq!(source.next(), Some(6));
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [2, 1]);
+
+        assert_eq!(source.by_ref().count(), 13);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [3]);
+        assert_eq!(source.markers_passed(), 4);
+    }
+
+    #[test]
+    fn audio_is_unchanged() {
+        let (tx, _rx) = mpsc::channel();
+        let output: Vec<i16> = source().markers(&[Duration::ZERO], tx).collect();
+        assert_eq!(output, (0..20).collect::<Vec<i16>>());
+    }
+
+    #[test]
+    fn seeking_sends_markers_once() {
+        let (tx, rx) = mpsc::channel();
+        let times = [100, 500, 800].map(Duration::from_millis);
+        let mut source = source().markers(&times, tx);
+
+        source.by_ref().take(4).count();
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [0]);
+        source.try_seek(Duration::from_millis(600)).unwrap();
+        source.by_ref().take(2).count();
+        assert!(rx.try_recv().is_err());
+        source.try_seek(Duration::ZERO).unwrap();
+        source.by_ref().count();
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [2]);
+    }
+}
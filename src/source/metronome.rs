@@ -0,0 +1,282 @@
+use std::f32::consts::TAU;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::Source;
+
+use super::SeekError;
+
+/// Length of a click, in seconds.
+const CLICK_SECS: f32 = 0.01;
+
+/// An infinite source that clicks on every beat, louder and higher on the first beat of each
+/// bar.
+///
+/// Has one channel. The tempo can be changed while it plays with a [`MetronomeController`],
+/// the new tempo takes effect from the next beat. The beats are placed from the exact beat
+/// length rather than from a rounded number of samples, so they do not drift away from the
+/// tempo however long the metronome plays.
+#[derive(Clone, Debug)]
+pub struct Metronome {
+    sample_rate: u32,
+    // The tempo, as the bits of an f32, shared with the controllers.
+    bpm: Arc<AtomicU32>,
+    beats_per_bar: u32,
+    // Number of samples played.
+    position: u64,
+    // Where the next beat falls, in samples, not rounded.
+    next_beat: f64,
+    // The number of beats started.
+    beats: u64,
+    // Where the current click started, and whether it is accented.
+    click_start: Option<u64>,
+    accent: bool,
+}
+
+/// Changes the tempo of a [`Metronome`] while it plays, from any thread.
+#[derive(Clone, Debug)]
+pub struct MetronomeController {
+    bpm: Arc<AtomicU32>,
+}
+
+impl MetronomeController {
+    /// Sets the tempo, in beats per minute. It takes effect from the next beat.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bpm` is not a positive number.
+    #[inline]
+    pub fn set_bpm(&self, bpm: f32) {
+        assert_bpm(bpm);
+        self.bpm.store(bpm.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the tempo, in beats per minute.
+    #[inline]
+    pub fn bpm(&self) -> f32 {
+        f32::from_bits(self.bpm.load(Ordering::Relaxed))
+    }
+}
+
+fn assert_bpm(bpm: f32) {
+    assert!(
+        bpm.is_finite() && bpm > 0.0,
+        "the tempo of a metronome must be a positive number, got {bpm}"
+    );
+}
+
+impl Metronome {
+    /// Builds a metronome that plays `bpm` beats per minute, in bars of four beats.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bpm` is not a positive number or if `sample_rate` is 0.
+    #[inline]
+    pub fn new(bpm: f32, sample_rate: u32) -> Metronome {
+        assert_bpm(bpm);
+        assert!(sample_rate >= 1, "the sample rate can not be 0");
+        Metronome {
+            sample_rate,
+            bpm: Arc::new(AtomicU32::new(bpm.to_bits())),
+            beats_per_bar: 4,
+            position: 0,
+            next_beat: 0.0,
+            beats: 0,
+            click_start: None,
+            accent: false,
+        }
+    }
+
+    /// Sets the number of beats in a bar, the first of which is accented. With `1`, every
+    /// beat is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `beats_per_bar` is 0.
+    #[inline]
+    pub fn with_beats_per_bar(mut self, beats_per_bar: u32) -> Metronome {
+        assert!(beats_per_bar >= 1, "a bar needs at least one beat");
+        self.beats_per_bar = beats_per_bar;
+        self
+    }
+
+    /// Returns a controller that changes the tempo of this metronome.
+    #[inline]
+    pub fn controller(&self) -> MetronomeController {
+        MetronomeController {
+            bpm: self.bpm.clone(),
+        }
+    }
+
+    // Length of a beat at the current tempo, in samples.
+    #[inline]
+    fn beat_len(&self) -> f64 {
+        let bpm = f32::from_bits(self.bpm.load(Ordering::Relaxed));
+        60.0 * self.sample_rate as f64 / bpm as f64
+    }
+
+    fn start_beat(&mut self) {
+        self.click_start = Some(self.position);
+        self.accent = self.beats.is_multiple_of(self.beats_per_bar as u64);
+        self.beats += 1;
+        self.next_beat += self.beat_len();
+    }
+}
+
+impl Iterator for Metronome {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.position >= self.next_beat.round() as u64 {
+            self.start_beat();
+        }
+
+        let click_len = (CLICK_SECS * self.sample_rate as f32) as u64;
+        let sample = match self.click_start {
+            Some(start) if self.position - start < click_len => {
+                let t = (self.position - start) as f32;
+                let (freq, volume) = match self.accent {
+                    true => (1500.0, 1.0),
+                    false => (1000.0, 0.6),
+                };
+                let envelope = (1.0 - t / click_len as f32).powi(2);
+                volume * envelope * (TAU * freq * t / self.sample_rate as f32).cos()
+            }
+            _ => 0.0,
+        };
+        self.position += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl Source for Metronome {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    /// Seeks as if the metronome had always played at its current tempo.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let beat_len = self.beat_len();
+        let position = (pos.as_secs_f64() * self.sample_rate as f64) as u64;
+        // The beat being played, its click may still be sounding.
+        let beat = (position as f64 / beat_len).floor() as u64;
+        self.position = (beat as f64 * beat_len).round() as u64;
+        self.next_beat = beat as f64 * beat_len;
+        self.beats = beat;
+        self.click_start = None;
+        while self.position < position {
+            self.next();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Metronome;
+    use crate::source::Source;
+
+    // The samples at which a click starts.
+    fn clicks(samples: &[f32]) -> Vec<usize> {
+        let click_len = 480;
+        let mut starts: Vec<usize> = Vec::new();
+        for (i, sample) in samples.iter().enumerate() {
+            let in_click = starts.last().is_some_and(|&start| i < start + click_len);
+            if *sample != 0.0 && !in_click {
+                starts.push(i);
+            }
+        }
+        starts
+    }
+
+    #[test]
+    fn clicks_on_the_beat() {
+        let metronome = Metronome::new(120.0, 48000);
+        assert_eq!(metronome.channels(), 1);
+        assert_eq!(metronome.total_duration(), None);
+
+        let samples: Vec<f32> = metronome.take(48000 * 3).collect();
+        assert_eq!(clicks(&samples), [0, 24000, 48000, 72000, 96000, 120000]);
+    }
+
+    #[test]
+    fn accents_the_first_beat_of_each_bar() {
+        let samples: Vec<f32> = Metronome::new(120.0, 48000)
+            .with_beats_per_bar(3)
+            .take(48000 * 3)
+            .collect();
+        let accented: Vec<bool> = clicks(&samples)
+            .into_iter()
+            .map(|start| samples[start] == 1.0)
+            .collect();
+        assert_eq!(accented, [true, false, false, true, false, false]);
+    }
+
+    #[test]
+    fn does_not_drift() {
+        // 44100 * 60 / 130 is not a whole number of samples
+        let samples: Vec<f32> = Metronome::new(130.0, 44100).take(44100 * 60).collect();
+        let starts = clicks(&samples);
+        assert_eq!(starts.len(), 130);
+        for (beat, start) in starts.into_iter().enumerate() {
+            let exact = beat as f64 * 44100.0 * 60.0 / 130.0;
+            assert!((start as f64 - exact).abs() <= 0.5, "{beat} {start}");
+        }
+    }
+
+    #[test]
+    fn tempo_changes_at_the_next_beat() {
+        let mut metronome = Metronome::new(120.0, 48000);
+        let controller = metronome.controller();
+        let mut samples: Vec<f32> = metronome.by_ref().take(12000).collect();
+        controller.set_bpm(240.0);
+        assert_eq!(controller.bpm(), 240.0);
+        samples.extend(metronome.take(36000));
+        assert_eq!(clicks(&samples), [0, 24000, 36000]);
+    }
+
+    #[test]
+    fn seeking_lands_between_beats() {
+        let mut metronome = Metronome::new(120.0, 48000);
+        metronome.try_seek(Duration::from_millis(1250)).unwrap();
+        let samples: Vec<f32> = metronome.take(24000).collect();
+        assert_eq!(clicks(&samples), [12000]);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive number")]
+    fn rejects_a_zero_tempo() {
+        Metronome::new(0.0, 48000);
+    }
+}
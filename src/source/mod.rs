@@ -2,75 +2,168 @@
 
 use core::fmt;
 use core::time::Duration;
+use std::sync::mpsc::Sender;
 
-use cpal::FromSample;
+use cpal::{FromSample, SampleFormat};
 
+use crate::buffer::{SamplesBuffer, UnboundedSourceError};
 use crate::Sample;
 
 pub use self::agc::AutomaticGainControl;
 pub use self::amplify::Amplify;
+pub use self::balance::Balance;
+pub use self::bitcrush::Bitcrush;
 pub use self::blt::BltFilter;
 pub use self::buffered::Buffered;
+pub use self::channel_layout::ChannelLayout;
+pub use self::channel_matrix::ChannelMatrix;
 pub use self::channel_volume::ChannelVolume;
 pub use self::chirp::{chirp, Chirp};
-pub use self::crossfade::Crossfade;
+pub use self::chorus::Chorus;
+pub use self::crossfade::{crossfade, Crossfade, CrossfadeTransition};
+pub use self::declick::Declick;
 pub use self::delay::Delay;
+pub use self::dither::{Dither, DitherKind};
 pub use self::done::Done;
+pub use self::downmix::Downmix;
 pub use self::empty::Empty;
 pub use self::empty_callback::EmptyCallback;
+pub use self::equalizer::{Equalizer, EqualizerController};
 pub use self::fadein::FadeIn;
 pub use self::fadeout::FadeOut;
+pub use self::filter_sweep::{FilterKind, FilterSweep};
+pub use self::flanger::Flanger;
+pub use self::frames::{Frame, Frames};
 pub use self::from_factory::{from_factory, FromFactoryIter};
 pub use self::from_iter::{from_iter, FromIter};
+pub use self::haas::{Haas, Side};
+pub use self::input::Input;
+pub use self::inspect_samples::InspectSamples;
+pub use self::lazy::LazySource;
+pub use self::leveler::Leveler;
+pub use self::limit_samples::LimitSamples;
 pub use self::linear_ramp::LinearGainRamp;
+pub use self::loop_region::LoopRegion;
+pub use self::loudness::{Loudness, LoudnessMeter};
+pub use self::map_samples::MapSamples;
+pub use self::markers::Markers;
+pub use self::metronome::{Metronome, MetronomeController};
 pub use self::mix::Mix;
+pub use self::normalize::Normalize;
+pub use self::oversample::{Oversampled, Upsampled};
 pub use self::pausable::Pausable;
 pub use self::periodic::PeriodicAccess;
+pub use self::pitch_shift::PitchShift;
 pub use self::position::TrackPosition;
+pub use self::prefetch::Prefetch;
+pub use self::process_frames::{FrameAction, ProcessFrames};
+pub use self::remap_channels::RemapChannels;
+pub use self::remove_dc::RemoveDc;
 pub use self::repeat::Repeat;
+pub(crate) use self::ring_buffer::{ring, RingReader, RingWriter};
+pub use self::ring_buffer::{RingBufferProducer, RingBufferSource};
 pub use self::samples_converter::SamplesConverter;
+pub use self::saturate::Saturate;
+pub use self::sequence::{sequence, Sequence};
 pub use self::signal_generator::{Function, SignalGenerator};
+pub use self::silence::Silence;
 pub use self::sine::SineWave;
 pub use self::skip::SkipDuration;
 pub use self::skippable::Skippable;
 pub use self::spatial::Spatial;
 pub use self::speed::Speed;
+pub use self::stereo_width::StereoWidth;
 pub use self::stoppable::Stoppable;
+pub use self::stretch::Stretch;
+pub use self::switchable::{SwitchHandle, SwitchableSource};
 pub use self::take::TakeDuration;
-pub use self::uniform::UniformSourceIterator;
+pub use self::tee::Tee;
+pub use self::tremolo::Tremolo;
+pub use self::underrun::UnderrunPolicy;
+pub use self::uniform::{ResampleQuality, UniformSourceIterator};
+pub use self::upmix::Upmix;
+pub use self::variable_speed::{VariableSpeed, VariableSpeedController};
+pub use self::vibrato::Vibrato;
+pub use self::volume_ramp::VolumeRamp;
 pub use self::zero::Zero;
 
 mod agc;
 mod amplify;
+mod balance;
+mod bitcrush;
 mod blt;
 mod buffered;
+mod channel_layout;
+mod channel_matrix;
 mod channel_volume;
 mod chirp;
+mod chorus;
 mod crossfade;
+mod declick;
 mod delay;
+mod delay_line;
+mod dither;
 mod done;
+mod downmix;
 mod empty;
 mod empty_callback;
+mod equalizer;
 mod fadein;
 mod fadeout;
+mod filter_sweep;
+mod flanger;
+mod frames;
 mod from_factory;
 mod from_iter;
+mod haas;
+mod input;
+mod inspect_samples;
+mod lazy;
+mod leveler;
+mod limit_samples;
 mod linear_ramp;
+mod loop_region;
+mod loudness;
+mod map_samples;
+mod markers;
+mod metronome;
 mod mix;
+mod normalize;
+mod oversample;
 mod pausable;
 mod periodic;
+mod pitch_shift;
 mod position;
+mod prefetch;
+mod process_frames;
+mod remap_channels;
+mod remove_dc;
 mod repeat;
+mod ring_buffer;
 mod samples_converter;
+mod saturate;
+mod sequence;
 mod signal_generator;
+mod silence;
 mod sine;
 mod skip;
 mod skippable;
 mod spatial;
 mod speed;
+mod stereo_width;
 mod stoppable;
+mod stretch;
+mod switchable;
 mod take;
+mod tee;
+mod tremolo;
+mod trim_silence;
+mod underrun;
 mod uniform;
+mod upmix;
+mod variable_speed;
+mod vibrato;
+mod volume_ramp;
 mod zero;
 
 #[cfg(feature = "noise")]
@@ -78,6 +171,11 @@ mod noise;
 #[cfg(feature = "noise")]
 pub use self::noise::{pink, white, PinkNoise, WhiteNoise};
 
+#[cfg(feature = "wav")]
+mod wav_output;
+#[cfg(feature = "wav")]
+pub use self::wav_output::{render_to_wav, RenderError};
+
 /// A source of samples.
 ///
 /// # A quick lesson about sounds
@@ -146,6 +244,23 @@ pub use self::noise::{pink, white, PinkNoise, WhiteNoise};
 /// the number of samples that remain in the iterator before the samples rate and number of
 /// channels can potentially change.
 ///
+/// # Borrowing a source
+///
+/// The methods of this trait take the source by value. A mutable reference to a source is also
+/// a source, which forwards everything, seeking included, to the one it borrows. So
+/// [`Iterator::by_ref`] lets a source go through a combinator for a while and then be used
+/// again, with whatever the combinator did not play:
+///
+/// ```
+/// use rodio::buffer::SamplesBuffer;
+/// use rodio::Source;
+///
+/// let mut source = SamplesBuffer::new(1, 10, (0..10i16).collect::<Vec<_>>());
+/// let start: Vec<i16> = source.by_ref().limit_samples(3).collect();
+/// assert_eq!(start, vec![0, 1, 2]);
+/// assert_eq!(source.channels(), 1);
+/// assert_eq!(source.next(), Some(3));
+/// ```
 pub trait Source: Iterator
 where
     Self::Item: Sample,
@@ -169,6 +284,18 @@ where
     /// `None` indicates at the same time "infinite" or "unknown".
     fn total_duration(&self) -> Option<Duration>;
 
+    /// Returns the number of samples left to play, counting the samples of all the channels,
+    /// if known.
+    ///
+    /// Unlike [`total_duration`](Source::total_duration) this goes down as the source plays,
+    /// and is exact rather than rounded to a duration, so it can size an output buffer.
+    /// `None`, the default, means at the same time "infinite" or "unknown". Sources that play
+    /// as many samples as their inner source return the count of that one.
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        None
+    }
+
     /// Stores the source in a buffer in addition to returning it. This iterator can be cloned.
 
     #[inline]
@@ -179,6 +306,49 @@ where
         buffered::buffered(self)
     }
 
+    /// Reads the source ahead of playback on a background thread, keeping up to `capacity`
+    /// samples in memory.
+    ///
+    /// This keeps a source that is slow to produce its samples, such as a heavy decoder, from
+    /// holding up the audio thread. When the background thread falls behind, silence is played
+    /// until it catches up. The channels, sample rate and total duration are the ones of the
+    /// source when this is called. Seeking is not supported.
+    #[inline]
+    fn prefetch(self, capacity: usize) -> Prefetch<Self::Item>
+    where
+        Self: Sized + Send + 'static,
+        Self::Item: Send,
+    {
+        prefetch::prefetch(self, capacity, UnderrunPolicy::EmitSilence)
+    }
+
+    /// Same as [`prefetch`](Source::prefetch), but `policy` chooses what is played when the
+    /// background thread falls behind.
+    #[inline]
+    fn prefetch_with_policy(self, capacity: usize, policy: UnderrunPolicy) -> Prefetch<Self::Item>
+    where
+        Self: Sized + Send + 'static,
+        Self::Item: Send,
+    {
+        prefetch::prefetch(self, capacity, policy)
+    }
+
+    /// Splits the source in two sources that both play all of its samples, for example to
+    /// send the same mix to two devices, or to record what is being played.
+    ///
+    /// The two halves can be played from different threads and at their own pace. Whichever is
+    /// ahead reads this source, and the samples are kept until the other one plays them too, up
+    /// to `capacity` samples. A half that falls further behind skips the oldest frames it did
+    /// not play, so that the one ahead never waits for it. Dropping a half lets the other play
+    /// alone. Seeking is not supported.
+    #[inline]
+    fn split(self, capacity: usize) -> (Tee<Self>, Tee<Self>)
+    where
+        Self: Sized,
+    {
+        tee::tee(self, capacity)
+    }
+
     /// Mixes this source with another one.
     #[inline]
     fn mix<S>(self, other: S) -> Mix<Self, S>
@@ -191,6 +361,25 @@ where
         mix::mix(self, other)
     }
 
+    /// Plays this source until `end`, then jumps back to `start` and plays the region
+    /// between them forever, such as the loop of a piece of music that follows an intro.
+    ///
+    /// Both points are rounded down to the start of a frame. The jump is done by seeking, a
+    /// source that can not seek ends when reaching `end`. If `end` is past the end of the
+    /// source, it jumps back when the source ends instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is not before `end`.
+    #[inline]
+    fn loop_region(self, start: Duration, end: Duration) -> LoopRegion<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        loop_region::loop_region(self, start, end)
+    }
+
     /// Repeats this source forever.
     ///
     /// Note that this works by storing the data in a buffer, so the amount of memory used is
@@ -212,6 +401,20 @@ where
         take::take_duration(self, duration)
     }
 
+    /// Plays at most `samples` samples of this source and then stops.
+    ///
+    /// The limit is rounded down to a whole number of frames of the source, so it never ends in
+    /// the middle of one. This is how to bound an endless source in exact sample counts, for
+    /// example before rendering it to a file. The total duration is the one of the limit if the
+    /// source is longer, or if its own duration is unknown.
+    #[inline]
+    fn limit_samples(self, samples: usize) -> LimitSamples<Self>
+    where
+        Self: Sized,
+    {
+        limit_samples::limit_samples(self, samples)
+    }
+
     /// Delays the sound by a certain duration.
     ///
     /// The rate and channels of the silence will use the same format as the first frame of the
@@ -244,6 +447,198 @@ where
         amplify::amplify(self, value)
     }
 
+    /// Rounds off the peaks of the sound with a `tanh` soft clipper instead of clipping them
+    /// hard.
+    ///
+    /// The samples are multiplied by `drive` before the curve is applied, the higher it is the
+    /// stronger the effect. The output is normalized so that a full scale sample stays at full
+    /// scale. A `drive` of `0.0` leaves the sound unchanged.
+    #[inline]
+    fn saturate(self, drive: f32) -> Saturate<Self>
+    where
+        Self: Sized,
+        Self::Item: FromSample<f32>,
+    {
+        saturate::saturate(self, drive)
+    }
+
+    /// Lowers the resolution of the sound to `bits` bits, and holds every sample for
+    /// `downsample` frames, without any filtering, for a lo-fi sound.
+    ///
+    /// The samples are rounded in the `f32` domain, so this works the same on every sample
+    /// type: with `bits` bits a sample takes one of `2^bits` values between `-1.0` and `1.0`.
+    /// The channels are held together, so that they stay aligned. With `bits` at least the
+    /// size of the samples, such as `16` for `i16`, and a `downsample` of `1`, the sound is
+    /// unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` or `downsample` is 0.
+    #[inline]
+    fn bitcrush(self, bits: u8, downsample: u32) -> Bitcrush<Self>
+    where
+        Self: Sized,
+        Self::Item: FromSample<f32>,
+    {
+        bitcrush::bitcrush(self, bits, downsample)
+    }
+
+    /// Runs a nonlinear effect, such as [`saturate`](Source::saturate), at `factor` times the
+    /// sample rate to reduce aliasing.
+    ///
+    /// The source is upsampled and given to `effect`, and what `effect` returns is brought back
+    /// to the original sample rate. The harmonics the effect creates above the original Nyquist
+    /// frequency are filtered out on the way down instead of folding back into the audible
+    /// range. A `factor` of 2 or 4 is usually enough. The format of the source when this is
+    /// called is kept throughout, and the result can not be seeked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// let warm = SineWave::new(5000.0).oversampled(4, |source| source.saturate(2.0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is 0.
+    #[inline]
+    fn oversampled<F, S>(self, factor: u32, effect: F) -> Oversampled<S>
+    where
+        Self: Sized,
+        Self::Item: FromSample<f32>,
+        F: FnOnce(Upsampled<Self>) -> S,
+        S: Source<Item = Self::Item>,
+    {
+        oversample::oversampled(self, factor, effect)
+    }
+
+    /// Reads the whole source into memory and scales it so that its loudest sample reaches
+    /// `target_peak`, where `1.0` is full scale.
+    ///
+    /// The source is read when this is called, which can take a while for a long one. Frames
+    /// in another format than the first one are converted to it. A silent source is left as it
+    /// is.
+    ///
+    /// Reading stops when the source ends or after `max_duration`, whichever comes first. A
+    /// source that does not know its [`total_duration`](Source::total_duration) may never end,
+    /// so it is rejected with [`UnboundedSourceError`] unless `max_duration` is given.
+    #[inline]
+    fn normalize(
+        self,
+        target_peak: f32,
+        max_duration: Option<Duration>,
+    ) -> Result<Normalize<Self::Item>, UnboundedSourceError>
+    where
+        Self: Sized,
+    {
+        normalize::normalize(self, target_peak, max_duration)
+    }
+
+    /// Reads the whole source into memory and keeps what is between its first and last
+    /// frames louder than `threshold_db`, in dB relative to full scale, to remove the silence
+    /// around a recording.
+    ///
+    /// A frame is loud when the peak of one of its channels is above the threshold. A source
+    /// that is silent all along becomes empty. Frames in another format than the first one are
+    /// converted to it.
+    ///
+    /// Reading stops when the source ends or after `max_duration`, whichever comes first. A
+    /// source that does not know its [`total_duration`](Source::total_duration) may never end,
+    /// so it is rejected with [`UnboundedSourceError`] unless `max_duration` is given.
+    #[inline]
+    fn trim_silence(
+        self,
+        threshold_db: f32,
+        max_duration: Option<Duration>,
+    ) -> Result<SamplesBuffer<Self::Item>, UnboundedSourceError>
+    where
+        Self: Sized,
+    {
+        trim_silence::trim_silence(self, threshold_db, max_duration)
+    }
+
+    /// Makes the volume go up and down `rate_hz` times per second.
+    ///
+    /// `depth` goes from `0.0`, which leaves the volume unchanged, to `1.0`, where the sound
+    /// fades out completely at the bottom of every cycle. Values outside of this range are
+    /// clamped. The sound starts at full volume.
+    #[inline]
+    fn tremolo(self, rate_hz: f32, depth: f32) -> Tremolo<Self>
+    where
+        Self: Sized,
+    {
+        tremolo::tremolo(self, rate_hz, depth)
+    }
+
+    /// Makes the pitch waver `rate_hz` times per second.
+    ///
+    /// The sound is played through a delay that grows from zero to `depth` and back on every
+    /// cycle, which bends the pitch up and down. A `depth` of a few milliseconds gives a usual
+    /// vibrato, longer ones sound more and more out of tune. The sound comes out late by up to
+    /// `depth`, and the end of the source within that delay is not played.
+    #[inline]
+    fn vibrato(self, rate_hz: f32, depth: Duration) -> Vibrato<Self>
+    where
+        Self: Sized,
+        Self::Item: FromSample<f32>,
+    {
+        vibrato::vibrato(self, rate_hz, depth)
+    }
+
+    /// Thickens the sound with `voices` copies of it, each played through its own delay that
+    /// keeps changing, as if several players played the same part.
+    ///
+    /// The delay of every voice goes from 15 ms to 15 ms plus `depth` and back `rate_hz` times
+    /// per second, the voices being spread evenly over that cycle. Usual settings are a
+    /// `rate_hz` between 0.1 and 3, a `depth` of 1 to 10 ms and 1 to 4 voices; more depth
+    /// sounds out of tune. The copies and the dry sound are mixed at the same volume, so that
+    /// the loudness stays about the same.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `voices` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// let thick = SineWave::new(440.0).chorus(0.8, Duration::from_millis(5), 3);
+    /// ```
+    #[inline]
+    fn chorus(self, rate_hz: f32, depth: Duration, voices: u16) -> Chorus<Self>
+    where
+        Self: Sized,
+        Self::Item: FromSample<f32>,
+    {
+        chorus::chorus(self, rate_hz, depth, voices)
+    }
+
+    /// Sums the sound with a copy of itself played through a delay that goes from nothing to
+    /// `depth` and back `rate_hz` times per second.
+    ///
+    /// The copy cancels out the frequencies whose half period fits in the delay an odd number
+    /// of times. These notches sweep up and down the spectrum with the delay, which gives the
+    /// well known jet plane sound. Usual settings are a `rate_hz` between 0.05 and 1 and a
+    /// `depth` of 1 to 10 ms. `feedback` sends part of the delayed sound back into the delay,
+    /// which makes the effect more pronounced: positive and negative values ring at different
+    /// frequencies, and values close to `1.0` or `-1.0` get loud.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `feedback` is not strictly between `-1.0` and `1.0`.
+    #[inline]
+    fn flanger(self, rate_hz: f32, depth: Duration, feedback: f32) -> Flanger<Self>
+    where
+        Self: Sized,
+        Self::Item: FromSample<f32>,
+    {
+        flanger::flanger(self, rate_hz, depth, feedback)
+    }
+
     /// Applies automatic gain control to the sound.
     ///
     /// Automatic Gain Control (AGC) adjusts the amplitude of the audio signal
@@ -343,6 +738,41 @@ where
         )
     }
 
+    /// Keeps the level of the source near `target_db` dBFS RMS, raising quiet material by up
+    /// to `max_gain_db` and lowering loud material, for content such as podcasts whose level
+    /// varies.
+    ///
+    /// The level is averaged over 100 ms. The gain goes down within about `attack` when the
+    /// source gets louder and back up within about `release` when it gets quieter, and is the
+    /// same for all the channels. Unlike
+    /// [`automatic_gain_control`](Source::automatic_gain_control), the settings are in dB and
+    /// durations, and the gain is held during silence: while the source stays below the gate,
+    /// -50 dBFS unless changed with [`Leveler::set_gate_db`], the noise floor of a pause is not
+    /// pumped up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// let speech = SineWave::new(440.0).amplify(0.05);
+    /// let leveled = speech.agc(-18.0, 12.0, Duration::from_millis(50), Duration::from_secs(1));
+    /// ```
+    #[inline]
+    fn agc(
+        self,
+        target_db: f32,
+        max_gain_db: f32,
+        attack: Duration,
+        release: Duration,
+    ) -> Leveler<Self>
+    where
+        Self: Sized,
+    {
+        leveler::leveler(self, target_db, max_gain_db, attack, release)
+    }
+
     /// Mixes this sound fading out with another sound fading in for the given duration.
     ///
     /// Only the crossfaded portion (beginning of self, beginning of other) is returned.
@@ -353,7 +783,21 @@ where
         Self::Item: FromSample<S::Item>,
         <S as Iterator>::Item: Sample,
     {
-        crossfade::crossfade(self, other, duration)
+        crossfade::take_crossfade(self, other, duration)
+    }
+
+    /// Crossfades over the jump every time the source seeks, which would otherwise click.
+    ///
+    /// Before each seek, `fade` of the audio that would have been played next is read from the
+    /// source. It fades out while the audio after the seek fades in, with a Hann window. A few
+    /// milliseconds are enough. The source still jumps right away when the seek fails, the audio
+    /// read ahead is then played as is.
+    #[inline]
+    fn declick(self, fade: Duration) -> Declick<Self>
+    where
+        Self: Sized,
+    {
+        declick::declick(self, fade)
     }
 
     /// Fades in the sound.
@@ -393,6 +837,29 @@ where
         linear_ramp::linear_gain_ramp(self, duration, start_value, end_value, clamp_end)
     }
 
+    /// Moves the volume linearly from 1.0 to `target` over `duration`, then holds it.
+    ///
+    /// The gain changes once per frame, so all the channels of a frame are played at the same
+    /// volume. Use [`VolumeRamp::set_target`] to start a new ramp from the current volume,
+    /// for example through [`periodic_access`](Source::periodic_access).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// // Duck the music to 20% over a quarter of a second.
+    /// let music = SineWave::new(440.0).volume_ramp(0.2, Duration::from_millis(250));
+    /// ```
+    #[inline]
+    fn volume_ramp(self, target: f32, duration: Duration) -> VolumeRamp<Self>
+    where
+        Self: Sized,
+    {
+        volume_ramp::volume_ramp(self, target, duration)
+    }
+
     /// Calls the `access` closure on `Self` the first time the source is iterated and every
     /// time `period` elapses.
     ///
@@ -412,6 +879,152 @@ where
         periodic::periodic(self, period, access)
     }
 
+    /// Sends the index of each of the `markers` over `sender` when playback reaches its time,
+    /// for example to start something in a game on a beat of the music. The sound is played
+    /// unchanged.
+    ///
+    /// A marker is sent as the first sample of the frame at or after its time is read, and
+    /// the time is counted from the samples played and the sample rate. The markers are sent
+    /// in the order of their times, each of them once, however many fall in the same frame.
+    /// Those that are jumped over by [`try_seek`](Source::try_seek) are not sent, and seeking
+    /// back does not send the markers again. Nothing happens if the receiver is gone.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use std::time::Duration;
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let beats = [Duration::from_millis(500), Duration::from_secs(1)];
+    /// let music = SineWave::new(440.0).markers(&beats, sender);
+    /// // receiver.recv() returns 0, then 1, while the music plays.
+    /// ```
+    #[inline]
+    fn markers(self, markers: &[Duration], sender: Sender<usize>) -> Markers<Self>
+    where
+        Self: Sized,
+    {
+        markers::markers(self, markers, sender)
+    }
+
+    /// Calls `f` on every sample and plays what it returns, like [`Iterator::map`] does but
+    /// keeping the channels, sample rate and duration of the source.
+    ///
+    /// The closure runs on the audio thread, once per sample, so it must be quick and should
+    /// not lock or allocate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// // Crude hard clipping.
+    /// let clipped = SineWave::new(440.0).map_samples(|sample| sample.clamp(-0.5, 0.5));
+    /// ```
+    #[inline]
+    fn map_samples<F>(self, f: F) -> MapSamples<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> Self::Item,
+    {
+        map_samples::map_samples(self, f)
+    }
+
+    /// Calls `f` on every sample as it is played, and plays it unchanged, like
+    /// [`Iterator::inspect`] does but keeping the channels, sample rate and duration of the
+    /// source.
+    ///
+    /// This is meant for debugging, for example to count the samples read at some point of a
+    /// chain of sources, or to print them. The closure runs on the audio thread.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// let mut peak = 0.0f32;
+    /// let watched = SineWave::new(440.0).inspect_samples(move |sample| {
+    ///     peak = peak.max(sample.abs());
+    /// });
+    /// ```
+    #[inline]
+    fn inspect_samples<F>(self, f: F) -> InspectSamples<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&Self::Item),
+    {
+        inspect_samples::inspect_samples(self, f)
+    }
+
+    /// Calls `f` on every frame, with one sample per channel, and plays what it leaves in the
+    /// frame according to the [`FrameAction`] it returns.
+    ///
+    /// The closure can change the samples, clear the frame or add whole frames after it, for
+    /// example to mute some frames or to control the rate frame by frame. An incomplete frame
+    /// at the end of the source is not played. The duration of the result is not known.
+    ///
+    /// The closure runs on the audio thread. The same `Vec` is passed to it for every frame
+    /// so no allocation is needed once it is big enough: keep it, do not replace it with a new
+    /// one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::source::{FrameAction, SineWave, Source};
+    ///
+    /// // Mutes every other frame.
+    /// let mut odd = false;
+    /// let gated = SineWave::new(440.0).process_frames(move |frame| {
+    ///     odd = !odd;
+    ///     if odd {
+    ///         frame.iter_mut().for_each(|sample| *sample = 0.0);
+    ///     }
+    ///     FrameAction::Emit
+    /// });
+    /// ```
+    #[inline]
+    fn process_frames<F>(self, f: F) -> ProcessFrames<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&mut Vec<Self::Item>) -> FrameAction,
+    {
+        process_frames::process_frames(self, f)
+    }
+
+    /// Turns the source into an iterator over whole [`Frame`]s, one sample per channel,
+    /// instead of interleaved samples.
+    ///
+    /// Each frame has as many channels as the source has at that point. If the source ends
+    /// in the middle of a frame, that last frame is completed with silence. The sample rate
+    /// and the duration are still available on the iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the source has more than [`Frame::MAX_CHANNELS`] channels.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::buffer::SamplesBuffer;
+    /// use rodio::Source;
+    ///
+    /// let source = SamplesBuffer::new(2, 44100, vec![0.5f32, -0.5, 0.25, -0.25]);
+    /// for frame in source.frames() {
+    ///     let (left, right) = (frame[0], frame[1]);
+    ///     assert_eq!(left, -right);
+    /// }
+    /// ```
+    #[inline]
+    fn frames(self) -> Frames<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        frames::frames(self)
+    }
+
     /// Changes the play speed of the sound. Does not adjust the samples, only the playback speed.
     ///
     /// # Note:
@@ -434,6 +1047,75 @@ where
         speed::speed(self, ratio)
     }
 
+    /// Plays the sound at a speed that can be changed while it plays, by resampling it.
+    ///
+    /// Like [`speed`](Source::speed), a ratio of 2.0 plays twice as fast and an octave
+    /// higher, but the sample rate stays the same and the ratio can be changed from any thread
+    /// through [`VariableSpeed::controller`]. It is read once per frame, so bends and sweeps
+    /// stay smooth, and any ratio above 0 works.
+    ///
+    /// The frames in between are interpolated with a cubic Hermite spline over four input
+    /// frames. Nothing filters the input first, so sped up sounds alias above
+    /// `sample_rate / (2 * ratio)`. For a fixed conversion between sample rates prefer
+    /// [`UniformSourceIterator`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` is not a positive number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// let source = SineWave::new(440.0).variable_speed(1.0);
+    /// let controller = source.controller();
+    /// // a semitone up
+    /// controller.set_ratio(2f32.powf(1.0 / 12.0));
+    /// ```
+    #[inline]
+    fn variable_speed(self, ratio: f32) -> VariableSpeed<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample + FromSample<f32>,
+    {
+        variable_speed::variable_speed(self, ratio)
+    }
+
+    /// Changes the duration of the sound by `factor` without changing its pitch.
+    ///
+    /// A factor of 2.0 makes the sound last twice as long, 0.5 makes it twice as short.
+    /// Unlike [`speed`](Source::speed) the frequencies stay the same. This works best between
+    /// 0.5 and 2.0, further than that the sound gets noticeably rough or echoey.
+    ///
+    /// See [`Stretch`] for details
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not positive.
+    #[inline]
+    fn stretch(self, factor: f32) -> Stretch<Self>
+    where
+        Self: Sized,
+        Self::Item: FromSample<f32>,
+    {
+        stretch::stretch(self, factor)
+    }
+
+    /// Transposes the sound by `semitones` without changing its duration.
+    ///
+    /// `12.0` raises the pitch by an octave, `-12.0` lowers it by an octave. The sound is
+    /// [stretched](Source::stretch) and then resampled, so the same range applies: it
+    /// works best within an octave either way.
+    #[inline]
+    fn pitch_shift(self, semitones: f32) -> PitchShift<Self>
+    where
+        Self: Sized,
+        Self::Item: FromSample<f32>,
+    {
+        pitch_shift::pitch_shift(self, semitones)
+    }
+
     /// Adds a basic reverb effect.
     ///
     /// This function requires the source to implement `Clone`. This can be done by using
@@ -456,6 +1138,24 @@ where
     }
 
     /// Converts the samples of this source to another type.
+    ///
+    /// Each sample is converted on its own, the format of the source is unchanged. This lets
+    /// a chain switch types on the way, for example to process the `i16` samples of a decoder
+    /// as `f32` and go back to `i16` afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::buffer::SamplesBuffer;
+    /// use rodio::Source;
+    ///
+    /// let decoded = SamplesBuffer::new(2, 44100, vec![16384i16, -8192]);
+    /// let processed = decoded
+    ///     .convert_samples::<f32>()
+    ///     .map_samples(|sample| sample * 0.5)
+    ///     .convert_samples::<i16>();
+    /// assert_eq!(processed.collect::<Vec<_>>(), vec![8192, -4096]);
+    /// ```
     #[inline]
     fn convert_samples<D>(self) -> SamplesConverter<Self, D>
     where
@@ -465,6 +1165,248 @@ where
         SamplesConverter::new(self)
     }
 
+    /// Converts the samples of this source to another type, applying triangular dither of one
+    /// least significant bit of the target type before rounding.
+    ///
+    /// The dither noise comes from a pseudo random generator initialized with `seed`, so the
+    /// same source and seed always produce the same output.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::Source;
+    /// # let source = rodio::source::SineWave::new(440.0);
+    /// // Reduce the sine wave to 16 bits without truncation distortion.
+    /// let source = source.dither::<i16>(0);
+    /// ```
+    #[inline]
+    fn dither<D>(self, seed: u64) -> Dither<Self, D>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+        D: Sample + FromSample<f32>,
+    {
+        dither::dither(self, seed, DitherKind::Tpdf)
+    }
+
+    /// Converts the samples of this source to another type like [`dither`](Source::dither),
+    /// with the kind of dither given.
+    ///
+    /// [`DitherKind::NoiseShaped`] moves the rounding noise towards the highest frequencies,
+    /// where it is harder to hear, which suits the final conversion of high quality output.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::source::{DitherKind, SineWave, Source};
+    ///
+    /// let source = SineWave::new(440.0).dither_with_kind::<i16>(0, DitherKind::NoiseShaped);
+    /// ```
+    #[inline]
+    fn dither_with_kind<D>(self, seed: u64, kind: DitherKind) -> Dither<Self, D>
+    where
+        Self: Sized,
+        D: Sample + FromSample<f32>,
+    {
+        dither::dither(self, seed, kind)
+    }
+
+    /// Mixes the channels of this source down to stereo.
+    ///
+    /// The channels are weighted according to the speaker `layout` says they belong to. The
+    /// center and surround channels are attenuated by 3 dB and the low frequency effects
+    /// channel is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channels count of this source does not match `layout`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::buffer::SamplesBuffer;
+    /// use rodio::source::{ChannelLayout, Source};
+    ///
+    /// let surround = SamplesBuffer::new(6, 48000, vec![0.0f32; 6]);
+    /// let stereo = surround.downmix_to_stereo(ChannelLayout::Surround5_1);
+    /// ```
+    #[inline]
+    fn downmix_to_stereo(self, layout: ChannelLayout) -> Downmix<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        downmix::downmix_to_stereo(self, layout)
+    }
+
+    /// Mixes the channels of this source down to mono.
+    ///
+    /// This is the average of the left and right channels given by
+    /// [`downmix_to_stereo`](Source::downmix_to_stereo).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channels count of this source does not match `layout`.
+    #[inline]
+    fn downmix_to_mono(self, layout: ChannelLayout) -> Downmix<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        downmix::downmix_to_mono(self, layout)
+    }
+
+    /// Spreads the channels of this source over the speakers of `layout`.
+    ///
+    /// The layout of this source is deduced from its channels count. Channels that exist in
+    /// both layouts are copied as is, the others are filled from the nearest channels:
+    ///
+    /// - Mono to stereo plays the sound on both sides at -3 dB, which keeps the loudness the
+    ///   same. Mono to quad plays it on all four speakers at -6 dB. Mono to surround plays it
+    ///   on the center speaker.
+    /// - Stereo to quad or 5.1 plays each side on its front and rear speakers at -3 dB. Stereo
+    ///   to 7.1 plays it on the front, side and back speakers at -4.8 dB.
+    /// - Quad to surround moves the back channels to the side ones for 5.1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layout` has fewer channels than this source, or if there is no known layout
+    /// for the channels count of this source.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::source::{ChannelLayout, SineWave, Source};
+    ///
+    /// let source = SineWave::new(440.0).upmix(ChannelLayout::Stereo);
+    /// assert_eq!(source.channels(), 2);
+    /// ```
+    #[inline]
+    fn upmix(self, layout: ChannelLayout) -> Upmix<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        upmix::upmix(self, layout)
+    }
+
+    /// Maps the channels of this source to new channels through a matrix of gains.
+    ///
+    /// `matrix` has a row for every output channel, holding the gain of each input channel in
+    /// it. The output has as many channels as `matrix` has rows. Every frame of this source is
+    /// turned into one output frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `matrix` is empty, or if a row does not have one gain for every channel of
+    /// this source.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// // sends the sine wave to the first and third outputs, quieter on the third one
+    /// let source = SineWave::new(440.0).channel_matrix(vec![vec![1.0], vec![0.0], vec![0.5]]);
+    /// assert_eq!(source.channels(), 3);
+    /// ```
+    #[inline]
+    fn channel_matrix(self, matrix: Vec<Vec<f32>>) -> ChannelMatrix<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        channel_matrix::channel_matrix(self, matrix)
+    }
+
+    /// Reorders the channels of this source, without changing their volume.
+    ///
+    /// `map` has an entry for every output channel, holding the index of the channel of this
+    /// source it plays. A channel can be played more than once or not at all. The output has
+    /// as many channels as `map` has entries. Every frame of this source is turned into one
+    /// output frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `map` is empty, or if it holds an index that is not a channel of this
+    /// source.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::buffer::SamplesBuffer;
+    /// use rodio::source::Source;
+    ///
+    /// // swaps the side channels of a 5.1 source
+    /// let surround = SamplesBuffer::new(6, 48000, vec![0.0f32; 6]);
+    /// let fixed = surround.remap_channels(&[0, 1, 2, 3, 5, 4]);
+    /// ```
+    #[inline]
+    fn remap_channels(self, map: &[usize]) -> RemapChannels<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        remap_channels::remap_channels(self, map)
+    }
+
+    /// Narrows or widens the stereo image of this source.
+    ///
+    /// Every frame is split into its mid `(L + R) / 2` and side `(L - R) / 2`, the side is
+    /// multiplied by `width` and the frame is put back together. A `width` of `1.0` leaves the
+    /// sound unchanged, `0.0` turns it into mono and values above `1.0` widen it. Widening can
+    /// push the samples past full scale.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this source does not have two channels.
+    #[inline]
+    fn stereo_width(self, width: f32) -> StereoWidth<Self>
+    where
+        Self: Sized,
+        Self::Item: FromSample<f32>,
+    {
+        stereo_width::stereo_width(self, width)
+    }
+
+    /// Turns down one channel of this stereo source, like the balance knob of an amplifier.
+    ///
+    /// A negative `balance` turns the right channel down and a positive one the left channel,
+    /// `-1.0` and `1.0` mute it and `0.0` leaves the sound unchanged. The other channel is
+    /// left as it is, so unlike panning a centered sound is not moved with a pan law.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this source does not have two channels, or if `balance` is not between `-1.0`
+    /// and `1.0`.
+    #[inline]
+    fn balance(self, balance: f32) -> Balance<Self>
+    where
+        Self: Sized,
+    {
+        balance::balance(self, balance)
+    }
+
+    /// Turns this mono source into stereo by playing it on both channels, `side` being
+    /// `delay` later than the other one.
+    ///
+    /// This is the Haas or precedence effect: with a delay between about 1 and 35 ms the ear
+    /// still hears a single sound, coming from the side that plays first, but wider than the
+    /// mono source. Longer delays are heard as an echo. The sound lasts `delay` longer, while
+    /// the lagging channel finishes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this source does not have one channel.
+    #[inline]
+    fn haas(self, delay: Duration, side: Side) -> Haas<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        haas::haas(self, delay, side)
+    }
+
     /// Makes the sound pausable.
     // TODO: add example
     #[inline]
@@ -512,6 +1454,35 @@ where
         position::track_position(self)
     }
 
+    /// Measures the integrated loudness of the sound as it plays, following ITU-R BS.1770,
+    /// and leaves the sound untouched.
+    ///
+    /// The samples are K-weighted and their power is measured over 400 ms blocks. Blocks
+    /// quieter than -70 LUFS, or more than 10 LU below the loudness of the other blocks, are
+    /// ignored. Once a finite source has played to its end, the measure is the integrated
+    /// loudness of the whole source.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::buffer::SamplesBuffer;
+    /// use rodio::source::Source;
+    ///
+    /// let samples: Vec<f32> = (0..48000).map(|i| (i as f32 / 48.0 * 6.283).sin()).collect();
+    /// let meter = SamplesBuffer::new(1, 48000, samples).measure_lufs();
+    /// let loudness = meter.loudness();
+    /// meter.for_each(drop);
+    /// let lufs = loudness.integrated_lufs().unwrap();
+    /// assert!((lufs + 3.0).abs() < 0.1);
+    /// ```
+    #[inline]
+    fn measure_lufs(self) -> LoudnessMeter<Self>
+    where
+        Self: Sized,
+    {
+        loudness::loudness_meter(self)
+    }
+
     /// Applies a low-pass filter to the source.
     /// **Warning**: Probably buggy.
     #[inline]
@@ -553,9 +1524,153 @@ where
         blt::high_pass_with_q(self, freq, q)
     }
 
-    // There is no `can_seek()` method as it is impossible to use correctly. Between
-    // checking if a source supports seeking and actually seeking the sink can
-    // switch to a new source.
+    /// Applies a low-pass or high-pass filter whose cutoff moves from `start_hz` to `end_hz`
+    /// over `duration`, then stays at `end_hz`. Typically used for risers and transitions.
+    ///
+    /// The cutoff moves evenly in pitch and is updated every 64 frames. Each channel is
+    /// filtered on its own, and the state of the filter is kept when the cutoff changes so
+    /// that the sweep does not click.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start_hz` or `end_hz` is 0.
+    #[inline]
+    fn filter_sweep(
+        self,
+        start_hz: u32,
+        end_hz: u32,
+        duration: Duration,
+        kind: FilterKind,
+    ) -> FilterSweep<Self>
+    where
+        Self: Sized,
+        Self: Source<Item = f32>,
+    {
+        filter_sweep::filter_sweep(self, start_hz, end_hz, duration, kind)
+    }
+
+    /// Turns the lows, mids and highs of this source up or down, like the tone controls of a
+    /// media player. The gains are in decibels, `0.0` leaving a band unchanged.
+    ///
+    /// The lows are shelved below 250 Hz, the mids are centered on 1 kHz and the highs are
+    /// shelved above 4 kHz. The gains can be changed while the source plays with an
+    /// [`EqualizerController`], and move to their new value over a few milliseconds so that
+    /// the change does not click.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// let equalizer = SineWave::new(440.0).equalizer(0.0, 0.0, 0.0);
+    /// let controller = equalizer.controller();
+    /// // later, from another thread
+    /// controller.set_eq(3.0, 0.0, -6.0);
+    /// ```
+    #[inline]
+    fn equalizer(self, low_db: f32, mid_db: f32, high_db: f32) -> Equalizer<Self>
+    where
+        Self: Sized,
+        Self: Source<Item = f32>,
+    {
+        equalizer::equalizer(self, low_db, mid_db, high_db)
+    }
+
+    /// Removes the DC offset of the source, which wastes headroom and causes clicks when the
+    /// sound starts or stops.
+    ///
+    /// This is a one-pole high-pass filter at 10 Hz. The offset fades out with a time
+    /// constant of about 16 ms. It is cheap enough to be left in the chain permanently.
+    #[inline]
+    fn remove_dc(self) -> RemoveDc<Self>
+    where
+        Self: Sized,
+        Self::Item: FromSample<f32>,
+    {
+        remove_dc::remove_dc(self)
+    }
+
+    /// Returns whether [`try_seek`](Source::try_seek) can move this source, without seeking.
+    ///
+    /// This is meant for things like graying out a scrubber. It is only a hint: the answer can
+    /// change once a queue or a mixer moves on to other sources, and a seek that is supported
+    /// can still fail, so the result of `try_seek` must be handled anyway.
+    ///
+    /// `false`, the default, means that seeking returns [`SeekError::NotSupported`]. Sources
+    /// that seek their inner source return the answer of that one.
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    /// Returns how far the source has played, from its start, or `None` if it does not keep
+    /// track.
+    ///
+    /// [`SamplesBuffer`](crate::buffer::SamplesBuffer) and the WAV and Symphonia decoders
+    /// know their position, sources that play their input sample by sample, like
+    /// [`amplify`](Source::amplify) or [`pausable`](Source::pausable), pass it on, and
+    /// [`track_position`](Source::track_position) adds one to any source. It is what
+    /// [`try_seek_relative`](Source::try_seek_relative) starts from.
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Attempts to seek `delta` forward or back from the
+    /// [`playback_pos`](Source::playback_pos), for buttons like "skip 10 seconds".
+    ///
+    /// Seeking back before the start lands at the start, and seeking forward past the end
+    /// saturates like [`try_seek`](Source::try_seek).
+    ///
+    /// # Errors
+    /// Returns [`SeekError::NotSupported`] if the source does not know its position, and
+    /// any error of `try_seek`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::buffer::SamplesBuffer;
+    /// use rodio::source::{SeekDirection, Source};
+    /// use std::time::Duration;
+    ///
+    /// let mut source = SamplesBuffer::new(1, 10, vec![0.0f32; 100]);
+    /// source.try_seek_relative(Duration::from_secs(4), SeekDirection::Forward).unwrap();
+    /// source.try_seek_relative(Duration::from_secs(1), SeekDirection::Backward).unwrap();
+    /// assert_eq!(source.playback_pos(), Some(Duration::from_secs(3)));
+    /// ```
+    fn try_seek_relative(
+        &mut self,
+        delta: Duration,
+        direction: SeekDirection,
+    ) -> Result<(), SeekError> {
+        let pos = self.playback_pos().ok_or(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })?;
+        let pos = match direction {
+            SeekDirection::Forward => pos.saturating_add(delta),
+            SeekDirection::Backward => pos.saturating_sub(delta),
+        };
+        self.try_seek(pos)
+    }
+
+    /// Attempts to seek to the sample at index `n`, counting the samples of every channel like
+    /// [`samples_remaining`](Source::samples_remaining) does.
+    ///
+    /// The index is rounded down to the start of its frame. By default this seeks to the
+    /// time of that frame at the current sample rate, which lands on it unless the source
+    /// changes format before it. [`SamplesBuffer`](crate::buffer::SamplesBuffer) jumps to
+    /// the sample exactly. Past the end, the seek saturates like
+    /// [`try_seek`](Source::try_seek).
+    ///
+    /// # Errors
+    /// The same as [`try_seek`](Source::try_seek).
+    fn try_seek_samples(&mut self, n: u64) -> Result<(), SeekError> {
+        let frame = n / self.channels().max(1) as u64;
+        // Rounded up, a time just before the frame would land on the previous one.
+        let nanos = (frame as u128 * 1_000_000_000).div_ceil(self.sample_rate().max(1) as u128);
+        let pos = u64::try_from(nanos).map_or(Duration::MAX, Duration::from_nanos);
+        self.try_seek(pos)
+    }
 
     /// Attempts to seek to a given position in the current source.
     ///
@@ -564,6 +1679,11 @@ where
     /// of 42 seconds calling `try_seek()` with 60 seconds as argument will seek to
     /// 42 seconds.
     ///
+    /// A seek always lands at the start of a frame: the next sample is for the first channel,
+    /// even if the source was in the middle of a frame. A position inside a frame is rounded
+    /// down to the start of that frame. Sources that process whole frames rely on this, and
+    /// implementations must keep it.
+    ///
     /// # Errors
     /// This function will return [`SeekError::NotSupported`] if one of the underlying
     /// sources does not support seeking.
@@ -579,6 +1699,25 @@ where
             underlying_source: std::any::type_name::<Self>(),
         })
     }
+
+    /// Returns the native format of the samples.
+    ///
+    /// By default this is the format of `Self::Item`. Decoders return the format stored in the
+    /// file instead, which lets an output that supports it pick a better conversion than going
+    /// through the type of the samples.
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        <Self::Item as Sample>::FORMAT
+    }
+}
+
+/// Which way [`Source::try_seek_relative`] moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeekDirection {
+    /// Towards the end of the source.
+    Forward,
+    /// Towards the start of the source.
+    Backward,
 }
 
 // We might add decoders requiring new error types, without non_exhaustive
@@ -680,10 +1819,35 @@ macro_rules! source_pointer_impl {
                 (**self).total_duration()
             }
 
+            #[inline]
+            fn samples_remaining(&self) -> Option<usize> {
+                (**self).samples_remaining()
+            }
+
+            #[inline]
+            fn is_seekable(&self) -> bool {
+                (**self).is_seekable()
+            }
+
+            #[inline]
+            fn playback_pos(&self) -> Option<Duration> {
+                (**self).playback_pos()
+            }
+
             #[inline]
             fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
                 (**self).try_seek(pos)
             }
+
+            #[inline]
+            fn try_seek_samples(&mut self, n: u64) -> Result<(), SeekError> {
+                (**self).try_seek_samples(n)
+            }
+
+            #[inline]
+            fn sample_format(&self) -> SampleFormat {
+                (**self).sample_format()
+            }
         }
     };
 }
@@ -694,4 +1858,32 @@ source_pointer_impl!(<S> Source for Box<dyn Source<Item = S> + Send> where S: Sa
 
 source_pointer_impl!(<S> Source for Box<dyn Source<Item = S> + Send + Sync> where S: Sample,);
 
-source_pointer_impl!(<'a, S, C> Source for &'a mut C where S: Sample, C: Source<Item = S>,);
+source_pointer_impl!(<'a, S, C> Source for &'a mut C where S: Sample, C: Source<Item = S> + ?Sized,);
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    // The adapters that only touch the samples play them in the format of their input.
+    #[test]
+    fn sample_adapters_keep_the_format() {
+        let source = || SamplesBuffer::new(2, 44100, vec![1i16, -2, 3, -4]);
+        let duration = source().total_duration();
+        let assert_format = |source: &dyn Source<Item = _>, seekable| {
+            assert_eq!(source.channels(), 2);
+            assert_eq!(source.sample_rate(), 44100);
+            assert_eq!(source.total_duration(), duration);
+            assert_eq!(source.is_seekable(), seekable);
+        };
+
+        assert_format(&source().map_samples(|sample| sample), true);
+        assert_format(&source().inspect_samples(|_| ()), true);
+        assert_format(&source().oversampled(2, |source| source), false);
+        let converted = source().convert_samples::<f32>();
+        assert_eq!(converted.channels(), 2);
+        assert_eq!(converted.sample_rate(), 44100);
+        assert_eq!(converted.total_duration(), duration);
+        assert!(converted.is_seekable());
+    }
+}
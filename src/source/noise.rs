@@ -78,6 +78,11 @@ impl Source for WhiteNoise {
         None
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
     #[inline]
     fn try_seek(&mut self, _: std::time::Duration) -> Result<(), SeekError> {
         // Does nothing, should do nothing
@@ -150,6 +155,11 @@ impl Source for PinkNoise {
         None
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
     #[inline]
     fn try_seek(&mut self, _: std::time::Duration) -> Result<(), SeekError> {
         // Does nothing, should do nothing
@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use super::SeekError;
+use crate::buffer::{SamplesBuffer, UnboundedSourceError};
+use crate::{Sample, Source};
+
+/// Internal function that builds a `Normalize` object.
+pub fn normalize<I>(
+    input: I,
+    target_peak: f32,
+    max_duration: Option<Duration>,
+) -> Result<Normalize<I::Item>, UnboundedSourceError>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    // Frames with another format are converted, so that the samples can be stored together.
+    let buffer = SamplesBuffer::<I::Item>::from_source(input, max_duration)?;
+    let channels = buffer.channels();
+    let sample_rate = buffer.sample_rate();
+    let samples: Vec<I::Item> = buffer.collect();
+
+    let peak = samples
+        .iter()
+        .fold(0.0f32, |peak, sample| peak.max(sample.to_f32().abs()));
+    // Silence stays silent.
+    let gain = if peak > 0.0 { target_peak / peak } else { 1.0 };
+
+    Ok(Normalize {
+        samples,
+        position: 0,
+        gain,
+        channels,
+        sample_rate,
+    })
+}
+
+/// Source that plays another source, stored in memory, scaled so that its loudest sample
+/// reaches a given peak.
+#[derive(Clone, Debug)]
+pub struct Normalize<S> {
+    samples: Vec<S>,
+    position: usize,
+    gain: f32,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<S> Normalize<S> {
+    /// Returns the gain applied to the samples of the source.
+    #[inline]
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+}
+
+impl<S> Iterator for Normalize<S>
+where
+    S: Sample,
+{
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        let sample = self.samples.get(self.position)?;
+        self.position += 1;
+        Some(sample.amplify(self.gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.samples.len() - self.position;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<S> ExactSizeIterator for Normalize<S> where S: Sample {}
+
+impl<S> Source for Normalize<S>
+where
+    S: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len() - self.position)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let frames = (self.samples.len() / self.channels.max(1) as usize) as u64;
+        Some(Duration::from_nanos(
+            frames * 1_000_000_000 / self.sample_rate as u64,
+        ))
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let frame = pos.as_nanos() * self.sample_rate as u128 / 1_000_000_000;
+        let position = frame as usize * self.channels as usize;
+        self.position = position.min(self.samples.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::{SamplesBuffer, UnboundedSourceError};
+    use crate::source::{SineWave, Source};
+
+    #[test]
+    fn reaches_the_target_peak() {
+        let data = vec![0.1f32, -0.2, 0.5, 0.25, -0.4, 0.0];
+        let normalized = SamplesBuffer::new(2, 44100, data)
+            .normalize(1.0, None)
+            .unwrap();
+        assert_eq!(normalized.gain(), 2.0);
+        assert_eq!(normalized.channels(), 2);
+
+        let output: Vec<f32> = normalized.collect();
+        assert_eq!(output, vec![0.2, -0.4, 1.0, 0.5, -0.8, 0.0]);
+        let peak = output.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert_eq!(peak, 1.0);
+    }
+
+    #[test]
+    fn silence_stays_silent() {
+        let normalized = SamplesBuffer::new(1, 44100, vec![0.0f32; 4])
+            .normalize(1.0, None)
+            .unwrap();
+        assert_eq!(normalized.gain(), 1.0);
+        assert_eq!(normalized.collect::<Vec<_>>(), vec![0.0; 4]);
+    }
+
+    #[test]
+    fn seeks_within_the_buffer() {
+        let mut normalized = SamplesBuffer::new(1, 10, vec![0.5f32, 0.25, -0.25])
+            .normalize(1.0, None)
+            .unwrap();
+        assert_eq!(
+            normalized.total_duration(),
+            Some(Duration::from_millis(300))
+        );
+        normalized.try_seek(Duration::from_millis(100)).unwrap();
+        assert_eq!(normalized.collect::<Vec<_>>(), vec![0.5, -0.5]);
+    }
+
+    #[test]
+    fn rejects_infinite_sources() {
+        let normalized = SineWave::new(440.0).normalize(1.0, None);
+        assert_eq!(normalized.unwrap_err(), UnboundedSourceError);
+    }
+
+    #[test]
+    fn reads_infinite_sources_up_to_the_maximum_duration() {
+        let normalized = SineWave::new(440.0)
+            .amplify(0.5)
+            .normalize(1.0, Some(Duration::from_millis(100)))
+            .unwrap();
+        assert_eq!(
+            normalized.total_duration(),
+            Some(Duration::from_millis(100))
+        );
+        let peak = normalized.fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!((peak - 1.0).abs() < 1e-6);
+    }
+}
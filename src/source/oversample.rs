@@ -0,0 +1,313 @@
+use std::time::Duration;
+
+use cpal::{FromSample, SampleRate};
+
+use crate::conversions::SincSampleRateConverter;
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds an `Oversampled` object.
+pub fn oversampled<I, F, S>(input: I, factor: u32, effect: F) -> Oversampled<S>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+    F: FnOnce(Upsampled<I>) -> S,
+    S: Source<Item = I::Item>,
+{
+    assert!(factor >= 1, "the oversampling factor must be at least 1");
+    let channels = input.channels();
+    let sample_rate = input.sample_rate();
+    let high_rate = sample_rate * factor;
+
+    let upsampled = Upsampled {
+        input: SincSampleRateConverter::new(
+            input,
+            SampleRate(sample_rate),
+            SampleRate(high_rate),
+            channels,
+            FromSample::from_sample_,
+        ),
+        channels,
+        sample_rate: high_rate,
+    };
+    let output = SincSampleRateConverter::new(
+        effect(upsampled),
+        SampleRate(high_rate),
+        SampleRate(sample_rate),
+        channels,
+        FromSample::from_sample_,
+    );
+    Oversampled {
+        output,
+        channels,
+        sample_rate,
+    }
+}
+
+/// The source given to the effect of [`Source::oversampled`]: the original source at a
+/// multiple of its sample rate.
+#[derive(Clone)]
+pub struct Upsampled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: SincSampleRateConverter<I>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<I> Upsampled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        self.input.inner()
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        self.input.inner_mut()
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input.into_inner()
+    }
+}
+
+impl<I> Iterator for Upsampled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Upsampled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.inner().total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.inner().sample_format()
+    }
+}
+
+/// Source that runs an effect at a multiple of the sample rate of another source, made with
+/// [`Source::oversampled`].
+///
+/// The harmonics that a nonlinear effect adds above the original Nyquist frequency are
+/// removed by the filter that brings the effect back to the original sample rate, instead of
+/// folding back as aliasing. The filters look ahead as far as they look back, so the sound is
+/// not delayed, but the start of the inner source is read in advance.
+#[derive(Clone)]
+pub struct Oversampled<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    output: SincSampleRateConverter<S>,
+    // The format of the source when the wrapper was made, kept throughout.
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<S> Oversampled<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    /// Returns a reference to the effect.
+    #[inline]
+    pub fn inner(&self) -> &S {
+        self.output.inner()
+    }
+
+    /// Returns a mutable reference to the effect.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut S {
+        self.output.inner_mut()
+    }
+
+    /// Returns the effect.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.output.into_inner()
+    }
+}
+
+impl<S> Iterator for Oversampled<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    type Item = S::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<S::Item> {
+        self.output.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.output.size_hint()
+    }
+}
+
+impl<S> Source for Oversampled<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.output.inner().total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        // The filters would play what they read before the seek.
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.output.inner().sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::f32::consts::TAU;
+    use std::rc::Rc;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    const RATE: u32 = 48000;
+
+    // A 15 kHz sine, the third harmonic of its saturation is at 45 kHz and folds back to 3 kHz.
+    fn sine() -> SamplesBuffer<f32> {
+        let samples = (0..RATE / 10)
+            .map(|i| 0.8 * (TAU * 15000.0 * i as f32 / RATE as f32).sin())
+            .collect::<Vec<_>>();
+        SamplesBuffer::new(1, RATE, samples)
+    }
+
+    // Amplitude of a frequency over a whole number of its periods.
+    fn amplitude(samples: &[f32], freq: f32) -> f32 {
+        let (sin, cos) = samples
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(s, c), (i, x)| {
+                let phase = TAU * freq * i as f32 / RATE as f32;
+                (s + x * phase.sin(), c + x * phase.cos())
+            });
+        2.0 * (sin * sin + cos * cos).sqrt() / samples.len() as f32
+    }
+
+    #[test]
+    fn runs_the_effect_at_the_higher_rate() {
+        let seen = Rc::new(Cell::new(0));
+        let counter = seen.clone();
+        let oversampled = sine().oversampled(4, |source| {
+            assert_eq!(source.sample_rate(), 4 * RATE);
+            source.inspect_samples(move |_| counter.set(counter.get() + 1))
+        });
+        assert_eq!(oversampled.sample_rate(), RATE);
+
+        assert_eq!(oversampled.count(), RATE as usize / 10);
+        assert_eq!(seen.get(), 4 * RATE as usize / 10);
+    }
+
+    #[test]
+    fn less_aliasing() {
+        let plain: Vec<f32> = sine().saturate(4.0).collect();
+        let oversampled: Vec<f32> = sine()
+            .oversampled(4, |source| source.saturate(4.0))
+            .collect();
+
+        // away from the edges, where the filters start and stop
+        let middle = 480..4320;
+        let plain_alias = amplitude(&plain[middle.clone()], 3000.0);
+        let alias = amplitude(&oversampled[middle.clone()], 3000.0);
+        assert!(plain_alias > 0.1, "{plain_alias}");
+        assert!(alias < plain_alias / 50.0, "{alias} {plain_alias}");
+
+        let tone = amplitude(&oversampled[middle], 15000.0);
+        assert!(tone > 0.8, "{tone}");
+    }
+
+    #[test]
+    fn stereo_keeps_channel_order() {
+        let source = SamplesBuffer::new(2, RATE, [0.5f32, -0.5].repeat(1000));
+        let output: Vec<f32> = source.oversampled(2, |source| source).collect();
+        for frame in output[200..1800].chunks(2) {
+            assert!((frame[0] - 0.5).abs() < 1e-3, "{frame:?}");
+            assert!((frame[1] + 0.5).abs() < 1e-3, "{frame:?}");
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use super::{stretch::stretch, SeekError, Stretch};
+use crate::conversions::SincSampleRateConverter;
+use crate::{Sample, SampleFormat, Source};
+
+/// Internal function that builds a `PitchShift` object.
+pub fn pitch_shift<I>(input: I, semitones: f32) -> PitchShift<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    let ratio = 2f32.powf(semitones / 12.0);
+    let channels = input.channels();
+    let sample_rate = input.sample_rate();
+    let total_duration = input.total_duration();
+
+    // Make the sound longer while keeping its pitch, then play it faster so it gets back to
+    // its original duration at a higher pitch.
+    let stretched = stretch(input, ratio);
+    let faster_rate = ((sample_rate as f32 * ratio).round() as u32).max(1);
+    let input = SincSampleRateConverter::new(
+        stretched,
+        cpal::SampleRate(faster_rate),
+        cpal::SampleRate(sample_rate),
+        channels,
+        <I::Item as FromSample<f32>>::from_sample_,
+    );
+
+    PitchShift {
+        input,
+        semitones,
+        channels,
+        sample_rate,
+        total_duration,
+    }
+}
+
+/// Filter that transposes a source without changing its duration.
+#[derive(Clone)]
+pub struct PitchShift<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    input: SincSampleRateConverter<Stretch<I>>,
+    semitones: f32,
+    channels: u16,
+    sample_rate: u32,
+    total_duration: Option<Duration>,
+}
+
+impl<I> PitchShift<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    /// Returns the number of semitones the sound is transposed by.
+    #[inline]
+    pub fn semitones(&self) -> f32 {
+        self.semitones
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        self.input.inner().inner()
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        self.input.inner_mut().inner_mut()
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input.into_inner().into_inner()
+    }
+}
+
+impl<I> Iterator for PitchShift<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for PitchShift<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.inner().is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        // The stretched sound is longer, by the factor it is stretched by.
+        let stretched = self.input.inner_mut();
+        let pos = pos.mul_f32(stretched.factor());
+        stretched.try_seek(pos)
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.inner().sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    const RATE: u32 = 8000;
+
+    fn sine(frequency: f32) -> SamplesBuffer<f32> {
+        let data = (0..RATE).map(|i| (2.0 * PI * frequency * i as f32 / RATE as f32).sin());
+        SamplesBuffer::new(1, RATE, data.collect::<Vec<_>>())
+    }
+
+    /// Frequency estimated from the zero crossings, skipping the edges.
+    fn frequency(samples: &[f32]) -> f32 {
+        let middle = &samples[samples.len() / 10..samples.len() * 9 / 10];
+        let crossings = middle
+            .windows(2)
+            .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+            .count();
+        crossings as f32 / 2.0 / (middle.len() as f32 / RATE as f32)
+    }
+
+    #[test]
+    fn octave_up() {
+        let shifted = sine(300.0).pitch_shift(12.0);
+        assert_eq!(shifted.total_duration(), Some(Duration::from_secs(1)));
+
+        let output: Vec<f32> = shifted.collect();
+        assert_eq!(output.len(), RATE as usize);
+        let frequency = frequency(&output);
+        assert!((frequency - 600.0).abs() < 600.0 * 0.02, "{}", frequency);
+    }
+
+    #[test]
+    fn fifth_down() {
+        let output: Vec<f32> = sine(600.0).pitch_shift(-7.0).collect();
+        assert_eq!(output.len(), RATE as usize);
+        let expected = 600.0 * 2f32.powf(-7.0 / 12.0);
+        let frequency = frequency(&output);
+        assert!(
+            (frequency - expected).abs() < expected * 0.02,
+            "{}",
+            frequency
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 use super::SeekError;
 
@@ -144,6 +144,22 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    /// The same as [`get_pos`](TrackPosition::get_pos).
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        Some(self.get_pos())
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         let result = self.input.try_seek(pos);
@@ -156,6 +172,11 @@ where
         }
         result
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
 }
 
 #[cfg(test)]
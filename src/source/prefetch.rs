@@ -0,0 +1,311 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::{SeekError, UnderrunPolicy};
+use crate::{Sample, SampleFormat, Source};
+
+/// Internal function that builds a `Prefetch` object.
+pub fn prefetch<I>(mut input: I, capacity: usize, policy: UnderrunPolicy) -> Prefetch<I::Item>
+where
+    I: Source + Send + 'static,
+    I::Item: Sample + Send,
+{
+    let channels = input.channels();
+    let sample_rate = input.sample_rate();
+    let total_duration = input.total_duration();
+    let sample_format = input.sample_format();
+    // Room for at least one frame.
+    let capacity = capacity.max(channels.max(1) as usize);
+
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            samples: VecDeque::with_capacity(capacity),
+            finished: false,
+            dropped: false,
+        }),
+        space: Condvar::new(),
+        underruns: AtomicUsize::new(0),
+    });
+    let worker = shared.clone();
+    thread::spawn(move || {
+        let mut frame = Vec::with_capacity(channels as usize);
+        loop {
+            frame.clear();
+            frame.extend(input.by_ref().take(channels as usize));
+            let mut state = worker.state.lock().unwrap();
+            while !state.dropped && state.samples.len() + frame.len() > capacity {
+                state = worker.space.wait(state).unwrap();
+            }
+            // only whole frames are queued, so the channels never get swapped
+            if state.dropped || frame.len() < channels.max(1) as usize {
+                state.finished = true;
+                return;
+            }
+            state.samples.extend(&frame);
+        }
+    });
+
+    Prefetch {
+        shared,
+        channels,
+        sample_rate,
+        total_duration,
+        sample_format,
+        capacity,
+        policy,
+        current_channel: 0,
+        underrun: false,
+        last_frame: vec![I::Item::zero_value(); channels as usize],
+    }
+}
+
+/// Source that reads another source ahead of playback, on a background thread.
+///
+/// When the background thread falls behind, this never waits for it, what it plays instead
+/// depends on its [`UnderrunPolicy`]. Seeking is not supported.
+pub struct Prefetch<S> {
+    shared: Arc<Shared<S>>,
+    channels: u16,
+    sample_rate: u32,
+    total_duration: Option<Duration>,
+    sample_format: SampleFormat,
+    capacity: usize,
+    policy: UnderrunPolicy,
+    current_channel: u16,
+    // Whether the current frame is not there because the background thread is late.
+    underrun: bool,
+    // The last frame read from the background thread, played again by `UnderrunPolicy::Repeat`.
+    last_frame: Vec<S>,
+}
+
+struct Shared<S> {
+    state: Mutex<State<S>>,
+    // Notified when there is room for more samples or the source is dropped.
+    space: Condvar,
+    underruns: AtomicUsize,
+}
+
+struct State<S> {
+    samples: VecDeque<S>,
+    finished: bool,
+    dropped: bool,
+}
+
+impl<S> Prefetch<S> {
+    /// Returns the number of frames that the background thread did not deliver in time.
+    #[inline]
+    pub fn underruns(&self) -> usize {
+        self.shared.underruns.load(Ordering::Relaxed)
+    }
+}
+
+impl<S> Iterator for Prefetch<S>
+where
+    S: Sample,
+{
+    type Item = S;
+
+    fn next(&mut self) -> Option<S> {
+        let mut state = self.shared.state.lock().unwrap();
+        if self.current_channel == 0 {
+            if state.samples.is_empty() && state.finished {
+                return None;
+            }
+            // The samples of a frame are queued together, if the first one is there so are
+            // the others.
+            self.underrun = state.samples.is_empty();
+            if self.underrun {
+                self.shared.underruns.fetch_add(1, Ordering::Relaxed);
+                if self.policy == UnderrunPolicy::EndStream {
+                    return None;
+                }
+            }
+        }
+        let channel = self.current_channel as usize;
+        self.current_channel = (self.current_channel + 1) % self.channels.max(1);
+
+        if self.underrun {
+            return match self.policy {
+                UnderrunPolicy::Repeat => Some(self.last_frame[channel]),
+                _ => Some(S::zero_value()),
+            };
+        }
+        let sample = state.samples.pop_front();
+        if state.samples.len() < self.capacity / 2 {
+            self.shared.space.notify_one();
+        }
+        if let (Some(sample), Some(last)) = (sample, self.last_frame.get_mut(channel)) {
+            *last = sample;
+        }
+        sample
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<S> Source for Prefetch<S>
+where
+    S: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.sample_format
+    }
+}
+
+impl<S> Drop for Prefetch<S> {
+    fn drop(&mut self) {
+        // stops the background thread
+        self.shared.state.lock().unwrap().dropped = true;
+        self.shared.space.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{Source, UnderrunPolicy};
+
+    // A mono source that waits for each of its samples.
+    struct Gated(Receiver<i16>);
+
+    impl Iterator for Gated {
+        type Item = i16;
+
+        fn next(&mut self) -> Option<i16> {
+            self.0.recv().ok()
+        }
+    }
+
+    impl Source for Gated {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            1
+        }
+
+        fn sample_rate(&self) -> u32 {
+            48000
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    // Plays until something else than silence comes out.
+    fn next_sound(source: &mut impl Source<Item = i16>) -> Option<i16> {
+        loop {
+            match source.next() {
+                Some(0) => thread::sleep(Duration::from_millis(1)),
+                other => return other,
+            }
+        }
+    }
+
+    #[test]
+    fn plays_everything_then_ends() {
+        let data: Vec<i16> = (1..=1000).collect();
+        let source = SamplesBuffer::new(2, 48000, data.clone());
+        let mut prefetch = source.prefetch(64);
+        assert_eq!(prefetch.channels(), 2);
+        assert_eq!(prefetch.sample_rate(), 48000);
+        assert!(prefetch.total_duration().is_some());
+
+        // the silence played while the thread catches up comes in whole frames
+        let played: Vec<i16> = prefetch.by_ref().filter(|&sample| sample != 0).collect();
+        assert_eq!(played, data);
+        assert_eq!(prefetch.next(), None);
+    }
+
+    #[test]
+    fn can_not_seek() {
+        let mut prefetch = SamplesBuffer::new(1, 48000, vec![0i16; 10]).prefetch(4);
+        assert!(prefetch.try_seek(Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn end_stream_returns_none_when_late() {
+        let (tx, rx) = mpsc::channel();
+        let mut prefetch = Gated(rx).prefetch_with_policy(16, UnderrunPolicy::EndStream);
+        assert_eq!(prefetch.next(), None);
+        assert_eq!(prefetch.underruns(), 1);
+
+        tx.send(3).unwrap();
+        let played = loop {
+            if let Some(sample) = prefetch.next() {
+                break sample;
+            }
+            thread::sleep(Duration::from_millis(1));
+        };
+        assert_eq!(played, 3);
+    }
+
+    #[test]
+    fn emit_silence_counts_underruns() {
+        let (tx, rx) = mpsc::channel();
+        let mut prefetch = Gated(rx).prefetch(16);
+        assert_eq!(prefetch.next(), Some(0));
+        assert_eq!(prefetch.next(), Some(0));
+        assert_eq!(prefetch.underruns(), 2);
+
+        tx.send(4).unwrap();
+        assert_eq!(next_sound(&mut prefetch), Some(4));
+        drop(tx);
+        assert_eq!(next_sound(&mut prefetch), None);
+    }
+
+    #[test]
+    fn repeat_plays_the_last_frame_again() {
+        let (tx, rx) = mpsc::channel();
+        let mut prefetch = Gated(rx).prefetch_with_policy(16, UnderrunPolicy::Repeat);
+        // nothing played yet
+        assert_eq!(prefetch.next(), Some(0));
+
+        tx.send(5).unwrap();
+        assert_eq!(next_sound(&mut prefetch), Some(5));
+        let underruns = prefetch.underruns();
+        assert_eq!(prefetch.next(), Some(5));
+        assert_eq!(prefetch.next(), Some(5));
+        assert_eq!(prefetch.underruns(), underruns + 2);
+    }
+}
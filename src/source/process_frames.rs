@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `ProcessFrames` object.
+pub fn process_frames<I, F>(input: I, f: F) -> ProcessFrames<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut Vec<I::Item>) -> FrameAction,
+{
+    let channels = input.channels().max(1) as usize;
+    ProcessFrames {
+        input,
+        f,
+        frame: Vec::with_capacity(channels),
+        frame_pos: 0,
+        stopped: false,
+    }
+}
+
+/// What [`Source::process_frames`] does with a frame once the closure has seen it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameAction {
+    /// Plays the samples left in the frame.
+    Emit,
+    /// Plays nothing for this frame.
+    Drop,
+    /// Plays the samples left in the frame, then ends the source.
+    EmitThenStop,
+}
+
+/// Source that calls a closure on every frame of another source, see
+/// [`Source::process_frames`].
+#[derive(Clone, Debug)]
+pub struct ProcessFrames<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    f: F,
+    // The samples handed to the closure, reused for every frame.
+    frame: Vec<I::Item>,
+    frame_pos: usize,
+    // Set by `FrameAction::EmitThenStop`.
+    stopped: bool,
+}
+
+impl<I, F> ProcessFrames<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut Vec<I::Item>) -> FrameAction,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Reads frames until the closure keeps one, returns false once there are no more.
+    fn next_frame(&mut self) -> bool {
+        let channels = self.input.channels().max(1) as usize;
+        while !self.stopped {
+            self.frame.clear();
+            self.frame_pos = 0;
+            self.frame.extend(self.input.by_ref().take(channels));
+            if self.frame.len() < channels {
+                // an incomplete frame at the end is not played
+                self.frame.clear();
+                return false;
+            }
+
+            match (self.f)(&mut self.frame) {
+                FrameAction::Emit => (),
+                FrameAction::Drop => self.frame.clear(),
+                FrameAction::EmitThenStop => self.stopped = true,
+            }
+            if !self.frame.is_empty() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<I, F> Iterator for ProcessFrames<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut Vec<I::Item>) -> FrameAction,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.frame_pos >= self.frame.len() && !self.next_frame() {
+            return None;
+        }
+        let sample = self.frame[self.frame_pos];
+        self.frame_pos += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // The closure can drop or add frames.
+        (self.frame.len() - self.frame_pos, None)
+    }
+}
+
+impl<I, F> Source for ProcessFrames<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut Vec<I::Item>) -> FrameAction,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // What is left of the current frame is from before the seek.
+        self.frame.clear();
+        self.frame_pos = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::FrameAction;
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn sees_whole_frames() {
+        let source = SamplesBuffer::new(2, 44100, vec![1i16, 2, 3, 4]);
+        let mut frames = Vec::new();
+        let output: Vec<i16> = source
+            .process_frames(|frame| {
+                frames.push(frame.clone());
+                frame.swap(0, 1);
+                FrameAction::Emit
+            })
+            .collect();
+        assert_eq!(output, vec![2, 1, 4, 3]);
+        assert_eq!(frames, vec![vec![1, 2], vec![3, 4]]);
+    }
+
+    #[test]
+    fn drops_and_duplicates_frames() {
+        let source = SamplesBuffer::new(1, 44100, (1i16..=6).collect::<Vec<_>>());
+        let output: Vec<i16> = source
+            .process_frames(|frame| match frame[0] % 3 {
+                0 => FrameAction::Drop,
+                1 => {
+                    frame.push(frame[0]);
+                    FrameAction::Emit
+                }
+                _ => FrameAction::Emit,
+            })
+            .collect();
+        assert_eq!(output, vec![1, 1, 2, 4, 4, 5]);
+    }
+
+    #[test]
+    fn stops_after_the_frame() {
+        let mut processed = SamplesBuffer::new(2, 44100, (1i16..=8).collect::<Vec<_>>())
+            .process_frames(|frame| match frame[0] {
+                3 => FrameAction::EmitThenStop,
+                _ => FrameAction::Emit,
+            });
+        assert_eq!(processed.by_ref().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(processed.next(), None);
+        assert_eq!(processed.inner().size_hint(), (4, Some(4)));
+    }
+
+    #[test]
+    fn seeking_drops_the_current_frame() {
+        let mut processed = SamplesBuffer::new(2, 10, (1i16..=8).collect::<Vec<_>>())
+            .process_frames(|_| FrameAction::Emit);
+        assert_eq!(processed.next(), Some(1));
+        processed.try_seek(Duration::from_millis(200)).unwrap();
+        assert_eq!(processed.collect::<Vec<_>>(), vec![5, 6, 7, 8]);
+    }
+}
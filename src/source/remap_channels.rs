@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `RemapChannels` object.
+pub fn remap_channels<I>(input: I, map: &[usize]) -> RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let in_channels = input.channels() as usize;
+    assert!(!map.is_empty(), "the map needs at least one output channel");
+    if let Some(&channel) = map.iter().find(|&&channel| channel >= in_channels) {
+        panic!(
+            "the map reads input channel {}, but the source only has {} channels",
+            channel, in_channels
+        );
+    }
+
+    let mut remap = RemapChannels {
+        input,
+        map: map.to_vec(),
+        frame: vec![I::Item::zero_value(); in_channels],
+        finished: false,
+        current_channel: 0,
+    };
+    remap.next_frame();
+    remap
+}
+
+/// Reorders, copies or drops the channels of a source, see [`Source::remap_channels`].
+#[derive(Clone, Debug)]
+pub struct RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // For each output channel, the input channel it plays.
+    map: Vec<usize>,
+    // Input frame the output channels are read from.
+    frame: Vec<I::Item>,
+    // Set once the input has run out.
+    finished: bool,
+    // Next output channel to be produced.
+    current_channel: usize,
+}
+
+impl<I> RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    fn next_frame(&mut self) {
+        self.current_channel = 0;
+        for (i, sample) in self.frame.iter_mut().enumerate() {
+            match self.input.next() {
+                Some(s) => *sample = s,
+                // A frame cut short is completed with silence.
+                None if i > 0 => *sample = I::Item::zero_value(),
+                None => {
+                    self.finished = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let sample = self.frame[self.map[self.current_channel]];
+
+        self.current_channel += 1;
+        if self.current_channel >= self.map.len() {
+            self.next_frame();
+        }
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.input.size_hint();
+        let in_channels = self.frame.len();
+        let out_channels = self.map.len();
+        let buffered = if self.finished {
+            0
+        } else {
+            out_channels - self.current_channel
+        };
+        (
+            min / in_channels * out_channels + buffered,
+            max.map(|max| max.div_ceil(in_channels) * out_channels + buffered),
+        )
+    }
+}
+
+impl<I> Source for RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input
+            .current_frame_len()
+            .map(|len| len / self.frame.len() * self.map.len())
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.map.len() as u16
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.finished = false;
+        self.next_frame();
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn reorders_channels() {
+        // the surrounds of a 5.1 frame swapped
+        let source = SamplesBuffer::new(6, 48000, (0..12i16).collect::<Vec<_>>());
+        let output: Vec<i16> = source.remap_channels(&[0, 1, 2, 3, 5, 4]).collect();
+        assert_eq!(output, [0, 1, 2, 3, 5, 4, 6, 7, 8, 9, 11, 10]);
+    }
+
+    #[test]
+    fn copies_and_drops_channels() {
+        let source = SamplesBuffer::new(3, 48000, vec![1i16, 2, 3, 4, 5, 6]);
+        let remapped = source.remap_channels(&[2, 2]);
+        assert_eq!(remapped.channels(), 2);
+        assert_eq!(remapped.size_hint(), (4, Some(4)));
+        assert_eq!(remapped.collect::<Vec<_>>(), [3, 3, 6, 6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "reads input channel 2, but the source only has 2 channels")]
+    fn rejects_missing_channels() {
+        let source = SamplesBuffer::new(2, 48000, vec![0i16; 4]);
+        let _ = source.remap_channels(&[0, 2]);
+    }
+}
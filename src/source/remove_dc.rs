@@ -0,0 +1,205 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Cutoff of the filter, well below what can be heard.
+const CUTOFF_HZ: f32 = 10.0;
+
+/// Internal function that builds a `RemoveDc` object.
+pub fn remove_dc<I>(input: I) -> RemoveDc<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    let mut filter = RemoveDc {
+        input,
+        pole: 0.0,
+        state: Vec::new(),
+        current_channel: 0,
+    };
+    filter.reset();
+    filter
+}
+
+/// Filter that removes the DC offset of a source, with a one-pole high-pass filter.
+///
+/// The cutoff is 10 Hz, so the offset fades out with a time constant of
+/// `1 / (2π × 10 Hz)`, about 16 ms, whatever the sample rate.
+#[derive(Clone, Debug)]
+pub struct RemoveDc<I> {
+    input: I,
+    // Feedback coefficient, derived from the sample rate.
+    pole: f32,
+    // The previous input and output of every channel.
+    state: Vec<(f32, f32)>,
+    current_channel: usize,
+}
+
+impl<I> RemoveDc<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    // Forgets the previous samples, and adapts to the format of the input.
+    fn reset(&mut self) {
+        let sample_rate = self.input.sample_rate() as f32;
+        self.pole = (-TAU * CUTOFF_HZ / sample_rate).exp();
+        self.state.clear();
+        self.state
+            .resize(self.input.channels().max(1) as usize, (0.0, 0.0));
+        self.current_channel = 0;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for RemoveDc<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        // The format can only change between frames.
+        if self.current_channel == 0 && self.state.len() != self.input.channels() as usize {
+            self.reset();
+        }
+
+        let sample = self.input.next()?.to_f32();
+        let (previous_in, previous_out) = &mut self.state[self.current_channel];
+        let output = sample - *previous_in + self.pole * *previous_out;
+        *previous_in = sample;
+        *previous_out = output;
+
+        self.current_channel = (self.current_channel + 1) % self.state.len();
+        Some(I::Item::from_sample_(output))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for RemoveDc<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample + FromSample<f32>,
+{
+}
+
+impl<I> Source for RemoveDc<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.reset();
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    const RATE: u32 = 48000;
+
+    #[test]
+    fn offset_converges_to_zero() {
+        let source = SamplesBuffer::new(1, RATE, vec![0.5f32; RATE as usize]);
+        let output: Vec<f32> = source.remove_dc().collect();
+
+        assert_eq!(output[0], 0.5);
+        // ten time constants of about 16 ms each
+        let settled = &output[RATE as usize * 160 / 1000..];
+        assert!(settled.iter().all(|sample| sample.abs() < 1e-4));
+    }
+
+    #[test]
+    fn channels_are_filtered_separately() {
+        let data: Vec<f32> = [0.25f32, -0.25].repeat(RATE as usize / 2);
+        let output: Vec<f32> = SamplesBuffer::new(2, RATE, data).remove_dc().collect();
+
+        assert_eq!(&output[..2], &[0.25, -0.25]);
+        let end = &output[output.len() - 2..];
+        assert!(end.iter().all(|sample| sample.abs() < 1e-4), "{end:?}");
+    }
+
+    #[test]
+    fn keeps_audible_frequencies() {
+        let tone: Vec<f32> = (0..RATE)
+            .map(|i| (std::f32::consts::TAU * 440.0 * i as f32 / RATE as f32).sin() + 0.2)
+            .collect();
+        let output: Vec<f32> = SamplesBuffer::new(1, RATE, tone).remove_dc().collect();
+
+        let settled = &output[RATE as usize / 2..];
+        let peak = settled
+            .iter()
+            .fold(0.0f32, |max, sample| max.max(sample.abs()));
+        let mean = settled.iter().sum::<f32>() / settled.len() as f32;
+        assert!((peak - 1.0).abs() < 0.01, "{peak}");
+        assert!(mean.abs() < 1e-3, "{mean}");
+    }
+}
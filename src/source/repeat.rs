@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use crate::source::buffered::Buffered;
 
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 use super::SeekError;
 
@@ -87,10 +87,20 @@ where
         None
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.inner.is_seekable()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.inner.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.inner.sample_format()
+    }
 }
 
 impl<I> Clone for Repeat<I>
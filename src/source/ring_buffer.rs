@@ -0,0 +1,459 @@
+use std::cell::UnsafeCell;
+use std::sync::atomic::{self, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use super::{SeekError, UnderrunPolicy};
+use crate::{Sample, Source};
+
+/// Source that plays the samples pushed to it from another thread, such as the audio captured
+/// from a microphone.
+///
+/// The samples are pushed with a [`RingBufferProducer`]. Whenever there is not a whole frame
+/// waiting, the source does not wait for one and counts an underrun, what it plays instead
+/// depends on its [`UnderrunPolicy`]. The source ends once all the producers are dropped and
+/// it has played every whole frame. What is left of a frame the producers did not finish is
+/// dropped.
+pub struct RingBufferSource<S> {
+    reader: RingReader<S>,
+    // Tells whether a producer still exists.
+    producers: Weak<Mutex<RingWriter<S>>>,
+    underruns: Arc<AtomicUsize>,
+    channels: u16,
+    sample_rate: u32,
+    policy: UnderrunPolicy,
+    // The frame being played, kept during an underrun for `UnderrunPolicy::Repeat`.
+    frame: Vec<S>,
+    frame_pos: usize,
+}
+
+/// Pushes samples to a [`RingBufferSource`].
+pub struct RingBufferProducer<S> {
+    // Shared by the clones of the producer, so that they push one at a time.
+    writer: Arc<Mutex<RingWriter<S>>>,
+    underruns: Arc<AtomicUsize>,
+}
+
+/// Builds a ring that holds up to `capacity` samples, and returns the only end that writes
+/// it along with the only end that reads it.
+pub(crate) fn ring<S>(capacity: usize) -> (RingWriter<S>, RingReader<S>)
+where
+    S: Sample,
+{
+    let ring = Arc::new(Ring {
+        slots: (0..=capacity)
+            .map(|_| UnsafeCell::new(S::zero_value()))
+            .collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+    });
+    let writer = RingWriter { ring: ring.clone() };
+    (writer, RingReader { ring })
+}
+
+// The samples go around a buffer allocated once. The writer only writes the free slots and
+// moves `tail`, the reader only reads the filled slots and moves `head`, so neither ever
+// waits for the other.
+struct Ring<S> {
+    // One slot more than the capacity, so that a full ring is told apart from an empty one.
+    slots: Box<[UnsafeCell<S>]>,
+    // Slots of the next sample to read and of the next one to write.
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// SAFETY: there is a single writer and a single reader, see `ring`. A slot is only written
+// while it is free, and only read while it is filled. `head` and `tail` hand the slots over
+// between the two, with release stores and acquire loads.
+unsafe impl<S: Send> Sync for Ring<S> {}
+
+impl<S> Ring<S> {
+    #[inline]
+    fn slot(&self, pos: usize) -> &UnsafeCell<S> {
+        &self.slots[pos % self.slots.len()]
+    }
+
+    // Number of samples between `head` and `tail`.
+    #[inline]
+    fn filled(&self, head: usize, tail: usize) -> usize {
+        (tail + self.slots.len() - head) % self.slots.len()
+    }
+}
+
+/// The end of a [`ring`] that writes it.
+pub(crate) struct RingWriter<S> {
+    ring: Arc<Ring<S>>,
+}
+
+impl<S> RingWriter<S>
+where
+    S: Sample,
+{
+    /// Adds samples at the end of the ring, and returns how many were added. The samples
+    /// that do not fit are dropped.
+    pub(crate) fn push(&mut self, samples: &[S]) -> usize {
+        let ring = &*self.ring;
+        let tail = ring.tail.load(Ordering::Relaxed);
+        let filled = ring.filled(ring.head.load(Ordering::Acquire), tail);
+        let len = samples.len().min(ring.slots.len() - 1 - filled);
+        for (pos, sample) in (tail..).zip(&samples[..len]) {
+            // SAFETY: the reader does not read this slot until `tail` moves past it.
+            unsafe { *ring.slot(pos).get() = *sample };
+        }
+        ring.tail
+            .store((tail + len) % ring.slots.len(), Ordering::Release);
+        len
+    }
+}
+
+/// The end of a [`ring`] that reads it.
+pub(crate) struct RingReader<S> {
+    ring: Arc<Ring<S>>,
+}
+
+impl<S> RingReader<S>
+where
+    S: Sample,
+{
+    /// Returns the number of samples waiting to be read.
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        let ring = &*self.ring;
+        ring.filled(
+            ring.head.load(Ordering::Relaxed),
+            ring.tail.load(Ordering::Acquire),
+        )
+    }
+
+    /// Replaces the content of `frame` with the next `channels` samples, if that many are
+    /// waiting. Returns false and leaves `frame` alone if not.
+    pub(crate) fn pop_frame(&mut self, frame: &mut Vec<S>, channels: usize) -> bool {
+        if self.len() < channels {
+            return false;
+        }
+        let ring = &*self.ring;
+        let head = ring.head.load(Ordering::Relaxed);
+        frame.clear();
+        frame.extend((head..head + channels).map(|pos| {
+            // SAFETY: the writer does not write this slot until `head` moves past it.
+            unsafe { *ring.slot(pos).get() }
+        }));
+        ring.head
+            .store((head + channels) % ring.slots.len(), Ordering::Release);
+        true
+    }
+}
+
+impl<S> RingBufferSource<S>
+where
+    S: Sample,
+{
+    /// Builds a new ring buffer that holds up to `capacity` samples, and returns the producer
+    /// that fills it along with the source that plays it.
+    ///
+    /// The source plays silence during an underrun.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `channels` or `sample_rate` is 0, or if `capacity` is smaller than a frame.
+    pub fn new(
+        capacity: usize,
+        channels: u16,
+        sample_rate: u32,
+    ) -> (RingBufferProducer<S>, RingBufferSource<S>) {
+        RingBufferSource::with_policy(capacity, channels, sample_rate, UnderrunPolicy::EmitSilence)
+    }
+
+    /// Same as [`new`](RingBufferSource::new), but `policy` chooses what the source plays
+    /// during an underrun.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `channels` or `sample_rate` is 0, or if `capacity` is smaller than a frame.
+    pub fn with_policy(
+        capacity: usize,
+        channels: u16,
+        sample_rate: u32,
+        policy: UnderrunPolicy,
+    ) -> (RingBufferProducer<S>, RingBufferSource<S>) {
+        assert!(channels >= 1, "the ring buffer needs at least one channel");
+        assert!(sample_rate >= 1, "the sample rate can not be 0");
+        assert!(
+            capacity >= channels as usize,
+            "the ring buffer must hold at least one frame"
+        );
+
+        let (writer, reader) = ring(capacity);
+        let writer = Arc::new(Mutex::new(writer));
+        let underruns = Arc::new(AtomicUsize::new(0));
+        let source = RingBufferSource {
+            reader,
+            producers: Arc::downgrade(&writer),
+            underruns: underruns.clone(),
+            channels,
+            sample_rate,
+            policy,
+            frame: Vec::with_capacity(channels as usize),
+            frame_pos: 0,
+        };
+        let producer = RingBufferProducer { writer, underruns };
+        (producer, source)
+    }
+
+    /// Returns the number of frames that were not waiting when the source needed them.
+    #[inline]
+    pub fn underruns(&self) -> usize {
+        self.underruns.load(Ordering::Relaxed)
+    }
+
+    fn next_frame(&mut self) -> bool {
+        self.frame_pos = 0;
+        let channels = self.channels as usize;
+
+        if self.reader.pop_frame(&mut self.frame, channels) {
+            return true;
+        }
+        if self.producers.strong_count() == 0 {
+            // The last producer may have pushed right before it was dropped.
+            atomic::fence(Ordering::Acquire);
+            if self.reader.pop_frame(&mut self.frame, channels) {
+                return true;
+            }
+            // Nobody can finish a partial frame anymore, it is dropped.
+            self.frame.clear();
+            return false;
+        }
+
+        self.underruns.fetch_add(1, Ordering::Relaxed);
+        match self.policy {
+            UnderrunPolicy::EndStream => {
+                self.frame.clear();
+                return false;
+            }
+            UnderrunPolicy::EmitSilence => self.frame.clear(),
+            // the frame is empty until one was played
+            UnderrunPolicy::Repeat => (),
+        }
+        self.frame.resize(channels, S::zero_value());
+        true
+    }
+}
+
+impl<S> RingBufferProducer<S>
+where
+    S: Sample,
+{
+    /// Adds samples at the end of the buffer, interleaved like the ones of a [`Source`].
+    ///
+    /// Returns how many samples were added. When the buffer is full, the samples that do not
+    /// fit are dropped. Never waits for the source, only for the clones of this producer that
+    /// are pushing at the same time.
+    pub fn push(&self, samples: &[S]) -> usize {
+        self.writer.lock().unwrap().push(samples)
+    }
+
+    /// Returns the number of frames that were not waiting when the source needed them.
+    #[inline]
+    pub fn underruns(&self) -> usize {
+        self.underruns.load(Ordering::Relaxed)
+    }
+}
+
+impl<S> Clone for RingBufferProducer<S> {
+    fn clone(&self) -> Self {
+        RingBufferProducer {
+            writer: self.writer.clone(),
+            underruns: self.underruns.clone(),
+        }
+    }
+}
+
+impl<S> Iterator for RingBufferSource<S>
+where
+    S: Sample,
+{
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        if self.frame_pos >= self.frame.len() && !self.next_frame() {
+            return None;
+        }
+        let sample = self.frame[self.frame_pos];
+        self.frame_pos += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.frame.len() - self.frame_pos, None)
+    }
+}
+
+impl<S> Source for RingBufferSource<S>
+where
+    S: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::RingBufferSource;
+    use crate::source::UnderrunPolicy;
+
+    #[test]
+    fn plays_pushed_samples() {
+        let (producer, mut source) = RingBufferSource::new(8, 2, 48000);
+        assert_eq!(producer.push(&[1i16, 2, 3, 4]), 4);
+        assert_eq!(source.next(), Some(1));
+        assert_eq!(source.next(), Some(2));
+        producer.push(&[5, 6]);
+        assert_eq!(source.next(), Some(3));
+        assert_eq!(source.next(), Some(4));
+        assert_eq!(source.next(), Some(5));
+        assert_eq!(source.next(), Some(6));
+        assert_eq!(source.underruns(), 0);
+    }
+
+    #[test]
+    fn underrun_plays_silent_frames() {
+        let (producer, mut source) = RingBufferSource::new(8, 2, 48000);
+        // half a frame is not enough
+        producer.push(&[1i16]);
+        assert_eq!(source.next(), Some(0));
+        assert_eq!(source.next(), Some(0));
+        assert_eq!(producer.underruns(), 1);
+
+        producer.push(&[2]);
+        assert_eq!(source.next(), Some(1));
+        assert_eq!(source.next(), Some(2));
+        assert_eq!(source.underruns(), 1);
+    }
+
+    #[test]
+    fn underrun_can_end_the_source() {
+        let (producer, mut source) =
+            RingBufferSource::with_policy(8, 1, 48000, UnderrunPolicy::EndStream);
+        assert_eq!(source.next(), None);
+        assert_eq!(source.next(), None);
+        assert_eq!(producer.underruns(), 2);
+
+        producer.push(&[1i16]);
+        assert_eq!(source.next(), Some(1));
+    }
+
+    #[test]
+    fn underrun_repeats_the_last_frame() {
+        let (producer, mut source) =
+            RingBufferSource::with_policy(8, 2, 48000, UnderrunPolicy::Repeat);
+        assert_eq!(source.next(), Some(0));
+        assert_eq!(source.next(), Some(0));
+
+        producer.push(&[1i16, 2]);
+        assert_eq!(
+            source.by_ref().take(6).collect::<Vec<_>>(),
+            [1, 2, 1, 2, 1, 2]
+        );
+        assert_eq!(source.underruns(), 3);
+    }
+
+    #[test]
+    fn drops_what_does_not_fit() {
+        let (producer, source) = RingBufferSource::new(4, 1, 48000);
+        assert_eq!(producer.push(&[1i16, 2, 3]), 3);
+        assert_eq!(producer.push(&[4, 5, 6]), 1);
+        drop(producer);
+        assert_eq!(source.collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn drops_the_partial_frame_at_the_end() {
+        let (producer, mut source) = RingBufferSource::new(8, 2, 48000);
+        producer.push(&[1i16, 2, 3]);
+        drop(producer);
+        assert_eq!(source.by_ref().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(source.next(), None);
+        assert_eq!(source.underruns(), 0);
+    }
+
+    #[test]
+    fn goes_around_the_buffer() {
+        let (producer, mut source) = RingBufferSource::new(3, 1, 48000);
+        for i in 0..10i16 {
+            assert_eq!(producer.push(&[i, i + 100]), 2);
+            assert_eq!(source.next(), Some(i));
+            assert_eq!(source.next(), Some(i + 100));
+        }
+        assert_eq!(source.underruns(), 0);
+    }
+
+    #[test]
+    fn pushing_is_not_an_underrun() {
+        let (producer, mut source) = RingBufferSource::new(100_000, 1, 48000);
+        producer.push(&[1i16; 100_000]);
+        let done = Arc::new(AtomicBool::new(false));
+        let pushing = thread::spawn({
+            let done = done.clone();
+            move || {
+                // keeps the producer busy while the source plays what is in the buffer
+                while !done.load(Ordering::Relaxed) {
+                    producer.push(&[1; 16]);
+                }
+            }
+        });
+
+        for _ in 0..100_000 {
+            assert_eq!(source.next(), Some(1));
+        }
+        done.store(true, Ordering::Relaxed);
+        pushing.join().unwrap();
+        assert_eq!(source.underruns(), 0);
+    }
+
+    #[test]
+    fn from_another_thread() {
+        let (producer, source) = RingBufferSource::new(1024, 1, 48000);
+        let capture = producer.clone();
+        thread::spawn(move || {
+            for chunk in (0..1000i16).collect::<Vec<_>>().chunks(10) {
+                assert_eq!(capture.push(chunk), 10);
+            }
+        })
+        .join()
+        .unwrap();
+        drop(producer);
+
+        let played: Vec<i16> = source.collect();
+        assert_eq!(played, (0..1000).collect::<Vec<_>>());
+    }
+}
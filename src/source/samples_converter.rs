@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 use std::time::Duration;
 
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 use cpal::{FromSample, Sample as CpalSample};
 
 use super::SeekError;
@@ -97,8 +97,58 @@ where
         self.inner.total_duration()
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.inner.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.inner.is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.inner.playback_pos()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.inner.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        D::FORMAT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn converts_the_values() {
+        let samples = vec![i16::MIN, -16384, 0, 16384];
+        let converted: Vec<f32> = SamplesBuffer::new(2, 44100, samples)
+            .convert_samples()
+            .collect();
+        assert_eq!(converted, [-1.0, -0.5, 0.0, 0.5]);
+
+        let converted: Vec<i16> = SamplesBuffer::new(1, 44100, vec![0u8, 128, 192])
+            .convert_samples()
+            .collect();
+        assert_eq!(converted, [i16::MIN, 0, 16384]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let samples = vec![i16::MIN, -8192, 0, 1, 16384, i16::MAX];
+        let converted: Vec<i16> = SamplesBuffer::new(1, 44100, samples.clone())
+            .convert_samples::<f32>()
+            .convert_samples::<i16>()
+            .collect();
+        assert_eq!(converted, samples);
+    }
 }
@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Saturate` object.
+pub fn saturate<I>(input: I, drive: f32) -> Saturate<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    Saturate { input, drive }
+}
+
+/// Filter that rounds off the peaks of a source with a `tanh` soft clipper.
+#[derive(Clone, Debug)]
+pub struct Saturate<I> {
+    input: I,
+    drive: f32,
+}
+
+impl<I> Saturate<I> {
+    /// Modifies the drive.
+    #[inline]
+    pub fn set_drive(&mut self, drive: f32) {
+        self.drive = drive;
+    }
+
+    /// Returns the drive.
+    #[inline]
+    pub fn drive(&self) -> f32 {
+        self.drive
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Saturate<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let sample = self.input.next()?;
+        if self.drive == 0.0 {
+            return Some(sample);
+        }
+        // Normalized so that a full scale sample stays at full scale.
+        let shaped = (sample.to_f32() * self.drive).tanh() / self.drive.tanh();
+        Some(I::Item::from_sample_(shaped))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Saturate<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample + FromSample<f32>,
+{
+}
+
+impl<I> Source for Saturate<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        self.input.samples_remaining()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.input.playback_pos()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn rounds_off_peaks() {
+        let source = SamplesBuffer::new(1, 48000, vec![0.0f32, 0.1, 0.5, 1.0, -1.0]);
+        let output: Vec<f32> = source.saturate(4.0).collect();
+
+        assert_eq!(output[0], 0.0);
+        // quiet samples are boosted, the peaks stay at full scale
+        assert!(output[1] > 0.1 && output[1] < output[2]);
+        assert!(output[2] > 0.5 && output[2] < 1.0);
+        assert!((output[3] - 1.0).abs() < 1e-6);
+        assert!((output[4] + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn zero_drive_passes_through() {
+        let data = vec![-32768i16, -100, 0, 100, 32767];
+        let source = SamplesBuffer::new(1, 48000, data.clone());
+        assert_eq!(source.saturate(0.0).collect::<Vec<_>>(), data);
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use super::{SeekError, UniformSourceIterator};
+use crate::{Sample, Source};
+
+/// Builds a source that plays `sources` one after another, all converted to the same format.
+///
+/// Unlike [`from_iter`](super::from_iter), the sources can have different sample rates and
+/// channel counts: each one is converted with a [`UniformSourceIterator`] to the format of
+/// the first one, or to the one given to [`Sequence::with_format`]. The duration is the sum
+/// of the durations of all the sources, if they are all known.
+///
+/// # Example
+///
+/// ```
+/// use rodio::buffer::SamplesBuffer;
+/// use rodio::source::{sequence, Source};
+///
+/// let first = SamplesBuffer::new(2, 48000, vec![0.5f32; 960]);
+/// let second = SamplesBuffer::new(1, 44100, vec![0.25f32; 441]);
+/// let playlist = sequence([first, second]);
+/// assert_eq!(playlist.channels(), 2);
+/// assert_eq!(playlist.sample_rate(), 48000);
+/// ```
+pub fn sequence<I>(sources: I) -> Sequence<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Source,
+    <I::Item as Iterator>::Item: Sample,
+{
+    let sources: VecDeque<_> = sources.into_iter().collect();
+    let (channels, sample_rate) = sources
+        .front()
+        .map(|source| (source.channels().max(1), source.sample_rate().max(1)))
+        // Dummy values that only matter if there are no sources.
+        .unwrap_or((2, 44100));
+
+    Sequence {
+        sources,
+        current: None,
+        played: Some(Duration::ZERO),
+        channels,
+        sample_rate,
+        current_channel: 0,
+        padding: 0,
+    }
+}
+
+/// A source that plays sources one after another in a common format, see [`sequence`].
+pub struct Sequence<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    // The sources left to play, not converted yet.
+    sources: VecDeque<S>,
+    current: Option<UniformSourceIterator<S, S::Item>>,
+    // The duration of the sources already played, `None` if one of them was unknown.
+    played: Option<Duration>,
+    channels: u16,
+    sample_rate: u32,
+    current_channel: u16,
+    // Silent samples left to complete the last frame of a source that ended in the middle.
+    padding: u16,
+}
+
+impl<S> Sequence<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    /// Converts the sources to `channels` channels at `sample_rate`, instead of the format of
+    /// the first source. A source that already started keeps the format it started with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` or `sample_rate` is 0.
+    pub fn with_format(mut self, channels: u16, sample_rate: u32) -> Sequence<S> {
+        assert!(channels >= 1, "a sequence needs at least one channel");
+        assert!(
+            sample_rate >= 1,
+            "the sample rate of a sequence can not be 0"
+        );
+        if self.current.is_none() {
+            self.channels = channels;
+            self.sample_rate = sample_rate;
+        }
+        self
+    }
+
+    #[inline]
+    fn next_channel(&mut self) {
+        self.current_channel = (self.current_channel + 1) % self.channels;
+    }
+}
+
+impl<S> Iterator for Sequence<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    type Item = S::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<S::Item> {
+        loop {
+            if self.padding > 0 {
+                self.padding -= 1;
+                self.next_channel();
+                return Some(S::Item::zero_value());
+            }
+
+            if let Some(current) = &mut self.current {
+                if let Some(sample) = current.next() {
+                    self.next_channel();
+                    return Some(sample);
+                }
+                self.played = self
+                    .played
+                    .zip(current.total_duration())
+                    .map(|(played, duration)| played + duration);
+                self.current = None;
+                // The next source starts on a frame boundary.
+                if self.current_channel != 0 {
+                    self.padding = self.channels - self.current_channel;
+                    continue;
+                }
+            }
+
+            let source = self.sources.pop_front()?;
+            self.current = Some(UniformSourceIterator::new(
+                source,
+                self.channels,
+                self.sample_rate,
+            ));
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let current = self
+            .current
+            .as_ref()
+            .map_or(0, |current| current.size_hint().0);
+        (current + self.padding as usize, None)
+    }
+}
+
+impl<S> Source for Sequence<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let mut total = self.played?;
+        if let Some(current) = &self.current {
+            total += current.total_duration()?;
+        }
+        for source in &self.sources {
+            total += source.total_duration()?;
+        }
+        Some(total)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.current
+            .as_ref()
+            .is_none_or(|current| current.is_seekable())
+    }
+
+    /// Seeks in the source that is playing, like [`from_iter`](super::from_iter).
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        if let Some(current) = self.current.as_mut() {
+            current.try_seek(pos)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{sequence, Source};
+
+    #[test]
+    fn converts_to_the_first_format() {
+        let stereo = SamplesBuffer::new(2, 48000, vec![1.0f32; 96]);
+        let mono = SamplesBuffer::new(1, 44100, vec![0.5f32; 441]);
+        let playlist = sequence([stereo, mono]);
+        assert_eq!(playlist.channels(), 2);
+        assert_eq!(playlist.sample_rate(), 48000);
+        assert_eq!(playlist.total_duration(), Some(Duration::from_millis(11)));
+
+        let output: Vec<f32> = playlist.collect();
+        let (first, second) = output.split_at(96);
+        assert!(first.iter().all(|&sample| sample == 1.0));
+        // 10ms at 48000, twice for the two channels, played without a gap
+        assert!((second.len() as i32 - 960).abs() <= 2, "{}", second.len());
+        assert!(second.iter().all(|&sample| sample == 0.5));
+    }
+
+    #[test]
+    fn keeps_frames_aligned() {
+        // every source starts on the first channel of a frame
+        let first = SamplesBuffer::new(1, 1000, vec![1i16]);
+        let second = SamplesBuffer::new(2, 1000, vec![2i16, 3]);
+        let playlist = sequence([first, second]).with_format(3, 1000);
+        assert_eq!(playlist.channels(), 3);
+        assert_eq!(playlist.collect::<Vec<_>>(), [1, 1, 0, 2, 3, 0]);
+    }
+}
@@ -28,6 +28,12 @@ pub enum Function {
     Square,
     /// A rising sawtooth wave.
     Sawtooth,
+    /// A square wave like [`Function::Square`], with its edges smoothed to avoid the aliasing
+    /// the naive wave has at high frequencies.
+    BandlimitedSquare,
+    /// A rising sawtooth wave like [`Function::Sawtooth`], with its edges smoothed to avoid
+    /// the aliasing the naive wave has at high frequencies.
+    BandlimitedSawtooth,
 }
 
 impl Function {
@@ -47,10 +53,38 @@ impl Function {
                 }
             }
             Self::Sawtooth => 2.0f32 * (cycle_pos - (cycle_pos + 0.5f32).floor()),
+            Self::BandlimitedSquare => {
+                let phase = cycle_pos.fract();
+                let dt = 1.0 / period;
+                let naive = if phase < 0.5 { 1.0 } else { -1.0 };
+                naive + poly_blep(phase, dt) - poly_blep((phase + 0.5).fract(), dt)
+            }
+            Self::BandlimitedSawtooth => {
+                // Same phase as the naive sawtooth, which drops at the middle of the cycle.
+                let phase = (cycle_pos + 0.5).fract();
+                2.0 * phase - 1.0 - poly_blep(phase, 1.0 / period)
+            }
         }
     }
 }
 
+/// Correction of a unit step at `phase == 0`, applied over the samples around it.
+///
+/// `dt` is the part of the cycle covered by one sample. This is the polynomial band-limited
+/// step (PolyBLEP) approximation.
+#[inline]
+fn poly_blep(phase: f32, dt: f32) -> f32 {
+    if phase < dt {
+        let x = phase / dt;
+        2.0 * x - x * x - 1.0
+    } else if phase > 1.0 - dt {
+        let x = (phase - 1.0) / dt;
+        x * x + 2.0 * x + 1.0
+    } else {
+        0.0
+    }
+}
+
 /// An infinite source that produces one of a selection of test waveforms.
 #[derive(Clone, Debug)]
 pub struct SignalGenerator {
@@ -112,6 +146,11 @@ impl Source for SignalGenerator {
         None
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
     #[inline]
     fn try_seek(&mut self, duration: Duration) -> Result<(), SeekError> {
         self.i = (self.sample_rate.0 as f32 * duration.as_secs_f32()) as u64;
@@ -121,6 +160,8 @@ impl Source for SignalGenerator {
 
 #[cfg(test)]
 mod tests {
+    use std::f32::consts::TAU;
+
     use crate::source::{Function, SignalGenerator};
     use approx::assert_abs_diff_eq;
 
@@ -182,4 +223,60 @@ mod tests {
         assert_abs_diff_eq!(wf.next().unwrap(), 0.0f32);
         assert_abs_diff_eq!(wf.next().unwrap(), -0.58778554f32);
     }
+
+    /// Part of the energy that is not in the harmonics of `frequency`, which is aliasing.
+    /// `samples` must hold exactly one second.
+    fn aliased_energy(samples: &[f32], frequency: usize) -> f32 {
+        let len = samples.len();
+        let twiddles: Vec<(f32, f32)> = (0..len)
+            .map(|n| (TAU * n as f32 / len as f32).sin_cos())
+            .collect();
+        let mut total = 0.0;
+        let mut aliased = 0.0;
+        for bin in 1..len / 2 {
+            let (mut re, mut im) = (0.0, 0.0);
+            for (n, &sample) in samples.iter().enumerate() {
+                let (sin, cos) = twiddles[bin * n % len];
+                re += sample * cos;
+                im -= sample * sin;
+            }
+            let energy = re * re + im * im;
+            total += energy;
+            if bin % frequency != 0 {
+                aliased += energy;
+            }
+        }
+        aliased / total
+    }
+
+    #[test]
+    fn bandlimited_waves_alias_less() {
+        // a low sample rate keeps the transform small, the wave is well above a quarter of it
+        const RATE: u32 = 2000;
+        const FREQUENCY: usize = 313;
+        for (naive, bandlimited) in [
+            (Function::Sawtooth, Function::BandlimitedSawtooth),
+            (Function::Square, Function::BandlimitedSquare),
+        ] {
+            let render = |function| {
+                SignalGenerator::new(cpal::SampleRate(RATE), FREQUENCY as f32, function)
+                    .take(RATE as usize)
+                    .collect::<Vec<_>>()
+            };
+            let naive = aliased_energy(&render(naive), FREQUENCY);
+            let bandlimited = aliased_energy(&render(bandlimited), FREQUENCY);
+            assert!(bandlimited < naive / 4.0, "{bandlimited} vs {naive}");
+        }
+    }
+
+    #[test]
+    fn bandlimited_saw_keeps_its_shape() {
+        let mut wf =
+            SignalGenerator::new(cpal::SampleRate(1000), 10.0, Function::BandlimitedSawtooth);
+        assert_abs_diff_eq!(wf.next().unwrap(), 0.0f32);
+        assert_abs_diff_eq!(wf.nth(24).unwrap(), 0.5f32);
+        // the drop in the middle of the cycle is softened
+        assert!(wf.nth(24).unwrap().abs() < 0.1);
+        assert_abs_diff_eq!(wf.nth(24).unwrap(), -0.5f32);
+    }
 }
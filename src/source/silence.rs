@@ -0,0 +1,190 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Source that plays silence for a given duration, or forever.
+///
+/// Queued between sources it leaves a gap of that duration between them.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use rodio::source::{Silence, SineWave, Source};
+///
+/// let (queue, _output) = rodio::queue::queue::<f32>(false);
+/// let beep = || SineWave::new(440.0).take_duration(Duration::from_millis(100));
+/// queue.append(beep());
+/// queue.append(Silence::new(1, 48000, Duration::from_millis(400)));
+/// queue.append(beep());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Silence<S> {
+    channels: u16,
+    sample_rate: u32,
+    // Frames in the whole silence, `None` for a silence that never ends.
+    total_frames: Option<u64>,
+    // Samples left to play.
+    samples_left: u64,
+    marker: PhantomData<S>,
+}
+
+impl<S> Silence<S> {
+    /// Builds a silence that lasts `duration`, rounded down to a whole frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` or `sample_rate` is 0.
+    pub fn new(channels: u16, sample_rate: u32, duration: Duration) -> Silence<S> {
+        assert_format(channels, sample_rate);
+        let frames = (duration.as_nanos() * sample_rate as u128 / NANOS_PER_SEC) as u64;
+        Silence {
+            channels,
+            sample_rate,
+            total_frames: Some(frames),
+            samples_left: frames * channels as u64,
+            marker: PhantomData,
+        }
+    }
+
+    /// Builds a silence that never ends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` or `sample_rate` is 0.
+    pub fn forever(channels: u16, sample_rate: u32) -> Silence<S> {
+        assert_format(channels, sample_rate);
+        Silence {
+            channels,
+            sample_rate,
+            total_frames: None,
+            samples_left: u64::MAX,
+            marker: PhantomData,
+        }
+    }
+}
+
+fn assert_format(channels: u16, sample_rate: u32) {
+    assert!(channels >= 1, "a silence needs at least one channel");
+    assert!(
+        sample_rate >= 1,
+        "the sample rate of a silence can not be 0"
+    );
+}
+
+impl<S> Iterator for Silence<S>
+where
+    S: Sample,
+{
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        if self.total_frames.is_none() {
+            return Some(S::zero_value());
+        }
+        if self.samples_left == 0 {
+            return None;
+        }
+        self.samples_left -= 1;
+        Some(S::zero_value())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.total_frames {
+            Some(_) => {
+                let left = usize::try_from(self.samples_left).unwrap_or(usize::MAX);
+                (left, Some(left))
+            }
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+impl<S> Source for Silence<S>
+where
+    S: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.total_frames
+            .map(|_| usize::try_from(self.samples_left).unwrap_or(usize::MAX))
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_frames.map(|frames| {
+            let nanos = frames as u128 * NANOS_PER_SEC / self.sample_rate as u128;
+            Duration::from_nanos(nanos as u64)
+        })
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        if let Some(total_frames) = self.total_frames {
+            let frame = (pos.as_nanos() * self.sample_rate as u128 / NANOS_PER_SEC) as u64;
+            self.samples_left = total_frames.saturating_sub(frame) * self.channels as u64;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Silence;
+    use crate::source::Source;
+
+    #[test]
+    fn lasts_the_duration() {
+        let silence = Silence::<i16>::new(2, 1000, Duration::from_millis(5));
+        assert_eq!(silence.total_duration(), Some(Duration::from_millis(5)));
+        assert_eq!(silence.current_frame_len(), Some(10));
+        assert_eq!(silence.size_hint(), (10, Some(10)));
+        assert_eq!(silence.collect::<Vec<_>>(), [0; 10]);
+    }
+
+    #[test]
+    fn empty_when_it_lasts_nothing() {
+        let mut silence = Silence::<i16>::new(2, 1000, Duration::ZERO);
+        assert_eq!(silence.total_duration(), Some(Duration::ZERO));
+        assert_eq!(silence.next(), None);
+    }
+
+    #[test]
+    fn forever() {
+        let silence = Silence::<f32>::forever(1, 1000);
+        assert_eq!(silence.total_duration(), None);
+        assert_eq!(silence.current_frame_len(), None);
+        assert!(silence.take(10000).all(|sample| sample == 0.0));
+    }
+
+    #[test]
+    fn seeking() {
+        let mut silence = Silence::<i16>::new(2, 1000, Duration::from_millis(5));
+        silence.try_seek(Duration::from_millis(3)).unwrap();
+        assert_eq!(silence.count(), 4);
+    }
+}
@@ -56,6 +56,11 @@ impl Source for SineWave {
         None
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
     /// `try_seek()` does nothing on the sine generator. If you need to
     /// generate a sine tone with a precise phase or sample offset, consider
     /// using `skip::skip_samples()`.
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use crate::source::ChannelVolume;
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 use super::SeekError;
 
@@ -122,8 +122,45 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Spatial;
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    #[test]
+    fn seek_in_the_middle_of_a_frame() {
+        let spatial = || {
+            let input = SamplesBuffer::new(1, 1, vec![1.0f32, 2.0, 3.0]);
+            Spatial::new(input, [0.0, 0.0, 0.0], [0.0, 0.0, 0.0], [1.0, 0.0, 0.0])
+        };
+        let first: Vec<f32> = spatial().take(2).collect();
+        assert_ne!(first[0], first[1]);
+
+        let mut source = spatial();
+        source.next();
+        source.try_seek(Duration::from_secs(2)).unwrap();
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            vec![3.0 * first[0], 3.0 * first[1]]
+        );
+    }
 }
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `StereoWidth` object.
+pub fn stereo_width<I>(input: I, width: f32) -> StereoWidth<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    assert_eq!(
+        input.channels(),
+        2,
+        "the stereo width can only be changed on a stereo source"
+    );
+    StereoWidth {
+        input,
+        width,
+        right: None,
+    }
+}
+
+/// Filter that narrows or widens the stereo image of a source, in mid-side.
+#[derive(Clone, Debug)]
+pub struct StereoWidth<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    width: f32,
+    // The right sample of the frame whose left sample was just returned.
+    right: Option<I::Item>,
+}
+
+impl<I> StereoWidth<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Modifies the width.
+    #[inline]
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width;
+    }
+
+    /// Returns the width.
+    #[inline]
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for StereoWidth<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(right) = self.right.take() {
+            return Some(right);
+        }
+
+        let left = self.input.next()?;
+        // An incomplete last frame is left as it is.
+        let Some(right) = self.input.next() else {
+            return Some(left);
+        };
+        let (left, right) = (left.to_f32(), right.to_f32());
+        let mid = (left + right) / 2.0;
+        let side = (left - right) / 2.0 * self.width;
+
+        self.right = Some(I::Item::from_sample_(mid - side));
+        Some(I::Item::from_sample_(mid + side))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.right.is_some() as usize;
+        let (min, max) = self.input.size_hint();
+        (
+            min.saturating_add(pending),
+            max.and_then(|max| max.checked_add(pending)),
+        )
+    }
+}
+
+impl<I> ExactSizeIterator for StereoWidth<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample + FromSample<f32>,
+{
+}
+
+impl<I> Source for StereoWidth<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let pending = self.right.is_some() as usize;
+        self.input.current_frame_len().map(|len| len + pending)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.right = None;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    fn widen(width: f32) -> Vec<f32> {
+        let source = SamplesBuffer::new(2, 44100, vec![0.75f32, 0.25, -0.5, 0.5]);
+        source.stereo_width(width).collect()
+    }
+
+    #[test]
+    fn scales_the_side() {
+        // first frame: mid 0.5, side 0.25; second frame: mid 0.0, side -0.5
+        assert_eq!(widen(1.0), vec![0.75, 0.25, -0.5, 0.5]);
+        assert_eq!(widen(0.0), vec![0.5, 0.5, 0.0, 0.0]);
+        assert_eq!(widen(0.5), vec![0.625, 0.375, -0.25, 0.25]);
+        assert_eq!(widen(2.0), vec![1.0, 0.0, -1.0, 1.0]);
+        // swaps the channels
+        assert_eq!(widen(-1.0), vec![0.25, 0.75, 0.5, -0.5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "stereo source")]
+    fn needs_two_channels() {
+        SamplesBuffer::new(1, 44100, vec![0.0f32; 4]).stereo_width(1.0);
+    }
+}
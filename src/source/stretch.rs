@@ -0,0 +1,399 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use super::SeekError;
+use crate::{Sample, SampleFormat, Source};
+
+/// Internal function that builds a `Stretch` object.
+pub fn stretch<I>(input: I, factor: f32) -> Stretch<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    assert!(factor > 0.0, "the stretch factor must be positive");
+
+    let channels = input.channels() as usize;
+    // Segments of 30ms, long enough to hold a few periods of low notes.
+    let window_len = ((input.sample_rate() as f32 * 0.03) as usize / 2).max(8) * 2;
+    let out_hop = window_len / 2;
+    // Periodic Hann window, overlapping halves of it add up to one.
+    let window = (0..window_len)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / window_len as f32).cos())
+        .collect();
+
+    let mut stretch = Stretch {
+        input,
+        factor,
+        channels,
+        window,
+        out_hop,
+        in_hop: out_hop as f64 / factor as f64,
+        tolerance: window_len / 4,
+        buffer: Vec::new(),
+        buffer_start: 0,
+        input_frames: 0,
+        input_done: false,
+        hop: 0,
+        previous_start: None,
+        overlap: Vec::new(),
+        output: Vec::new(),
+        output_pos: 0,
+        skip_frames: 0,
+        emitted: 0,
+    };
+    stretch.reset();
+    stretch
+}
+
+/// Filter that changes the duration of a source without changing its pitch.
+///
+/// It uses WSOLA (waveform similarity overlap-add): short overlapping segments of the input
+/// are added back together at a different pace, each one picked near its ideal position so
+/// that it lines up with the waveform of the previous one. The same segments are used for all
+/// channels so they stay in phase.
+#[derive(Clone, Debug)]
+pub struct Stretch<I> {
+    input: I,
+    factor: f32,
+    channels: usize,
+    window: Vec<f32>,
+    // Number of frames between two segments in the output.
+    out_hop: usize,
+    // Ideal number of frames between two segments in the input.
+    in_hop: f64,
+    // Maximum distance, in frames, between a segment and its ideal position.
+    tolerance: usize,
+    // Interleaved input frames, preceded by some silence so the output fades in properly.
+    buffer: Vec<f32>,
+    // Index of the first frame of `buffer`, counting from the start of the silence.
+    buffer_start: usize,
+    // Number of frames read from `input`.
+    input_frames: u64,
+    input_done: bool,
+    // Index of the next segment.
+    hop: u64,
+    // Where the previous segment started in the input.
+    previous_start: Option<usize>,
+    // Segments added together that are not complete yet.
+    overlap: Vec<f32>,
+    // Complete output samples, waiting to be returned.
+    output: Vec<f32>,
+    output_pos: usize,
+    // Frames at the start of the output that only come from the silence.
+    skip_frames: usize,
+    // Number of samples returned.
+    emitted: u64,
+}
+
+impl<I> Stretch<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    /// Returns the factor the duration is multiplied by.
+    #[inline]
+    pub fn factor(&self) -> f32 {
+        self.factor
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.buffer.resize(self.out_hop * self.channels, 0.0);
+        self.buffer_start = 0;
+        self.input_frames = 0;
+        self.input_done = false;
+        self.hop = 0;
+        self.previous_start = None;
+        self.overlap.clear();
+        self.overlap.resize(self.window.len() * self.channels, 0.0);
+        self.output.clear();
+        self.output_pos = 0;
+        self.skip_frames = self.out_hop;
+        self.emitted = 0;
+    }
+
+    /// Number of samples the output will have, known once the input has ended.
+    fn output_len(&self) -> Option<u64> {
+        self.input_done.then(|| {
+            (self.input_frames as f64 * self.factor as f64).round() as u64 * self.channels as u64
+        })
+    }
+
+    /// Reads input until `buffer` holds the frames before `end`, or the input runs out.
+    fn fill_buffer(&mut self, end: usize) {
+        while !self.input_done && self.buffer_start + self.buffer.len() / self.channels < end {
+            for channel in 0..self.channels {
+                match self.input.next() {
+                    Some(sample) => self.buffer.push(sample.to_f32()),
+                    None => {
+                        // complete the last frame with silence
+                        if channel != 0 {
+                            let len = self.buffer.len() + self.channels - channel;
+                            self.buffer.resize(len, 0.0);
+                            self.input_frames += 1;
+                        }
+                        self.input_done = true;
+                        break;
+                    }
+                }
+                if channel == self.channels - 1 {
+                    self.input_frames += 1;
+                }
+            }
+        }
+    }
+
+    /// Frame `frame` of channel `channel`, silence outside of the buffer.
+    #[inline]
+    fn sample(&self, frame: usize, channel: usize) -> f32 {
+        frame
+            .checked_sub(self.buffer_start)
+            .and_then(|frame| self.buffer.get(frame * self.channels + channel))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Similarity of the segments starting at `a` and `b`, over all channels.
+    fn similarity(&self, a: usize, b: usize) -> f32 {
+        // Every fourth frame is plenty to compare waveforms and much cheaper.
+        (0..self.window.len())
+            .step_by(4)
+            .map(|i| {
+                (0..self.channels)
+                    .map(|c| self.sample(a + i, c) * self.sample(b + i, c))
+                    .sum::<f32>()
+            })
+            .sum()
+    }
+
+    /// Adds the next segment and moves the completed frames to `output`.
+    fn next_segment(&mut self) {
+        let window_len = self.window.len();
+        let ideal = (self.hop as f64 * self.in_hop).round() as usize;
+
+        let start = match self.previous_start {
+            None => ideal,
+            Some(previous) => {
+                // The segment that would naturally follow the previous one.
+                let natural = previous + self.out_hop;
+                self.fill_buffer((ideal + self.tolerance).max(natural) + window_len);
+
+                let first = ideal.saturating_sub(self.tolerance).max(self.buffer_start);
+                let mut best = (ideal.max(first), f32::MIN);
+                for candidate in first..=ideal + self.tolerance {
+                    let similarity = self.similarity(candidate, natural);
+                    if similarity > best.1 {
+                        best = (candidate, similarity);
+                    }
+                }
+                best.0
+            }
+        };
+        self.fill_buffer(start + window_len);
+
+        for (i, weight) in self.window.iter().enumerate() {
+            for c in 0..self.channels {
+                self.overlap[i * self.channels + c] += weight * self.sample(start + i, c);
+            }
+        }
+
+        let complete = self.out_hop * self.channels;
+        let skipped = self.skip_frames.min(self.out_hop);
+        self.skip_frames -= skipped;
+        self.output.clear();
+        self.output
+            .extend_from_slice(&self.overlap[skipped * self.channels..complete]);
+        self.output_pos = 0;
+        self.overlap.drain(..complete);
+        self.overlap.resize(window_len * self.channels, 0.0);
+
+        self.previous_start = Some(start);
+        self.hop += 1;
+
+        // Frames that neither the next segment nor its search can start on.
+        let next_ideal = (self.hop as f64 * self.in_hop).round() as usize;
+        let keep_from = next_ideal
+            .saturating_sub(self.tolerance)
+            .min(start + self.out_hop);
+        if keep_from > self.buffer_start {
+            let drop = ((keep_from - self.buffer_start) * self.channels).min(self.buffer.len());
+            self.buffer.drain(..drop);
+            self.buffer_start += drop / self.channels;
+        }
+    }
+}
+
+impl<I> Iterator for Stretch<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        loop {
+            if self.output_len().is_some_and(|len| self.emitted >= len) {
+                return None;
+            }
+            if let Some(sample) = self.output.get(self.output_pos) {
+                self.output_pos += 1;
+                self.emitted += 1;
+                return Some(I::Item::from_sample_(*sample));
+            }
+            self.next_segment();
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.output_len() {
+            Some(len) => {
+                let remaining = len.saturating_sub(self.emitted) as usize;
+                (remaining, Some(remaining))
+            }
+            None => {
+                let channels = self.channels as u64;
+                let bound = |input_frames: u64| {
+                    let frames = self.input_frames + input_frames;
+                    let len = (frames as f64 * self.factor as f64).round() as u64 * channels;
+                    len.saturating_sub(self.emitted) as usize
+                };
+                let (min, max) = self.input.size_hint();
+                (
+                    bound(min as u64 / channels),
+                    max.map(|max| bound((max as u64).div_ceil(channels))),
+                )
+            }
+        }
+    }
+}
+
+impl<I> Source for Stretch<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels as u16
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input
+            .total_duration()
+            .map(|duration| duration.mul_f32(self.factor))
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos.div_f32(self.factor))?;
+        self.reset();
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_1_SQRT_2, PI};
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    const RATE: u32 = 8000;
+
+    fn sine(frequency: f32, seconds: f32) -> SamplesBuffer<f32> {
+        let len = (RATE as f32 * seconds) as usize;
+        let data = (0..len).map(|i| (2.0 * PI * frequency * i as f32 / RATE as f32).sin());
+        SamplesBuffer::new(1, RATE, data.collect::<Vec<_>>())
+    }
+
+    /// Frequency estimated from the zero crossings, skipping the edges.
+    fn frequency(samples: &[f32], sample_rate: u32) -> f32 {
+        let middle = &samples[samples.len() / 10..samples.len() * 9 / 10];
+        let crossings = middle
+            .windows(2)
+            .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+            .count();
+        crossings as f32 / 2.0 / (middle.len() as f32 / sample_rate as f32)
+    }
+
+    #[test]
+    fn longer_keeps_pitch() {
+        let stretched = sine(440.0, 1.0).stretch(1.5);
+        assert_eq!(
+            stretched.total_duration(),
+            Some(Duration::from_millis(1500))
+        );
+
+        let output: Vec<f32> = stretched.collect();
+        assert_eq!(output.len(), 12000);
+        let frequency = frequency(&output, RATE);
+        assert!((frequency - 440.0).abs() < 440.0 * 0.02, "{}", frequency);
+
+        // the segments line up, so they do not cancel each other out
+        let middle = &output[1000..11000];
+        let rms = (middle.iter().map(|s| s * s).sum::<f32>() / middle.len() as f32).sqrt();
+        assert!((rms - FRAC_1_SQRT_2).abs() < 0.05, "{}", rms);
+    }
+
+    #[test]
+    fn shorter_keeps_pitch() {
+        let output: Vec<f32> = sine(440.0, 1.0).stretch(0.5).collect();
+        assert_eq!(output.len(), 4000);
+        let frequency = frequency(&output, RATE);
+        assert!((frequency - 440.0).abs() < 440.0 * 0.02, "{}", frequency);
+    }
+
+    #[test]
+    fn stereo_stays_in_sync() {
+        let data: Vec<f32> = sine(300.0, 0.5).flat_map(|s| [s, -s]).collect();
+        let output: Vec<f32> = SamplesBuffer::new(2, RATE, data).stretch(1.25).collect();
+        assert_eq!(output.len(), 2 * 5000);
+        for frame in output.chunks(2) {
+            assert!((frame[0] + frame[1]).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use super::{SeekError, UniformSourceIterator};
+use crate::{Sample, Source};
+
+type BoxedSource<S> = Box<dyn Source<Item = S> + Send>;
+
+/// Source whose content can be swapped for another source while it plays, for example to
+/// bring the layers of adaptive music in and out without touching the mixer.
+///
+/// The sources are set with a [`SwitchHandle`] and converted to the format of the switchable
+/// source, so that format never changes. A new source starts on a frame boundary, either right
+/// away or crossfading from the one before. The source plays silence while it has nothing
+/// to play, and ends once all the handles are dropped and its last source has ended.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use rodio::source::{SineWave, SwitchableSource};
+///
+/// let (handle, source) = SwitchableSource::<f32>::new(2, 48000);
+/// handle.set_source(SineWave::new(220.0));
+/// // later on, when the game gets tense
+/// handle.crossfade_to(SineWave::new(330.0), Duration::from_secs(2));
+/// ```
+pub struct SwitchableSource<S> {
+    shared: Arc<Mutex<Option<Switch<S>>>>,
+    channels: u16,
+    sample_rate: u32,
+    current: Option<BoxedSource<S>>,
+    // The source faded out during a crossfade, with the gain it fades out from.
+    outgoing: Option<(BoxedSource<S>, f32)>,
+    // Frames left in the crossfade, and its length.
+    fade_left: u64,
+    fade_len: u64,
+    // Gains of the current and outgoing sources during this frame.
+    gains: (f32, f32),
+    current_channel: u16,
+}
+
+/// Sets the source played by a [`SwitchableSource`], from any thread.
+pub struct SwitchHandle<S> {
+    shared: Arc<Mutex<Option<Switch<S>>>>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<S> Clone for SwitchHandle<S> {
+    fn clone(&self) -> Self {
+        SwitchHandle {
+            shared: self.shared.clone(),
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+        }
+    }
+}
+
+// A source waiting to be switched to, with the length of the crossfade in frames.
+struct Switch<S> {
+    source: BoxedSource<S>,
+    fade_frames: u64,
+}
+
+impl<S> SwitchableSource<S>
+where
+    S: Sample + Send + 'static,
+{
+    /// Builds a switchable source of `channels` channels at `sample_rate`, silent until a
+    /// source is set, and returns the handle that sets its sources along with it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` or `sample_rate` is 0.
+    pub fn new(channels: u16, sample_rate: u32) -> (SwitchHandle<S>, SwitchableSource<S>) {
+        assert!(
+            channels >= 1,
+            "a switchable source needs at least one channel"
+        );
+        assert!(
+            sample_rate >= 1,
+            "the sample rate of a switchable source can not be 0"
+        );
+        let shared = Arc::new(Mutex::new(None));
+        let handle = SwitchHandle {
+            shared: shared.clone(),
+            channels,
+            sample_rate,
+        };
+        let source = SwitchableSource {
+            shared,
+            channels,
+            sample_rate,
+            current: None,
+            outgoing: None,
+            fade_left: 0,
+            fade_len: 0,
+            gains: (1.0, 0.0),
+            current_channel: 0,
+        };
+        (handle, source)
+    }
+}
+
+impl<S> SwitchableSource<S>
+where
+    S: Sample,
+{
+    // Switches to the source waiting, if any, and moves the crossfade one frame further.
+    fn next_frame(&mut self) {
+        // Never waits for a handle: if one is busy the switch happens on a later frame.
+        let switch = match self.shared.try_lock() {
+            Ok(mut switch) => switch.take(),
+            Err(_) => None,
+        };
+        if let Some(Switch {
+            source,
+            fade_frames,
+        }) = switch
+        {
+            let previous = self.current.replace(source);
+            if fade_frames == 0 {
+                self.outgoing = None;
+                self.fade_left = 0;
+            } else {
+                // A source that was fading in fades out from where it got to.
+                self.outgoing = previous.map(|previous| (previous, self.gains.0));
+                self.fade_left = fade_frames;
+                self.fade_len = fade_frames;
+            }
+        }
+
+        if self.fade_left == 0 {
+            self.outgoing = None;
+            self.gains = (1.0, 0.0);
+            return;
+        }
+        let left = self.fade_left as f32 / self.fade_len as f32;
+        let outgoing_gain = self.outgoing.as_ref().map_or(0.0, |(_, gain)| *gain);
+        self.gains = (1.0 - left, outgoing_gain * left);
+        self.fade_left -= 1;
+    }
+
+    // Nothing is left to play, and no handle is left to set a source.
+    #[inline]
+    fn is_finished(&self) -> bool {
+        !self.is_playing() && Arc::strong_count(&self.shared) == 1
+    }
+
+    /// Returns true when a source is playing, false when the source plays silence.
+    #[inline]
+    pub fn is_playing(&self) -> bool {
+        self.current.is_some() || self.outgoing.is_some()
+    }
+}
+
+impl<S> SwitchHandle<S>
+where
+    S: Sample + Send + 'static,
+{
+    /// Plays `source` instead of the current one, from the next frame.
+    #[inline]
+    pub fn set_source<T>(&self, source: T)
+    where
+        T: Source + Send + 'static,
+        T::Item: Sample + Send,
+        S: FromSample<T::Item>,
+    {
+        self.switch(source, 0);
+    }
+
+    /// Plays `source` instead of the current one, fading it in while the current one fades
+    /// out over `duration`.
+    #[inline]
+    pub fn crossfade_to<T>(&self, source: T, duration: Duration)
+    where
+        T: Source + Send + 'static,
+        T::Item: Sample + Send,
+        S: FromSample<T::Item>,
+    {
+        let frames = duration.as_nanos() * self.sample_rate as u128 / 1_000_000_000;
+        self.switch(source, frames as u64);
+    }
+
+    fn switch<T>(&self, source: T, fade_frames: u64)
+    where
+        T: Source + Send + 'static,
+        T::Item: Sample + Send,
+        S: FromSample<T::Item>,
+    {
+        let source: UniformSourceIterator<T, S> =
+            UniformSourceIterator::new(source, self.channels, self.sample_rate);
+        // A switch that was not picked up yet is replaced.
+        *self.shared.lock().unwrap() = Some(Switch {
+            source: Box::new(source),
+            fade_frames,
+        });
+    }
+}
+
+impl<S> Iterator for SwitchableSource<S>
+where
+    S: Sample,
+{
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        let frame_start = self.current_channel == 0;
+        if frame_start {
+            self.next_frame();
+            if self.is_finished() {
+                return None;
+            }
+        }
+
+        let (gain, outgoing_gain) = self.gains;
+        let mut sample = match &mut self.current {
+            Some(current) => match current.next() {
+                Some(sample) => sample,
+                None => {
+                    self.current = None;
+                    S::zero_value()
+                }
+            },
+            None => S::zero_value(),
+        };
+        if gain < 1.0 {
+            sample = sample.amplify(gain);
+        }
+        if let Some((outgoing, _)) = &mut self.outgoing {
+            match outgoing.next() {
+                Some(value) => sample = sample.saturating_add(value.amplify(outgoing_gain)),
+                None => self.outgoing = None,
+            }
+        }
+        // The last source ended with the previous frame.
+        if frame_start && self.is_finished() {
+            return None;
+        }
+        self.current_channel = (self.current_channel + 1) % self.channels;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<S> Source for SwitchableSource<S>
+where
+    S: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.current
+            .as_ref()
+            .is_some_and(|current| current.is_seekable())
+    }
+
+    /// Seeks in the source that is playing, and ends any crossfade.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        match &mut self.current {
+            Some(current) => current.try_seek(pos)?,
+            None => {
+                return Err(SeekError::NotSupported {
+                    underlying_source: std::any::type_name::<Self>(),
+                })
+            }
+        }
+        self.outgoing = None;
+        self.fade_left = 0;
+        self.gains = (1.0, 0.0);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::SwitchableSource;
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{Source, Zero};
+
+    #[test]
+    fn switches_between_frames() {
+        let (handle, mut source) = SwitchableSource::<i16>::new(2, 1000);
+        assert_eq!(source.next(), Some(0));
+        // set in the middle of a frame, it starts with the next one
+        handle.set_source(SamplesBuffer::new(2, 1000, vec![1i16, 2, 3, 4]));
+        assert_eq!(source.next(), Some(0));
+        assert!(!source.is_playing());
+        assert_eq!(source.by_ref().take(4).collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert!(source.is_playing());
+
+        handle.set_source(SamplesBuffer::new(2, 1000, vec![5i16, 6]));
+        assert_eq!(source.by_ref().take(4).collect::<Vec<_>>(), [5, 6, 0, 0]);
+        assert!(!source.is_playing());
+    }
+
+    #[test]
+    fn keeps_its_format() {
+        let (handle, source) = SwitchableSource::<i16>::new(2, 1000);
+        handle.set_source(SamplesBuffer::new(1, 1000, vec![7i16, 8]));
+        drop(handle);
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.collect::<Vec<_>>(), [7, 7, 8, 8]);
+    }
+
+    #[test]
+    fn crossfades() {
+        let (handle, mut source) = SwitchableSource::<f32>::new(1, 1000);
+        handle.set_source(SamplesBuffer::new(1, 1000, vec![1.0f32; 20]));
+        assert_eq!(source.next(), Some(1.0));
+
+        handle.crossfade_to(Zero::<f32>::new(1, 1000), Duration::from_millis(10));
+        let faded: Vec<f32> = source.by_ref().take(11).collect();
+        assert_eq!(faded[0], 1.0);
+        assert!(faded.windows(2).all(|pair| pair[1] < pair[0]), "{faded:?}");
+        assert_eq!(faded[10], 0.0);
+    }
+
+    #[test]
+    fn ends_once_the_handles_are_dropped() {
+        let (handle, mut source) = SwitchableSource::<i16>::new(1, 1000);
+        assert_eq!(source.next(), Some(0));
+        drop(handle);
+        assert_eq!(source.next(), None);
+    }
+}
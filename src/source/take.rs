@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use crate::{Sample, Source};
+use crate::{Sample, SampleFormat, Source};
 
 use super::SeekError;
 
@@ -182,8 +182,52 @@ where
         }
     }
 
+    #[inline]
+    fn samples_remaining(&self) -> Option<usize> {
+        // A sample is only played while more than its duration is left.
+        let per_sample = self.duration_per_sample.as_nanos().max(1);
+        let remaining = self.remaining_duration.as_nanos().saturating_sub(1) / per_sample;
+        let remaining = usize::try_from(remaining).unwrap_or(usize::MAX);
+        // A source of unknown length is taken as endless.
+        match self.input.samples_remaining() {
+            Some(input) => Some(input.min(remaining)),
+            None => Some(remaining),
+        }
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn playback_pos(&self) -> Option<Duration> {
+        self.input.playback_pos()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::source::{SineWave, Source};
+
+    #[test]
+    fn counts_the_samples_left() {
+        let mut taken = SineWave::new(440.0).take_duration(Duration::from_millis(10));
+        let expected = taken.samples_remaining().unwrap();
+        assert_eq!(taken.clone().count(), expected);
+        taken.by_ref().take(100).count();
+        assert_eq!(taken.samples_remaining(), Some(expected - 100));
+    }
 }
@@ -0,0 +1,227 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::SeekError;
+use crate::{Sample, SampleFormat, Source};
+
+/// Internal function that builds the two halves of a `Tee`.
+pub fn tee<I>(input: I, capacity: usize) -> (Tee<I>, Tee<I>)
+where
+    I: Source,
+    I::Item: Sample,
+{
+    // Room for at least one frame.
+    let capacity = capacity.max(input.channels().max(1) as usize);
+    let shared = Arc::new(Mutex::new(Shared {
+        input,
+        buffer: VecDeque::with_capacity(capacity),
+        start: 0,
+        capacity,
+        positions: [0, 0],
+        alive: [true, true],
+    }));
+    let first = Tee {
+        shared: shared.clone(),
+        side: 0,
+    };
+    let second = Tee { shared, side: 1 };
+    (first, second)
+}
+
+/// One of the two sources that play the same samples as another source, made with
+/// [`Source::split`].
+///
+/// Whichever of the two is ahead reads the inner source, and the samples are kept until the
+/// other one plays them too. When the other one falls more than the capacity behind, it skips
+/// the oldest frames it has not played. The one ahead is never held back, so it can feed a
+/// device.
+pub struct Tee<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    shared: Arc<Mutex<Shared<I>>>,
+    // Which of the two halves this is.
+    side: usize,
+}
+
+struct Shared<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // The samples read from the input that one of the halves still has to play.
+    buffer: VecDeque<I::Item>,
+    // Index in the input of the first sample in the buffer.
+    start: u64,
+    capacity: usize,
+    // Index in the input of the next sample of each half.
+    positions: [u64; 2],
+    // A dropped half no longer keeps samples in the buffer.
+    alive: [bool; 2],
+}
+
+impl<I> Shared<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    fn next(&mut self, side: usize) -> Option<I::Item> {
+        // Fell behind and lost the oldest samples.
+        let position = self.positions[side].max(self.start);
+        let offset = (position - self.start) as usize;
+
+        let sample = match self.buffer.get(offset) {
+            Some(&sample) => sample,
+            None => {
+                let sample = self.input.next()?;
+                if self.alive[1 - side] {
+                    self.buffer.push_back(sample);
+                }
+                sample
+            }
+        };
+        self.positions[side] = position + 1;
+        self.trim();
+        Some(sample)
+    }
+
+    // Drops the samples that both halves played, then whole frames if the buffer is too long.
+    fn trim(&mut self) {
+        let played = (0..2)
+            .filter(|&side| self.alive[side])
+            .map(|side| self.positions[side])
+            .min()
+            .unwrap_or(u64::MAX);
+        while self.start < played && self.buffer.pop_front().is_some() {
+            self.start += 1;
+        }
+
+        let channels = self.input.channels().max(1) as usize;
+        while self.buffer.len() > self.capacity {
+            let dropped = channels.min(self.buffer.len());
+            self.buffer.drain(..dropped);
+            self.start += dropped as u64;
+        }
+    }
+}
+
+impl<I> Iterator for Tee<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        self.shared.lock().unwrap().next(self.side)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<I> Source for Tee<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let shared = self.shared.lock().unwrap();
+        let ahead = shared.positions[self.side] >= shared.start + shared.buffer.len() as u64;
+        match ahead {
+            true => shared.input.current_frame_len(),
+            // The format of the buffered samples is not known.
+            false => None,
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.shared.lock().unwrap().input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.shared.lock().unwrap().input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.shared.lock().unwrap().input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.shared.lock().unwrap().input.sample_format()
+    }
+}
+
+impl<I> Drop for Tee<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    fn drop(&mut self) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.alive[self.side] = false;
+            shared.trim();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn both_halves_play_everything() {
+        let data: Vec<i16> = (0..100).collect();
+        let (mut first, second) = SamplesBuffer::new(2, 48000, data.clone()).split(1000);
+        assert_eq!(first.channels(), 2);
+        assert_eq!(second.sample_rate(), 48000);
+
+        let start: Vec<i16> = first.by_ref().take(30).collect();
+        let handle = thread::spawn(move || second.collect::<Vec<_>>());
+        let end: Vec<i16> = first.collect();
+        assert_eq!([start, end].concat(), data);
+        assert_eq!(handle.join().unwrap(), data);
+    }
+
+    #[test]
+    fn slow_half_skips_whole_frames() {
+        let data: Vec<i16> = (0..20).collect();
+        let (first, mut second) = SamplesBuffer::new(2, 48000, data.clone()).split(4);
+        assert_eq!(second.next(), Some(0));
+        assert_eq!(second.next(), Some(1));
+
+        // ten samples ahead, only the last four are kept
+        let played: Vec<i16> = first.take(12).collect();
+        assert_eq!(played, data[..12]);
+        assert_eq!(second.collect::<Vec<_>>(), data[8..]);
+    }
+
+    #[test]
+    fn dropped_half_keeps_nothing() {
+        let (mut first, second) = SamplesBuffer::new(1, 48000, vec![0i16; 100]).split(10);
+        drop(second);
+        assert_eq!(first.by_ref().count(), 100);
+        assert!(first.shared.lock().unwrap().buffer.is_empty());
+    }
+}
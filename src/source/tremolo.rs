@@ -0,0 +1,172 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Tremolo` object.
+pub fn tremolo<I>(input: I, rate_hz: f32, depth: f32) -> Tremolo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Tremolo {
+        input,
+        rate_hz,
+        depth: depth.clamp(0.0, 1.0),
+        phase: 0.0,
+        gain: 1.0,
+        current_channel: 0,
+    }
+}
+
+/// Filter that makes the volume of a source go up and down periodically.
+#[derive(Clone, Debug)]
+pub struct Tremolo<I> {
+    input: I,
+    rate_hz: f32,
+    depth: f32,
+    // Phase of the oscillator, in cycles.
+    phase: f32,
+    // Gain of the current frame.
+    gain: f32,
+    current_channel: u16,
+}
+
+impl<I> Tremolo<I> {
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Tremolo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let sample = self.input.next()?;
+
+        // All the channels of a frame get the same gain.
+        if self.current_channel == 0 {
+            // Starts at full volume, goes down to `1.0 - depth` half a cycle later.
+            self.gain = 1.0 - self.depth * (1.0 - (TAU * self.phase).cos()) / 2.0;
+            self.phase = (self.phase + self.rate_hz / self.input.sample_rate() as f32).fract();
+        }
+        self.current_channel += 1;
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+        }
+
+        Some(sample.amplify(self.gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Tremolo<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Tremolo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.phase = (pos.as_secs_f32() * self.rate_hz).fract();
+        self.current_channel = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn envelope_follows_the_rate() {
+        const RATE: u32 = 1000;
+        let source = SamplesBuffer::new(2, RATE, vec![1.0f32; 2 * RATE as usize]);
+        let output: Vec<f32> = source.tremolo(5.0, 1.0).collect();
+
+        // every frame is scaled as a whole
+        assert!(output.chunks(2).all(|frame| frame[0] == frame[1]));
+        let envelope: Vec<f32> = output.iter().step_by(2).copied().collect();
+        assert_eq!(envelope[0], 1.0);
+        assert!(envelope[100].abs() < 1e-4);
+        assert!((envelope[200] - 1.0).abs() < 1e-4);
+
+        // five cycles in a second, each with a single dip
+        let dips = envelope
+            .windows(3)
+            .filter(|w| w[1] < w[0] && w[1] <= w[2])
+            .count();
+        assert_eq!(dips, 5);
+    }
+
+    #[test]
+    fn depth_limits_the_dip() {
+        let source = SamplesBuffer::new(1, 1000, vec![1.0f32; 1000]);
+        let output: Vec<f32> = source.tremolo(2.0, 0.5).collect();
+        let lowest = output.iter().copied().fold(f32::MAX, f32::min);
+        assert!((lowest - 0.5).abs() < 1e-4, "{lowest}");
+    }
+}
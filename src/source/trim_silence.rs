@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use crate::buffer::{SamplesBuffer, UnboundedSourceError};
+use crate::{Sample, Source};
+
+/// Internal function that reads a source and keeps what is between its silent ends.
+pub fn trim_silence<I>(
+    input: I,
+    threshold_db: f32,
+    max_duration: Option<Duration>,
+) -> Result<SamplesBuffer<I::Item>, UnboundedSourceError>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    // Frames with another format are converted, so that the samples can be stored together.
+    let buffer = SamplesBuffer::<I::Item>::from_source(input, max_duration)?;
+    let channels = buffer.channels();
+    let sample_rate = buffer.sample_rate();
+    let mut samples: Vec<I::Item> = buffer.collect();
+
+    let threshold = 10f32.powf(threshold_db / 20.0);
+    let is_loud = |frame: &[I::Item]| frame.iter().any(|s| s.to_f32().abs() > threshold);
+    let mut frames = samples.chunks_exact(channels as usize);
+    let first = frames.position(is_loud);
+    let last = frames.rposition(is_loud);
+
+    let region = match (first, last) {
+        // `rposition` counts from where `position` stopped.
+        (Some(first), Some(last)) => first..first + 1 + last + 1,
+        (Some(first), None) => first..first + 1,
+        (None, _) => 0..0,
+    };
+    samples.truncate(region.end * channels as usize);
+    samples.drain(..region.start * channels as usize);
+
+    Ok(SamplesBuffer::new(channels, sample_rate, samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::{SamplesBuffer, UnboundedSourceError};
+    use crate::source::{SineWave, Source};
+
+    #[test]
+    fn keeps_only_the_tone() {
+        let tone: Vec<f32> = (0..200)
+            .map(|i| 0.5 * (std::f32::consts::TAU * i as f32 / 20.0 + 0.1).sin())
+            .flat_map(|sample| [sample, -sample])
+            .collect();
+        let mut data = vec![0.0f32; 2 * 100];
+        data.extend(&tone);
+        data.extend(vec![0.0f32; 2 * 50]);
+
+        let trimmed = SamplesBuffer::new(2, 44100, data)
+            .trim_silence(-60.0, None)
+            .unwrap();
+        assert_eq!(trimmed.channels(), 2);
+        assert_eq!(trimmed.collect::<Vec<_>>(), tone);
+    }
+
+    #[test]
+    fn quiet_noise_counts_as_silence() {
+        let data = vec![0.0001f32, 0.0, 0.25, 0.5, 0.0, -0.0001];
+        let trimmed = SamplesBuffer::new(1, 44100, data)
+            .trim_silence(-60.0, None)
+            .unwrap();
+        assert_eq!(trimmed.collect::<Vec<_>>(), vec![0.25, 0.5]);
+    }
+
+    #[test]
+    fn silent_source_is_empty() {
+        let trimmed = SamplesBuffer::new(2, 44100, vec![0i16; 100])
+            .trim_silence(-60.0, None)
+            .unwrap();
+        assert_eq!(trimmed.count(), 0);
+
+        let single = SamplesBuffer::new(1, 44100, vec![0i16, 1000, 0])
+            .trim_silence(-60.0, None)
+            .unwrap();
+        assert_eq!(single.collect::<Vec<_>>(), vec![1000]);
+    }
+
+    #[test]
+    fn rejects_infinite_sources() {
+        let trimmed = SineWave::new(440.0).trim_silence(-60.0, None);
+        assert_eq!(trimmed.unwrap_err(), UnboundedSourceError);
+    }
+
+    #[test]
+    fn reads_infinite_sources_up_to_the_maximum_duration() {
+        let trimmed = SineWave::new(440.0)
+            .trim_silence(-60.0, Some(Duration::from_millis(100)))
+            .unwrap();
+        assert!(trimmed.total_duration().unwrap() <= Duration::from_millis(100));
+        assert!(trimmed.count() > 0);
+    }
+}
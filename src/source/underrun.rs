@@ -0,0 +1,23 @@
+/// What a source fed from another thread plays when that thread has not delivered the next
+/// frame yet, see [`Source::prefetch_with_policy`](crate::Source::prefetch_with_policy) and
+/// [`RingBufferSource::with_policy`](crate::source::RingBufferSource::with_policy).
+///
+/// Every frame that is not there in time counts as an underrun, whatever the policy.
+///
+/// A source that returns `None` is removed by the mixer or the queue that plays it, and does
+/// not come back when the data arrives. The queue of a [`Sink`](crate::Sink) is kept alive:
+/// with `EndStream` it plays silence in place of the source and the device stays open, but
+/// the rest of the stream is lost and the sink moves on to its next source. With
+/// `EmitSilence` and `Repeat` the source stays in the mix and resumes on its own, it only
+/// ends once all of its data has been played.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnderrunPolicy {
+    /// Ends the source, `next` returns `None`.
+    EndStream,
+    /// Plays frames of silence until the data arrives. `next` never returns `None` because of
+    /// an underrun.
+    #[default]
+    EmitSilence,
+    /// Plays the last frame again until the data arrives, silence if nothing was played yet.
+    Repeat,
+}
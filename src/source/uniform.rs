@@ -3,11 +3,25 @@ use std::time::Duration;
 
 use cpal::FromSample;
 
-use crate::conversions::{ChannelCountConverter, DataConverter, SampleRateConverter};
-use crate::{Sample, Source};
+use crate::conversions::{
+    ChannelCountConverter, DataConverter, SampleRateConverter, SincSampleRateConverter,
+};
+use crate::{Sample, SampleFormat, Source};
 
 use super::SeekError;
 
+/// Algorithm used by a [`UniformSourceIterator`] to convert the sample rate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResampleQuality {
+    /// Linear interpolation between neighbouring samples. It is cheap and adds no latency,
+    /// but lets through aliasing, especially when the rates are far apart.
+    #[default]
+    Linear,
+    /// Windowed sinc interpolation. It filters out what the output sample rate can not
+    /// represent, at the cost of more computation and a delay of a few samples.
+    Sinc,
+}
+
 /// An iterator that reads from a `Source` and converts the samples to a
 /// specific type, sample-rate and channels count.
 ///
@@ -20,10 +34,11 @@ where
     I::Item: Sample,
     D: Sample,
 {
-    inner: Option<DataConverter<ChannelCountConverter<SampleRateConverter<Take<I>>>, D>>,
+    inner: Option<DataConverter<Conversion<Take<I>>, D>>,
     target_channels: u16,
     target_sample_rate: u32,
     total_duration: Option<Duration>,
+    resampling: Resampling<I::Item>,
 }
 
 impl<I, D> UniformSourceIterator<I, D>
@@ -34,20 +49,87 @@ where
 {
     /// Wrap a `Source` and lazily convert its samples to a specific type,
     /// sample-rate and channels count.
+    ///
+    /// The sample rate is converted with [`ResampleQuality::Linear`].
+    ///
+    /// # Panic
+    ///
+    /// Panics if `target_channels` or `target_sample_rate` is 0.
     #[inline]
     pub fn new(
         input: I,
         target_channels: u16,
         target_sample_rate: u32,
     ) -> UniformSourceIterator<I, D> {
+        UniformSourceIterator::with_resampling(
+            input,
+            target_channels,
+            target_sample_rate,
+            Resampling::Linear,
+        )
+    }
+
+    /// Same as [`new`](UniformSourceIterator::new), but converts the sample rate with the
+    /// given algorithm.
+    ///
+    /// The sinc filter keeps its history from one frame of the source to the next, as long as
+    /// the channels and the sample rate stay the same. It only starts over when they change.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `target_channels` or `target_sample_rate` is 0.
+    #[inline]
+    pub fn new_with_quality(
+        input: I,
+        target_channels: u16,
+        target_sample_rate: u32,
+        quality: ResampleQuality,
+    ) -> UniformSourceIterator<I, D>
+    where
+        I::Item: FromSample<f32>,
+    {
+        let resampling = match quality {
+            ResampleQuality::Linear => Resampling::Linear,
+            ResampleQuality::Sinc => Resampling::Sinc(<I::Item as FromSample<f32>>::from_sample_),
+        };
+        UniformSourceIterator::with_resampling(
+            input,
+            target_channels,
+            target_sample_rate,
+            resampling,
+        )
+    }
+
+    #[inline]
+    fn with_resampling(
+        input: I,
+        target_channels: u16,
+        target_sample_rate: u32,
+        resampling: Resampling<I::Item>,
+    ) -> UniformSourceIterator<I, D> {
+        assert!(
+            target_channels >= 1,
+            "UniformSourceIterator requires at least one target channel"
+        );
+        assert!(
+            target_sample_rate >= 1,
+            "UniformSourceIterator requires a non-zero target sample rate"
+        );
+
         let total_duration = input.total_duration();
-        let input = UniformSourceIterator::bootstrap(input, target_channels, target_sample_rate);
+        let input = UniformSourceIterator::bootstrap(
+            input,
+            target_channels,
+            target_sample_rate,
+            resampling,
+        );
 
         UniformSourceIterator {
             inner: Some(input),
             target_channels,
             target_sample_rate,
             total_duration,
+            resampling,
         }
     }
 
@@ -56,26 +138,51 @@ where
         input: I,
         target_channels: u16,
         target_sample_rate: u32,
-    ) -> DataConverter<ChannelCountConverter<SampleRateConverter<Take<I>>>, D> {
+        resampling: Resampling<I::Item>,
+    ) -> DataConverter<Conversion<Take<I>>, D> {
         // Limit the frame length to something reasonable
         let frame_len = input.current_frame_len().map(|x| x.min(32768));
 
         let from_channels = input.channels();
         let from_sample_rate = input.sample_rate();
 
+        // The sinc filter needs the frames that come before and after, so it goes on through
+        // the frames that keep the same format instead of starting over at each one.
+        let keep_format = match &resampling {
+            Resampling::Sinc(_) => Some((from_channels, from_sample_rate)),
+            Resampling::Linear => None,
+        };
         let input = Take {
             iter: input,
             n: frame_len,
+            keep_format,
+        };
+
+        // The frame already has the right format, only the sample type may change.
+        if from_channels == target_channels && from_sample_rate == target_sample_rate {
+            return DataConverter::new(Conversion::Direct(input));
+        }
+
+        let from_sample_rate = cpal::SampleRate(from_sample_rate);
+        let target_sample_rate = cpal::SampleRate(target_sample_rate);
+        let input = match resampling {
+            Resampling::Linear => Resampler::Linear(SampleRateConverter::new(
+                input,
+                from_sample_rate,
+                target_sample_rate,
+                from_channels,
+            )),
+            Resampling::Sinc(from_f32) => Resampler::Sinc(SincSampleRateConverter::new(
+                input,
+                from_sample_rate,
+                target_sample_rate,
+                from_channels,
+                from_f32,
+            )),
         };
-        let input = SampleRateConverter::new(
-            input,
-            cpal::SampleRate(from_sample_rate),
-            cpal::SampleRate(target_sample_rate),
-            from_channels,
-        );
         let input = ChannelCountConverter::new(input, from_channels, target_channels);
 
-        DataConverter::new(input)
+        DataConverter::new(Conversion::Converted(input))
     }
 }
 
@@ -93,17 +200,14 @@ where
             return Some(value);
         }
 
-        let input = self
-            .inner
-            .take()
-            .unwrap()
-            .into_inner()
-            .into_inner()
-            .into_inner()
-            .iter;
+        let input = self.inner.take().unwrap().into_inner().into_inner().iter;
 
-        let mut input =
-            UniformSourceIterator::bootstrap(input, self.target_channels, self.target_sample_rate);
+        let mut input = UniformSourceIterator::bootstrap(
+            input,
+            self.target_channels,
+            self.target_sample_rate,
+            self.resampling,
+        );
 
         let value = input.next();
         self.inner = Some(input);
@@ -142,17 +246,179 @@ where
         self.total_duration
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.inner
+            .as_ref()
+            .is_none_or(|input| input.inner().inner().inner().is_seekable())
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
-        if let Some(input) = self.inner.as_mut() {
-            input
-                .inner_mut()
-                .inner_mut()
-                .inner_mut()
-                .inner_mut()
-                .try_seek(pos)
-        } else {
-            Ok(())
+        let Some(input) = self.inner.as_mut() else {
+            return Ok(());
+        };
+        input.inner_mut().inner_mut().inner_mut().try_seek(pos)?;
+
+        // The converters may be in the middle of a frame, they start over at the new position.
+        let input = self.inner.take().unwrap().into_inner().into_inner().iter;
+        self.inner = Some(UniformSourceIterator::bootstrap(
+            input,
+            self.target_channels,
+            self.target_sample_rate,
+            self.resampling,
+        ));
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        D::FORMAT
+    }
+}
+
+/// Algorithm chosen for the sample rate conversion, kept to build the converter of each
+/// frame.
+enum Resampling<S> {
+    Linear,
+    // The sinc filter computes in `f32` and converts back with this.
+    Sinc(fn(f32) -> S),
+}
+
+// Deriving would needlessly require `S: Clone`
+impl<S> Clone for Resampling<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for Resampling<S> {}
+
+/// Converts the channels and sample rate of a frame, unless they already match the target.
+#[derive(Clone)]
+enum Conversion<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    Direct(I),
+    Converted(ChannelCountConverter<Resampler<I>>),
+}
+
+impl<I> Conversion<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    #[inline]
+    fn into_inner(self) -> I {
+        match self {
+            Conversion::Direct(input) => input,
+            Conversion::Converted(converter) => converter.into_inner().into_inner(),
+        }
+    }
+
+    #[inline]
+    fn inner(&self) -> &I {
+        match self {
+            Conversion::Direct(input) => input,
+            Conversion::Converted(converter) => converter.inner().inner(),
+        }
+    }
+
+    #[inline]
+    fn inner_mut(&mut self) -> &mut I {
+        match self {
+            Conversion::Direct(input) => input,
+            Conversion::Converted(converter) => converter.inner_mut().inner_mut(),
+        }
+    }
+}
+
+impl<I> Iterator for Conversion<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        match self {
+            Conversion::Direct(input) => input.next(),
+            Conversion::Converted(converter) => converter.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Conversion::Direct(input) => input.size_hint(),
+            Conversion::Converted(converter) => converter.size_hint(),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Resampler<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    Linear(SampleRateConverter<I>),
+    Sinc(SincSampleRateConverter<I>),
+}
+
+impl<I> Resampler<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    #[inline]
+    fn into_inner(self) -> I {
+        match self {
+            Resampler::Linear(converter) => converter.into_inner(),
+            Resampler::Sinc(converter) => converter.into_inner(),
+        }
+    }
+
+    #[inline]
+    fn inner(&self) -> &I {
+        match self {
+            Resampler::Linear(converter) => converter.inner(),
+            Resampler::Sinc(converter) => converter.inner(),
+        }
+    }
+
+    #[inline]
+    fn inner_mut(&mut self) -> &mut I {
+        match self {
+            Resampler::Linear(converter) => converter.inner_mut(),
+            Resampler::Sinc(converter) => converter.inner_mut(),
+        }
+    }
+}
+
+impl<I> Iterator for Resampler<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        match self {
+            Resampler::Linear(converter) => converter.next(),
+            Resampler::Sinc(converter) => converter.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Resampler::Linear(converter) => converter.size_hint(),
+            Resampler::Sinc(converter) => converter.size_hint(),
         }
     }
 }
@@ -161,38 +427,62 @@ where
 struct Take<I> {
     iter: I,
     n: Option<usize>,
+    // Set to go on with the next frames for as long as they have this format.
+    keep_format: Option<(u16, u32)>,
 }
 
 impl<I> Take<I> {
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.iter
+    }
+
     #[inline]
     pub fn inner_mut(&mut self) -> &mut I {
         &mut self.iter
     }
 }
 
+impl<I> Take<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    // Moves on to the next frame of the source if it keeps the format, returns false if not.
+    fn next_frame(&mut self) -> bool {
+        match self.keep_format {
+            Some((channels, sample_rate))
+                if self.iter.channels() == channels && self.iter.sample_rate() == sample_rate =>
+            {
+                self.n = self.iter.current_frame_len().map(|x| x.min(32768));
+                self.n != Some(0)
+            }
+            _ => false,
+        }
+    }
+}
+
 impl<I> Iterator for Take<I>
 where
-    I: Iterator,
+    I: Source,
+    I::Item: Sample,
 {
     type Item = <I as Iterator>::Item;
 
     #[inline]
     fn next(&mut self) -> Option<<I as Iterator>::Item> {
+        if self.n == Some(0) && !self.next_frame() {
+            return None;
+        }
         if let Some(n) = &mut self.n {
-            if *n != 0 {
-                *n -= 1;
-                self.iter.next()
-            } else {
-                None
-            }
-        } else {
-            self.iter.next()
+            *n -= 1;
         }
+        self.iter.next()
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        if let Some(n) = self.n {
+        if let (Some(n), None) = (self.n, self.keep_format) {
             let (lower, upper) = self.iter.size_hint();
 
             let lower = cmp::min(lower, n);
@@ -209,4 +499,176 @@ where
     }
 }
 
-impl<I> ExactSizeIterator for Take<I> where I: ExactSizeIterator {}
+impl<I> ExactSizeIterator for Take<I>
+where
+    I: ExactSizeIterator + Source,
+    I::Item: Sample,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::{ResampleQuality, UniformSourceIterator};
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{from_iter, Source};
+
+    const FFT_LEN: usize = 4096;
+
+    fn fft(re: &mut [f64], im: &mut [f64]) {
+        let n = re.len();
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j |= bit;
+            if i < j {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= n {
+            let angle = -2.0 * PI / len as f64;
+            for start in (0..n).step_by(len) {
+                for k in 0..len / 2 {
+                    let (wi, wr) = (angle * k as f64).sin_cos();
+                    let (a, b) = (start + k, start + k + len / 2);
+                    let tr = re[b] * wr - im[b] * wi;
+                    let ti = re[b] * wi + im[b] * wr;
+                    re[b] = re[a] - tr;
+                    im[b] = im[a] - ti;
+                    re[a] += tr;
+                    im[a] += ti;
+                }
+            }
+            len <<= 1;
+        }
+    }
+
+    /// Ratio between the energy away from `frequency` and the energy around it.
+    fn alias_ratio(samples: &[f32], sample_rate: f64, frequency: f64) -> f64 {
+        // The Hann window keeps the tone from leaking into far away bins.
+        let mut re: Vec<f64> = samples
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let window = 0.5 - 0.5 * (2.0 * PI * i as f64 / FFT_LEN as f64).cos();
+                *s as f64 * window
+            })
+            .collect();
+        let mut im = vec![0.0; FFT_LEN];
+        fft(&mut re, &mut im);
+
+        let tone_bin = (frequency / sample_rate * FFT_LEN as f64).round() as usize;
+        let (mut tone, mut rest) = (0.0, 0.0);
+        for bin in 0..FFT_LEN / 2 {
+            let power = re[bin] * re[bin] + im[bin] * im[bin];
+            if bin.abs_diff(tone_bin) <= 8 {
+                tone += power;
+            } else {
+                rest += power;
+            }
+        }
+        rest / tone
+    }
+
+    fn sine() -> Vec<f32> {
+        (0..44100)
+            .map(|i| ((2.0 * PI * 15000.0 * i as f64 / 44100.0).sin() * 0.5) as f32)
+            .collect()
+    }
+
+    fn resampled<S>(source: S, quality: ResampleQuality) -> Vec<f32>
+    where
+        S: Source<Item = f32>,
+    {
+        UniformSourceIterator::<_, f32>::new_with_quality(source, 1, 48000, quality)
+            .skip(10000)
+            .take(FFT_LEN)
+            .collect()
+    }
+
+    fn resampled_sine(quality: ResampleQuality) -> Vec<f32> {
+        resampled(SamplesBuffer::new(1, 44100, sine()), quality)
+    }
+
+    #[test]
+    fn sinc_resampling_has_little_aliasing() {
+        let sinc = alias_ratio(&resampled_sine(ResampleQuality::Sinc), 48000.0, 15000.0);
+        assert!(sinc < 1e-5, "aliasing ratio {}", sinc);
+
+        // linear interpolation folds back much more of the images of the tone
+        let linear = alias_ratio(&resampled_sine(ResampleQuality::Linear), 48000.0, 15000.0);
+        assert!(linear > 1e-3, "aliasing ratio {}", linear);
+    }
+
+    #[test]
+    fn sinc_resampling_goes_on_across_frames() {
+        // the same sine, in frames of 64 samples
+        let frames: Vec<_> = sine()
+            .chunks(64)
+            .map(|chunk| SamplesBuffer::new(1, 44100, chunk.to_vec()))
+            .collect();
+        let source = from_iter(frames);
+        assert_eq!(source.current_frame_len(), Some(64));
+
+        let chunked = resampled(source, ResampleQuality::Sinc);
+        let whole = resampled_sine(ResampleQuality::Sinc);
+        assert_eq!(chunked.len(), whole.len());
+        for (a, b) in chunked.iter().zip(&whole) {
+            assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn matching_format_is_untouched() {
+        let data: Vec<i16> = (0..1000).map(|i| i * 31 - 15000).collect();
+        let source = SamplesBuffer::new(2, 44100, data.clone());
+        let output: Vec<i16> = UniformSourceIterator::<_, i16>::new_with_quality(
+            source,
+            2,
+            44100,
+            ResampleQuality::Sinc,
+        )
+        .collect();
+        assert_eq!(output, data);
+
+        // only the sample type is converted
+        let source = SamplesBuffer::new(2, 44100, data.clone());
+        let output: Vec<f32> = UniformSourceIterator::<_, f32>::new(source, 2, 44100).collect();
+        let expected: Vec<f32> = SamplesBuffer::new(2, 44100, data)
+            .convert_samples()
+            .collect();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn seek_in_the_middle_of_a_frame() {
+        let source = SamplesBuffer::new(1, 1, vec![1i16, 2, 3, 4]);
+        let mut uniform = UniformSourceIterator::<_, i16>::new(source, 2, 1);
+        assert_eq!(uniform.next(), Some(1));
+
+        uniform.try_seek(std::time::Duration::from_secs(2)).unwrap();
+        assert_eq!(uniform.collect::<Vec<_>>(), vec![3, 3, 4, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one target channel")]
+    fn panic_if_zero_target_channels() {
+        let source = SamplesBuffer::new(1, 44100, vec![0i16; 4]);
+        UniformSourceIterator::<_, i16>::new(source, 0, 44100);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero target sample rate")]
+    fn panic_if_zero_target_sample_rate() {
+        let source = SamplesBuffer::new(1, 44100, vec![0i16; 4]);
+        UniformSourceIterator::<_, i16>::new(source, 1, 0);
+    }
+}
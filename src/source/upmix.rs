@@ -0,0 +1,138 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use crate::{Sample, Source};
+
+use super::{ChannelLayout, ChannelMatrix};
+
+/// Internal function that builds a `Upmix` object.
+pub fn upmix<I>(input: I, layout: ChannelLayout) -> Upmix<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let from = ChannelLayout::from_channels(input.channels()).unwrap_or_else(|| {
+        panic!(
+            "no known channel layout for a source with {} channels",
+            input.channels()
+        )
+    });
+    let coefficients = upmix_coefficients(from, layout);
+    ChannelMatrix::new(input, coefficients)
+}
+
+/// Weight of every input channel in each output channel.
+///
+/// Channels that exist in both layouts are copied. The channels that have no counterpart are
+/// filled from the nearest ones. An input channel played on `n` speakers is attenuated by
+/// `1 / sqrt(n)` so the overall loudness stays the same.
+fn upmix_coefficients(from: ChannelLayout, to: ChannelLayout) -> Vec<Vec<f32>> {
+    use ChannelLayout::*;
+    const H: f32 = FRAC_1_SQRT_2;
+    // a third of the power of a channel, for the ones played on three speakers
+    const T: f32 = 0.577_350_26;
+
+    assert!(
+        from.channels() <= to.channels(),
+        "can not upmix {:?} to {:?}, downmix it instead",
+        from,
+        to
+    );
+
+    let mut coefficients = vec![vec![0.0; from.channels() as usize]; to.channels() as usize];
+    let mut set = |output: usize, input: usize, weight: f32| coefficients[output][input] = weight;
+
+    match (from, to) {
+        _ if from == to => (0..from.channels() as usize).for_each(|c| set(c, c, 1.0)),
+        (Mono, Stereo) => {
+            set(0, 0, H);
+            set(1, 0, H);
+        }
+        (Mono, Quad) => (0..4).for_each(|c| set(c, 0, 0.5)),
+        // the center speaker is the natural place for a single channel
+        (Mono, _) => set(2, 0, 1.0),
+        (Stereo, Quad) => {
+            set(0, 0, H);
+            set(1, 1, H);
+            set(2, 0, H);
+            set(3, 1, H);
+        }
+        (Stereo, Surround5_1) => {
+            set(0, 0, H);
+            set(1, 1, H);
+            set(4, 0, H);
+            set(5, 1, H);
+        }
+        (Stereo, _) => {
+            set(0, 0, T);
+            set(1, 1, T);
+            set(4, 0, T);
+            set(5, 1, T);
+            set(6, 0, T);
+            set(7, 1, T);
+        }
+        (Quad, Surround5_1) => {
+            set(0, 0, 1.0);
+            set(1, 1, 1.0);
+            set(4, 2, 1.0);
+            set(5, 3, 1.0);
+        }
+        (Quad, _) => (0..4).for_each(|c| set(if c < 2 { c } else { c + 2 }, c, 1.0)),
+        // 5.1 to 7.1, the side channels stay on the sides.
+        _ => {
+            (0..4).for_each(|c| set(c, c, 1.0));
+            set(6, 4, 1.0);
+            set(7, 5, 1.0);
+        }
+    }
+    coefficients
+}
+
+/// Spreads the channels of a source over more speakers.
+pub type Upmix<I> = ChannelMatrix<I>;
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{ChannelLayout, Source};
+
+    #[test]
+    fn mono_to_stereo() {
+        let source = SamplesBuffer::new(1, 48000, vec![0.5f32, -1.0]);
+        let upmixed = source.upmix(ChannelLayout::Stereo);
+        assert_eq!(upmixed.channels(), 2);
+
+        let output: Vec<f32> = upmixed.collect();
+        let expected = [0.5, 0.5, -1.0, -1.0].map(|s| s * FRAC_1_SQRT_2);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn stereo_to_quad() {
+        let source = SamplesBuffer::new(2, 48000, vec![0.5f32, 0.25, 0.1, 0.2]);
+        let upmixed = source.upmix(ChannelLayout::Quad);
+        assert_eq!(upmixed.channels(), 4);
+
+        let output: Vec<f32> = upmixed.collect();
+        let h = FRAC_1_SQRT_2;
+        let expected = [0.5, 0.25, 0.5, 0.25, 0.1, 0.2, 0.1, 0.2].map(|s| s * h);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn stereo_to_surround_keeps_the_power() {
+        for layout in [ChannelLayout::Surround5_1, ChannelLayout::Surround7_1] {
+            let source = SamplesBuffer::new(2, 48000, vec![1.0f32, 0.0]);
+            let power: f32 = source.upmix(layout).map(|s| s * s).sum();
+            assert!((power - 1.0).abs() < 1e-6, "{:?}: {}", layout, power);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn can_not_reduce_channels() {
+        let source = SamplesBuffer::new(2, 48000, vec![0i16; 4]);
+        let _ = source.upmix(ChannelLayout::Mono);
+    }
+}
@@ -0,0 +1,318 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::SeekError;
+
+/// Frames kept to interpolate between: the one before the position, the two around it and
+/// the one after.
+const HISTORY: usize = 4;
+
+/// Internal function that builds a `VariableSpeed` object.
+pub fn variable_speed<I>(input: I, ratio: f32) -> VariableSpeed<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    assert_ratio(ratio);
+    VariableSpeed {
+        input,
+        ratio: Arc::new(AtomicU32::new(ratio.to_bits())),
+        history: Vec::new(),
+        channels: 0,
+        padding: 0,
+        position: 0.0,
+        frame: Vec::new(),
+        current_channel: 0,
+    }
+}
+
+/// Filter that plays a source at a speed that can change continuously while it plays, see
+/// [`Source::variable_speed`].
+#[derive(Debug)]
+pub struct VariableSpeed<I> {
+    input: I,
+    ratio: Arc<AtomicU32>,
+    // The last `HISTORY` frames of the input, oldest first, as f32.
+    history: Vec<f32>,
+    channels: usize,
+    // How many of the last frames of the history are silence added after the input ended.
+    padding: usize,
+    // Position between the second and the third frame of the history, in [0, 1).
+    position: f64,
+    // The current output frame.
+    frame: Vec<f32>,
+    current_channel: usize,
+}
+
+/// Changes the speed of a [`VariableSpeed`] while it plays, from any thread.
+#[derive(Clone, Debug)]
+pub struct VariableSpeedController {
+    ratio: Arc<AtomicU32>,
+}
+
+impl VariableSpeedController {
+    /// Sets the speed, `2.0` plays twice as fast and an octave higher. It takes effect from
+    /// the next frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` is not a positive number.
+    #[inline]
+    pub fn set_ratio(&self, ratio: f32) {
+        assert_ratio(ratio);
+        self.ratio.store(ratio.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the speed.
+    #[inline]
+    pub fn ratio(&self) -> f32 {
+        f32::from_bits(self.ratio.load(Ordering::Relaxed))
+    }
+}
+
+fn assert_ratio(ratio: f32) {
+    assert!(
+        ratio > 0.0 && ratio.is_finite(),
+        "the speed must be a positive number, got {ratio}"
+    );
+}
+
+impl<I> VariableSpeed<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    /// Returns a controller that changes the speed of this source.
+    #[inline]
+    pub fn controller(&self) -> VariableSpeedController {
+        VariableSpeedController {
+            ratio: self.ratio.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    // Forgets the frames read, and reads the first ones of the input from there.
+    fn reset(&mut self) {
+        self.channels = self.input.channels().max(1) as usize;
+        self.history = vec![0.0; HISTORY * self.channels];
+        self.frame = vec![0.0; self.channels];
+        self.padding = 0;
+        self.position = 0.0;
+        // The frame before the first one is silent.
+        for _ in 1..HISTORY {
+            self.read_frame();
+        }
+    }
+
+    // Drops the oldest frame of the history and reads a new one.
+    fn read_frame(&mut self) {
+        self.history.copy_within(self.channels.., 0);
+        let newest = (HISTORY - 1) * self.channels;
+        if self.padding > 0 {
+            self.history[newest..].fill(0.0);
+            self.padding += 1;
+            return;
+        }
+        for (i, sample) in self.history[newest..].iter_mut().enumerate() {
+            match self.input.next() {
+                Some(value) => *sample = value.to_f32(),
+                // A frame cut short is completed with silence.
+                None if i > 0 => *sample = 0.0,
+                None => {
+                    self.history[newest..].fill(0.0);
+                    self.padding = 1;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl<I> Iterator for VariableSpeed<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel == 0 {
+            if self.history.is_empty() {
+                self.reset();
+            }
+            // Once the frame the position is in is silence added after the end.
+            if self.padding >= HISTORY - 1 {
+                return None;
+            }
+
+            let t = self.position as f32;
+            for (channel, output) in self.frame.iter_mut().enumerate() {
+                let sample = |frame: usize| self.history[frame * self.channels + channel];
+                *output = hermite(sample(0), sample(1), sample(2), sample(3), t);
+            }
+
+            let ratio = f32::from_bits(self.ratio.load(Ordering::Relaxed));
+            self.position += ratio as f64;
+            while self.position >= 1.0 && self.padding < HISTORY - 1 {
+                self.position -= 1.0;
+                self.read_frame();
+            }
+        }
+
+        let sample = self.frame[self.current_channel];
+        self.current_channel += 1;
+        if self.current_channel >= self.channels {
+            self.current_channel = 0;
+        }
+        Some(I::Item::from_sample_(sample))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+/// Interpolates between `x0` and `x1` at `t`, with a cubic Hermite spline that also goes
+/// through `x_1` before and `x2` after.
+#[inline]
+fn hermite(x_1: f32, x0: f32, x1: f32, x2: f32, t: f32) -> f32 {
+    let c1 = 0.5 * (x1 - x_1);
+    let c2 = x_1 - 2.5 * x0 + 2.0 * x1 - 0.5 * x2;
+    let c3 = 0.5 * (x2 - x_1) + 1.5 * (x0 - x1);
+    ((c3 * t + c2) * t + c1) * t + x0
+}
+
+impl<I> Source for VariableSpeed<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    /// `pos` is a position in the inner source, before the change of speed.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.history.clear();
+        self.current_channel = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn plays_at_the_ratio() {
+        let data: Vec<f32> = (0..100).map(|i| i as f32).collect();
+        let output: Vec<f32> = SamplesBuffer::new(1, 1000, data.clone())
+            .variable_speed(1.0)
+            .collect();
+        assert_eq!(output, data);
+
+        let output: Vec<f32> = SamplesBuffer::new(1, 1000, data)
+            .variable_speed(0.5)
+            .collect();
+        assert_eq!(output.len(), 200);
+        // a ramp stays a ramp, the frames in between are interpolated
+        for (i, &sample) in output.iter().enumerate().take(196).skip(2) {
+            assert!((sample - i as f32 / 2.0).abs() < 1e-4, "{i} {sample}");
+        }
+    }
+
+    #[test]
+    fn sweeping_the_ratio() {
+        // a whole number of periods, so that the sine ends on silence
+        const FRAMES: usize = 9600;
+        let data: Vec<f32> = (0..FRAMES * 2)
+            .map(|i| (TAU * 100.0 * (i / 2) as f32 / 48000.0).sin())
+            .collect();
+        let source = SamplesBuffer::new(2, 48000, data).variable_speed(0.5);
+        let controller = source.controller();
+
+        let mut source = source;
+        let mut consumed = 0.0;
+        let mut previous = 0.0f32;
+        let mut frames = 0;
+        while let Some(left) = source.next() {
+            let right = source.next().unwrap();
+            assert_eq!(left, right);
+            // the sine is slow enough that any glitch would be a jump
+            assert!((left - previous).abs() < 0.05, "{frames} {left} {previous}");
+            previous = left;
+            consumed += controller.ratio() as f64;
+            frames += 1;
+            // from half speed to twice as fast and back, every 1000 frames
+            let phase = (frames % 2000) as f32 / 1000.0;
+            let bend = if phase < 1.0 { phase } else { 2.0 - phase };
+            controller.set_ratio(0.5 * 4f32.powf(bend));
+        }
+        // every frame of the input was played once
+        assert!((consumed - FRAMES as f64).abs() < 2.0, "{consumed}");
+    }
+
+    #[test]
+    #[should_panic(expected = "positive number")]
+    fn rejects_a_stop() {
+        SamplesBuffer::new(1, 1000, vec![0.0f32; 4])
+            .variable_speed(1.0)
+            .controller()
+            .set_ratio(0.0);
+    }
+}
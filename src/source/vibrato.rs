@@ -0,0 +1,204 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::{Sample, SampleFormat, Source};
+
+use super::delay_line::DelayLine;
+use super::SeekError;
+
+/// Internal function that builds a `Vibrato` object.
+pub fn vibrato<I>(input: I, rate_hz: f32, depth: Duration) -> Vibrato<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    let mut vibrato = Vibrato {
+        input,
+        rate_hz,
+        depth,
+        phase: 0.0,
+        delay: DelayLine::new(0, 0.0),
+        current_channel: 0,
+        delay_frames: 0.0,
+    };
+    vibrato.reset();
+    vibrato
+}
+
+/// Filter that makes the pitch of a source waver, by reading it through a delay that keeps
+/// changing.
+#[derive(Clone, Debug)]
+pub struct Vibrato<I> {
+    input: I,
+    rate_hz: f32,
+    depth: Duration,
+    // Phase of the oscillator, in cycles.
+    phase: f32,
+    // The last input frames, with room for the longest delay.
+    delay: DelayLine,
+    current_channel: usize,
+    // Delay of the current frame, in frames.
+    delay_frames: f32,
+}
+
+impl<I> Vibrato<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    // Forgets the previous samples, and adapts to the format of the input.
+    fn reset(&mut self) {
+        let channels = self.input.channels().max(1) as usize;
+        let depth_frames = self.depth.as_secs_f32() * self.input.sample_rate() as f32;
+        self.delay = DelayLine::new(channels, depth_frames);
+        self.current_channel = 0;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Vibrato<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.current_channel == 0 {
+            // The format can only change between frames.
+            if self.delay.channels() != self.input.channels().max(1) as usize {
+                self.reset();
+            }
+            let sample_rate = self.input.sample_rate() as f32;
+            // Goes from no delay to `depth` and back once per cycle.
+            let swing = (1.0 - (TAU * self.phase).cos()) / 2.0;
+            self.delay_frames = self.depth.as_secs_f32() * sample_rate * swing;
+            self.phase = (self.phase + self.rate_hz / sample_rate).fract();
+        }
+
+        let sample = self.input.next()?.to_f32();
+        self.delay.write(self.current_channel, sample);
+        let output = self.delay.read(self.current_channel, self.delay_frames);
+
+        self.current_channel += 1;
+        if self.current_channel >= self.delay.channels() {
+            self.current_channel = 0;
+            self.delay.advance();
+        }
+        Some(I::Item::from_sample_(output))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Vibrato<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample + FromSample<f32>,
+{
+}
+
+impl<I> Source for Vibrato<I>
+where
+    I: Source,
+    I::Item: Sample + FromSample<f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.reset();
+        self.phase = (pos.as_secs_f32() * self.rate_hz).fract();
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn no_depth_passes_through() {
+        let data: Vec<f32> = (0..100).map(|i| i as f32 / 100.0).collect();
+        let source = SamplesBuffer::new(2, 1000, data.clone());
+        let output: Vec<f32> = source.vibrato(5.0, Duration::ZERO).collect();
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn delay_stays_within_depth() {
+        const RATE: u32 = 1000;
+        // on a ramp, the output tells how long the delay is
+        let ramp: Vec<f32> = (0..RATE).map(|i| i as f32).collect();
+        let source = SamplesBuffer::new(1, RATE, ramp);
+        let output: Vec<f32> = source.vibrato(4.0, Duration::from_millis(10)).collect();
+
+        let delays: Vec<f32> = output
+            .iter()
+            .enumerate()
+            .skip(10)
+            .map(|(i, sample)| i as f32 - sample)
+            .collect();
+        assert!(delays
+            .iter()
+            .all(|&delay| (-1e-3..=10.001).contains(&delay)));
+        // the longest delay is half a cycle in, at 125 ms
+        assert!((delays[115] - 10.0).abs() < 1e-3, "{}", delays[115]);
+        assert!(delays[240].abs() < 1e-2, "{}", delays[240]);
+    }
+}
@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use super::SeekError;
+use crate::{Sample, SampleFormat, Source};
+
+/// Internal function that builds a `VolumeRamp` object.
+pub fn volume_ramp<I>(input: I, target: f32, duration: Duration) -> VolumeRamp<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let mut ramp = VolumeRamp {
+        input,
+        gain: 1.0,
+        target: 1.0,
+        step: 0.0,
+        remaining_frames: 0,
+        current_channel: 0,
+    };
+    ramp.set_target(target, duration);
+    ramp
+}
+
+/// Filter that linearly moves the volume of a source to a target, then holds it there.
+///
+/// The target can be changed at any time with [`VolumeRamp::set_target`], the new ramp then
+/// starts from the volume the sound is currently playing at.
+#[derive(Clone, Debug)]
+pub struct VolumeRamp<I> {
+    input: I,
+    // Gain applied to the current frame.
+    gain: f32,
+    target: f32,
+    // Change of the gain from one frame to the next.
+    step: f32,
+    remaining_frames: u64,
+    current_channel: u16,
+}
+
+impl<I> VolumeRamp<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Starts moving the volume from its current value to `target` over `duration`.
+    ///
+    /// This replaces the ramp in progress, if any. A zero `duration` sets the volume
+    /// immediately.
+    pub fn set_target(&mut self, target: f32, duration: Duration) {
+        let frames = (duration.as_secs_f64() * self.input.sample_rate() as f64).round() as u64;
+        self.target = target;
+        self.remaining_frames = frames;
+        if frames == 0 {
+            self.gain = target;
+            self.step = 0.0;
+        } else {
+            self.step = (target - self.gain) / frames as f32;
+        }
+    }
+
+    /// Returns the volume the ramp is heading to.
+    #[inline]
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Returns the volume the current frame is played at.
+    #[inline]
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    fn next_frame(&mut self) {
+        match self.remaining_frames {
+            0 => (),
+            1 => {
+                // avoids accumulating rounding errors
+                self.gain = self.target;
+                self.remaining_frames = 0;
+            }
+            _ => {
+                self.gain += self.step;
+                self.remaining_frames -= 1;
+            }
+        }
+    }
+}
+
+impl<I> Iterator for VolumeRamp<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let sample = self.input.next()?.amplify(self.gain);
+
+        // The gain only changes between frames so all channels stay in sync.
+        self.current_channel += 1;
+        if self.current_channel >= self.input.channels() {
+            self.current_channel = 0;
+            self.next_frame();
+        }
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for VolumeRamp<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for VolumeRamp<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // Jumping elsewhere in the sound skips the rest of the ramp.
+        self.gain = self.target;
+        self.remaining_frames = 0;
+        self.current_channel = 0;
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_format(&self) -> SampleFormat {
+        self.input.sample_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn ramps_then_holds() {
+        let source = SamplesBuffer::new(1, 4, vec![1.0f32; 7]);
+        let ramp = source.volume_ramp(0.0, Duration::from_secs(1));
+        let output: Vec<f32> = ramp.collect();
+        assert_eq!(output, vec![1.0, 0.75, 0.5, 0.25, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn frames_share_gain() {
+        let source = SamplesBuffer::new(2, 2, vec![1.0f32, -1.0, 1.0, -1.0, 1.0, -1.0]);
+        let ramp = source.volume_ramp(0.0, Duration::from_secs(1));
+        let output: Vec<f32> = ramp.collect();
+        assert_eq!(output, vec![1.0, -1.0, 0.5, -0.5, 0.0, -0.0]);
+    }
+
+    #[test]
+    fn new_target_starts_from_current_gain() {
+        let source = SamplesBuffer::new(1, 4, vec![1.0f32; 6]);
+        let mut ramp = source.volume_ramp(0.0, Duration::from_secs(1));
+        assert_eq!(ramp.next(), Some(1.0));
+        assert_eq!(ramp.next(), Some(0.75));
+
+        ramp.set_target(1.0, Duration::from_millis(500));
+        assert_eq!(ramp.next(), Some(0.5));
+        assert_eq!(ramp.next(), Some(0.75));
+        assert_eq!(ramp.next(), Some(1.0));
+        assert_eq!(ramp.next(), Some(1.0));
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::source::UniformSourceIterator;
+use crate::{Sample, SampleFormat, Source};
+
+/// Renders a source to a PCM WAV file at `path`, without using an audio device.
+///
+/// The channels count and sample rate of the file are those of the source when this function
+/// is called. If the source changes them midway through, the rest of it is converted to match.
+///
+/// The samples are stored in the [`sample_format`](Source::sample_format) of the source: 8
+/// bits for `I8` and `U8`, 32 bits for the wider integers, 32 bits floats for `F32` and `F64`,
+/// and 16 bits for `I16`, `U16` and the other formats.
+///
+/// The samples are written as they are pulled out of the source, so long sources do not need
+/// to fit in memory. Rendering stops when the source ends or after `max_duration`, whichever
+/// comes first. A source that reports no [`total_duration`](Source::total_duration) may never
+/// end, so it is rejected with [`RenderError::UnboundedSource`] unless `max_duration` is given.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use rodio::source::{render_to_wav, SineWave};
+///
+/// let source = SineWave::new(440.0);
+/// render_to_wav(source, "sine.wav", Some(Duration::from_secs(1))).unwrap();
+/// ```
+pub fn render_to_wav<S>(
+    source: S,
+    path: impl AsRef<Path>,
+    max_duration: Option<Duration>,
+) -> Result<(), RenderError>
+where
+    S: Source,
+    S::Item: Sample,
+    i8: FromSample<S::Item>,
+    i16: FromSample<S::Item>,
+    i32: FromSample<S::Item>,
+    f32: FromSample<S::Item>,
+{
+    match max_duration {
+        Some(max_duration) => write_wav(source.take_duration(max_duration), path),
+        None if source.total_duration().is_some() => write_wav(source, path),
+        None => Err(RenderError::UnboundedSource),
+    }
+}
+
+fn write_wav<S>(source: S, path: impl AsRef<Path>) -> Result<(), RenderError>
+where
+    S: Source,
+    S::Item: Sample,
+    i8: FromSample<S::Item>,
+    i16: FromSample<S::Item>,
+    i32: FromSample<S::Item>,
+    f32: FromSample<S::Item>,
+{
+    let (bits_per_sample, sample_format) = match source.sample_format() {
+        SampleFormat::I8 | SampleFormat::U8 => (8, hound::SampleFormat::Int),
+        SampleFormat::I32 | SampleFormat::U32 | SampleFormat::I64 | SampleFormat::U64 => {
+            (32, hound::SampleFormat::Int)
+        }
+        SampleFormat::F32 | SampleFormat::F64 => (32, hound::SampleFormat::Float),
+        _ => (16, hound::SampleFormat::Int),
+    };
+    let spec = hound::WavSpec {
+        channels: source.channels(),
+        sample_rate: source.sample_rate(),
+        bits_per_sample,
+        sample_format,
+    };
+
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    match (bits_per_sample, sample_format) {
+        (8, _) => write_samples::<_, i8>(source, &mut writer)?,
+        (32, hound::SampleFormat::Int) => write_samples::<_, i32>(source, &mut writer)?,
+        (32, hound::SampleFormat::Float) => write_samples::<_, f32>(source, &mut writer)?,
+        _ => write_samples::<_, i16>(source, &mut writer)?,
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+// Writes the samples converted to `D`, which must match the spec of the writer.
+fn write_samples<S, D>(
+    source: S,
+    writer: &mut hound::WavWriter<BufWriter<File>>,
+) -> Result<(), hound::Error>
+where
+    S: Source,
+    S::Item: Sample,
+    D: FromSample<S::Item> + hound::Sample,
+{
+    let spec = writer.spec();
+    let samples = UniformSourceIterator::<_, S::Item>::new(source, spec.channels, spec.sample_rate);
+    for sample in samples {
+        writer.write_sample(D::from_sample_(sample))?;
+    }
+    Ok(())
+}
+
+/// Error that can happen when rendering a source to a file.
+#[derive(Debug)]
+pub enum RenderError {
+    /// The source does not know its total duration and no maximum duration was given, so it
+    /// could play forever.
+    UnboundedSource,
+    /// Creating or writing the WAV file failed.
+    Wav(hound::Error),
+}
+
+impl From<hound::Error> for RenderError {
+    #[inline]
+    fn from(err: hound::Error) -> Self {
+        RenderError::Wav(err)
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::UnboundedSource => {
+                write!(
+                    f,
+                    "The source may be infinite, a maximum duration is required"
+                )
+            }
+            RenderError::Wav(err) => write!(f, "Error writing wav file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderError::UnboundedSource => None,
+            RenderError::Wav(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use std::path::PathBuf;
+
+    use super::{render_to_wav, RenderError};
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{SineWave, Source};
+
+    // A path of its own for each test, and for each run of the tests.
+    fn temp_path(test: &str) -> PathBuf {
+        let name = format!("rodio_render_{test}_{}.wav", std::process::id());
+        std::env::temp_dir().join(name)
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp_path("round_trip");
+        let data = vec![0i16, 1000, -1000, i16::MAX, i16::MIN, 5];
+        render_to_wav(SamplesBuffer::new(2, 22050, data.clone()), &path, None).unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        let written: Vec<i16> = reader.samples::<i16>().map(Result::unwrap).collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(spec.channels, 2);
+        assert_eq!(spec.sample_rate, 22050);
+        assert_eq!(spec.bits_per_sample, 16);
+        assert_eq!(written, data);
+    }
+
+    #[test]
+    fn keeps_float_samples() {
+        let path = temp_path("float");
+        let data = vec![0.0f32, 0.5, -0.25, 1.0];
+        render_to_wav(SamplesBuffer::new(1, 8000, data.clone()), &path, None).unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        let written: Vec<f32> = reader.samples::<f32>().map(Result::unwrap).collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(spec.sample_format, hound::SampleFormat::Float);
+        assert_eq!(spec.bits_per_sample, 32);
+        assert_eq!(written, data);
+    }
+
+    #[test]
+    fn keeps_8_bits_samples() {
+        let path = temp_path("8_bits");
+        let data = vec![0u8, 128, 255, 64];
+        render_to_wav(SamplesBuffer::new(1, 8000, data), &path, None).unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        let written: Vec<i8> = reader.samples::<i8>().map(Result::unwrap).collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(spec.sample_format, hound::SampleFormat::Int);
+        assert_eq!(spec.bits_per_sample, 8);
+        assert_eq!(written, vec![-128, 0, 127, -64]);
+    }
+
+    #[test]
+    fn infinite_source_is_bounded() {
+        let path = temp_path("bounded");
+        let source = SineWave::new(440.0);
+        assert!(source.total_duration().is_none());
+        assert!(matches!(
+            render_to_wav(source.clone(), &path, None),
+            Err(RenderError::UnboundedSource)
+        ));
+
+        render_to_wav(source, &path, Some(Duration::from_millis(100))).unwrap();
+        let reader = hound::WavReader::open(&path).unwrap();
+        let len = reader.len();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(len, 4800);
+    }
+}
@@ -84,6 +84,11 @@ where
         None
     }
 
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
     #[inline]
     fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
         Ok(())
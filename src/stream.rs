@@ -1,6 +1,7 @@
 use std::io::{Read, Seek};
-use std::sync::{Arc, Weak};
-use std::{error, fmt};
+use std::sync::{mpsc, Arc, Weak};
+use std::time::Duration;
+use std::{error, fmt, thread};
 
 use crate::decoder;
 use crate::dynamic_mixer::{self, DynamicMixerController};
@@ -21,6 +22,81 @@ pub struct OutputStream {
 #[derive(Clone)]
 pub struct OutputStreamHandle {
     mixer: Weak<DynamicMixerController<f32>>,
+    latency: Duration,
+}
+
+/// Options for opening an `OutputStream`, on top of the device's stream config.
+///
+/// The buffer size sets how many frames the device asks for in one go. Smaller buffers
+/// make changes to the mix heard sooner but wake the audio thread more often and are more
+/// likely to underflow on a busy system. Larger buffers save power at the cost of latency.
+///
+/// The latency is how long the device might take to play the samples it already pulled
+/// from the mixer. [`OutputStreamHandle::play_blocking`] waits that long after the source
+/// is done, so it should cover the buffers of the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputStreamOptions {
+    buffer_size: cpal::BufferSize,
+    latency: Duration,
+}
+
+impl OutputStreamOptions {
+    /// The options `OutputStream` uses unless told otherwise: the device's default buffer
+    /// size and a latency of 100 milliseconds, which covers the buffers of the usual
+    /// backends.
+    #[inline]
+    pub fn new() -> OutputStreamOptions {
+        OutputStreamOptions {
+            buffer_size: cpal::BufferSize::Default,
+            latency: Duration::from_millis(100),
+        }
+    }
+
+    /// Sets the buffer size to ask the device for.
+    ///
+    /// # Panics
+    ///
+    /// If the buffer size is `BufferSize::Fixed(0)`.
+    #[inline]
+    pub fn with_buffer_size(mut self, buffer_size: cpal::BufferSize) -> OutputStreamOptions {
+        assert!(
+            buffer_size != cpal::BufferSize::Fixed(0),
+            "the buffer size must hold at least one frame"
+        );
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Sets how long the device might take to play the samples it already pulled.
+    ///
+    /// # Panics
+    ///
+    /// If the latency is zero.
+    #[inline]
+    pub fn with_latency(mut self, latency: Duration) -> OutputStreamOptions {
+        assert!(!latency.is_zero(), "the latency must not be zero");
+        self.latency = latency;
+        self
+    }
+
+    /// Returns the buffer size to ask the device for.
+    #[inline]
+    pub fn buffer_size(&self) -> cpal::BufferSize {
+        self.buffer_size
+    }
+
+    /// Returns how long the device might take to play the samples it already pulled.
+    #[inline]
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+}
+
+impl Default for OutputStreamOptions {
+    #[inline]
+    fn default() -> OutputStreamOptions {
+        OutputStreamOptions::new()
+    }
 }
 
 impl OutputStream {
@@ -43,11 +119,40 @@ impl OutputStream {
         device: &cpal::Device,
         config: SupportedStreamConfig,
     ) -> Result<(Self, OutputStreamHandle), StreamError> {
-        let (mixer, _stream) = device.try_new_output_stream_config(config)?;
+        OutputStream::try_from_device_config_with_options(
+            device,
+            config,
+            OutputStreamOptions::default(),
+        )
+    }
+
+    /// Returns a new stream & handle using the given output device, the default output
+    /// configuration and the given options.
+    pub fn try_from_device_with_options(
+        device: &cpal::Device,
+        options: OutputStreamOptions,
+    ) -> Result<(Self, OutputStreamHandle), StreamError> {
+        let default_config = device
+            .default_output_config()
+            .map_err(StreamError::DefaultStreamConfigError)?;
+        OutputStream::try_from_device_config_with_options(device, default_config, options)
+    }
+
+    /// Returns a new stream & handle using the given device, stream config and options.
+    ///
+    /// Fails like [`try_from_device_config`](OutputStream::try_from_device_config), also
+    /// when the device does not support the buffer size of the options.
+    pub fn try_from_device_config_with_options(
+        device: &cpal::Device,
+        config: SupportedStreamConfig,
+        options: OutputStreamOptions,
+    ) -> Result<(Self, OutputStreamHandle), StreamError> {
+        let (mixer, _stream) = device.try_new_output_stream_config(config, options.buffer_size)?;
         _stream.play().map_err(StreamError::PlayStreamError)?;
         let out = Self { mixer, _stream };
         let handle = OutputStreamHandle {
             mixer: Arc::downgrade(&out.mixer),
+            latency: options.latency,
         };
         Ok((out, handle))
     }
@@ -56,11 +161,20 @@ impl OutputStream {
     ///
     /// On failure will fallback to trying any non-default output devices.
     pub fn try_default() -> Result<(Self, OutputStreamHandle), StreamError> {
+        Self::try_default_with_options(OutputStreamOptions::default())
+    }
+
+    /// Return a new stream & handle using the default output device and the given options.
+    ///
+    /// On failure will fallback to trying any non-default output devices.
+    pub fn try_default_with_options(
+        options: OutputStreamOptions,
+    ) -> Result<(Self, OutputStreamHandle), StreamError> {
         let default_device = cpal::default_host()
             .default_output_device()
             .ok_or(StreamError::NoDevice)?;
 
-        let default_stream = Self::try_from_device(&default_device);
+        let default_stream = Self::try_from_device_with_options(&default_device, options);
 
         default_stream.or_else(|original_err| {
             // default device didn't work, try other ones
@@ -70,10 +184,47 @@ impl OutputStream {
             };
 
             devices
-                .find_map(|d| Self::try_from_device(&d).ok())
+                .find_map(|d| Self::try_from_device_with_options(&d, options).ok())
                 .ok_or(original_err)
         })
     }
+
+    /// Plays a source on the default output device, in the first format the device supports.
+    ///
+    /// Opens the stream like [`try_default`](OutputStream::try_default), then plays the
+    /// source like [`play_raw`](OutputStreamHandle::play_raw). The source plays for as long
+    /// as the returned `OutputStream` is kept. Returns [`StreamError::NoDevice`] if the
+    /// system has no output device.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use rodio::source::{SineWave, Source};
+    /// use rodio::OutputStream;
+    /// use std::time::Duration;
+    ///
+    /// let source = SineWave::new(440.0).take_duration(Duration::from_secs(1));
+    /// let _stream = OutputStream::play_default(source).unwrap();
+    /// std::thread::sleep(Duration::from_secs(1));
+    /// ```
+    pub fn play_default<S>(source: S) -> Result<Self, StreamError>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let (stream, _handle) = Self::try_default()?;
+        stream.mixer.add(source);
+        Ok(stream)
+    }
+
+    /// Returns the mixer that feeds the device.
+    ///
+    /// Sources added to it play on the device for as long as the `OutputStream` lives, and can
+    /// be controlled through the [`SourceHandle`](crate::dynamic_mixer::SourceHandle) it
+    /// returns. It mixes in the format of the device.
+    #[inline]
+    pub fn mixer(&self) -> &Arc<DynamicMixerController<f32>> {
+        &self.mixer
+    }
 }
 
 impl OutputStreamHandle {
@@ -87,6 +238,47 @@ impl OutputStreamHandle {
         Ok(())
     }
 
+    /// Plays a source with a device and blocks the current thread until it has been heard.
+    ///
+    /// The thread does not poll while the source plays: it waits for the mixer to finish the
+    /// source, then sleeps for as long as the device needs to play the end of it, based on
+    /// the samples the device pulled since, see [`OutputStreamOptions::with_latency`]. Meant for tools that play a sound and exit, the
+    /// `OutputStream` can be dropped as soon as this returns.
+    ///
+    /// Returns [`PlayError::NoDevice`] if the `OutputStream` is dropped before the source
+    /// ends.
+    pub fn play_blocking<S>(&self, source: S) -> Result<(), PlayError>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        let (done_tx, done_rx) = mpsc::channel();
+        mixer.add(source).on_done(move || {
+            let _ = done_tx.send(());
+        });
+        drop(mixer);
+        // The callback, along with the sender, is dropped unused when the stream is dropped.
+        done_rx.recv().map_err(|_| PlayError::NoDevice)?;
+
+        // The device pulls a sample from the mixer for every sample it plays, silent or not.
+        let Some(mixer) = self.mixer.upgrade() else {
+            return Ok(());
+        };
+        let (channels, sample_rate) = mixer.output_format();
+        let samples_per_sec = channels as f64 * sample_rate as f64;
+        let end = mixer.sample_count() + (self.latency.as_secs_f64() * samples_per_sec) as usize;
+        drop(mixer);
+
+        while let Some(played) = self.mixer.upgrade().map(|mixer| mixer.sample_count()) {
+            if played >= end {
+                break;
+            }
+            let left = (end - played) as f64 / samples_per_sec;
+            thread::sleep(Duration::from_secs_f64(left));
+        }
+        Ok(())
+    }
+
     /// Plays a sound once. Returns a `Sink` that can be used to control the sound.
     pub fn play_once<R>(&self, input: R) -> Result<Sink, PlayError>
     where
@@ -179,11 +371,13 @@ pub(crate) trait CpalDeviceExt {
     fn new_output_stream_with_format(
         &self,
         format: cpal::SupportedStreamConfig,
+        buffer_size: cpal::BufferSize,
     ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), cpal::BuildStreamError>;
 
     fn try_new_output_stream_config(
         &self,
         config: cpal::SupportedStreamConfig,
+        buffer_size: cpal::BufferSize,
     ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), StreamError>;
 }
 
@@ -191,9 +385,14 @@ impl CpalDeviceExt for cpal::Device {
     fn new_output_stream_with_format(
         &self,
         format: cpal::SupportedStreamConfig,
+        buffer_size: cpal::BufferSize,
     ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), cpal::BuildStreamError> {
         let (mixer_tx, mut mixer_rx) =
             dynamic_mixer::mixer::<f32>(format.channels(), format.sample_rate().0);
+        let config = cpal::StreamConfig {
+            buffer_size,
+            ..format.config()
+        };
 
         let error_callback = |err| {
             #[cfg(feature = "tracing")]
@@ -204,7 +403,7 @@ impl CpalDeviceExt for cpal::Device {
 
         match format.sample_format() {
             cpal::SampleFormat::F32 => self.build_output_stream::<f32, _, _>(
-                &format.config(),
+                &config,
                 move |data, _| {
                     data.iter_mut()
                         .for_each(|d| *d = mixer_rx.next().unwrap_or(0f32))
@@ -213,7 +412,7 @@ impl CpalDeviceExt for cpal::Device {
                 None,
             ),
             cpal::SampleFormat::F64 => self.build_output_stream::<f64, _, _>(
-                &format.config(),
+                &config,
                 move |data, _| {
                     data.iter_mut()
                         .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0f64))
@@ -222,7 +421,7 @@ impl CpalDeviceExt for cpal::Device {
                 None,
             ),
             cpal::SampleFormat::I8 => self.build_output_stream::<i8, _, _>(
-                &format.config(),
+                &config,
                 move |data, _| {
                     data.iter_mut()
                         .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i8))
@@ -231,7 +430,7 @@ impl CpalDeviceExt for cpal::Device {
                 None,
             ),
             cpal::SampleFormat::I16 => self.build_output_stream::<i16, _, _>(
-                &format.config(),
+                &config,
                 move |data, _| {
                     data.iter_mut()
                         .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i16))
@@ -240,7 +439,7 @@ impl CpalDeviceExt for cpal::Device {
                 None,
             ),
             cpal::SampleFormat::I32 => self.build_output_stream::<i32, _, _>(
-                &format.config(),
+                &config,
                 move |data, _| {
                     data.iter_mut()
                         .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i32))
@@ -249,7 +448,7 @@ impl CpalDeviceExt for cpal::Device {
                 None,
             ),
             cpal::SampleFormat::I64 => self.build_output_stream::<i64, _, _>(
-                &format.config(),
+                &config,
                 move |data, _| {
                     data.iter_mut()
                         .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i64))
@@ -258,7 +457,7 @@ impl CpalDeviceExt for cpal::Device {
                 None,
             ),
             cpal::SampleFormat::U8 => self.build_output_stream::<u8, _, _>(
-                &format.config(),
+                &config,
                 move |data, _| {
                     data.iter_mut().for_each(|d| {
                         *d = mixer_rx
@@ -271,7 +470,7 @@ impl CpalDeviceExt for cpal::Device {
                 None,
             ),
             cpal::SampleFormat::U16 => self.build_output_stream::<u16, _, _>(
-                &format.config(),
+                &config,
                 move |data, _| {
                     data.iter_mut().for_each(|d| {
                         *d = mixer_rx
@@ -284,7 +483,7 @@ impl CpalDeviceExt for cpal::Device {
                 None,
             ),
             cpal::SampleFormat::U32 => self.build_output_stream::<u32, _, _>(
-                &format.config(),
+                &config,
                 move |data, _| {
                     data.iter_mut().for_each(|d| {
                         *d = mixer_rx
@@ -297,7 +496,7 @@ impl CpalDeviceExt for cpal::Device {
                 None,
             ),
             cpal::SampleFormat::U64 => self.build_output_stream::<u64, _, _>(
-                &format.config(),
+                &config,
                 move |data, _| {
                     data.iter_mut().for_each(|d| {
                         *d = mixer_rx
@@ -317,14 +516,16 @@ impl CpalDeviceExt for cpal::Device {
     fn try_new_output_stream_config(
         &self,
         config: SupportedStreamConfig,
+        buffer_size: cpal::BufferSize,
     ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), StreamError> {
-        self.new_output_stream_with_format(config).or_else(|err| {
-            // look through all supported formats to see if another works
-            supported_output_formats(self)?
-                .find_map(|format| self.new_output_stream_with_format(format).ok())
+        self.new_output_stream_with_format(config, buffer_size)
+            .or_else(|err| {
+                // look through all supported formats to see if another works
+                supported_output_formats(self)?
+                .find_map(|format| self.new_output_stream_with_format(format, buffer_size).ok())
                 // return original error if nothing works
                 .ok_or(StreamError::BuildStreamError(err))
-        })
+            })
     }
 }
 
@@ -351,3 +552,37 @@ fn supported_output_formats(
         formats
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::OutputStreamOptions;
+    use std::time::Duration;
+
+    #[test]
+    fn default_options() {
+        let options = OutputStreamOptions::default();
+        assert_eq!(options.buffer_size(), cpal::BufferSize::Default);
+        assert_eq!(options.latency(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn set_options() {
+        let options = OutputStreamOptions::new()
+            .with_buffer_size(cpal::BufferSize::Fixed(256))
+            .with_latency(Duration::from_millis(20));
+        assert_eq!(options.buffer_size(), cpal::BufferSize::Fixed(256));
+        assert_eq!(options.latency(), Duration::from_millis(20));
+    }
+
+    #[test]
+    #[should_panic(expected = "the buffer size must hold at least one frame")]
+    fn zero_buffer_size() {
+        OutputStreamOptions::new().with_buffer_size(cpal::BufferSize::Fixed(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "the latency must not be zero")]
+    fn zero_latency() {
+        OutputStreamOptions::new().with_latency(Duration::ZERO);
+    }
+}
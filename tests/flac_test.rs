@@ -24,3 +24,16 @@ fn test_flac_encodings() {
         assert_eq!(decoder.total_duration(), Some(Duration::from_secs(3)));
     }
 }
+
+#[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+#[test]
+fn test_flac_samples_remaining() {
+    let file = std::fs::File::open("assets/audacity16bit_level5.flac").unwrap();
+    let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let total = 3 * decoder.sample_rate() as usize * decoder.channels() as usize;
+    assert_eq!(decoder.samples_remaining(), Some(total));
+
+    decoder.next();
+    assert_eq!(decoder.samples_remaining(), Some(total - 1));
+    assert_eq!(decoder.count(), total - 1);
+}
@@ -0,0 +1,42 @@
+use std::io::BufReader;
+
+#[cfg(any(feature = "flac", feature = "symphonia-flac"))]
+#[test]
+fn flac_tags() {
+    let file = std::fs::File::open("assets/music.flac").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let metadata = decoder.metadata();
+    assert_eq!(metadata.title(), Some("Corelli Trio Sonata 11, m1"));
+    assert_eq!(metadata.artist(), Some("RP and E Goldstein"));
+}
+
+// note: not with symphonia-vorbis, broken decoder see issue: #516 and #539
+#[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+#[test]
+fn vorbis_tags() {
+    let file = std::fs::File::open("assets/music.ogg").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let metadata = decoder.metadata();
+    assert_eq!(metadata.title(), Some("Corelli Trio Sonata 11, m1"));
+    assert_eq!(metadata.get("artist"), Some("RP and E Goldstein"));
+    // the file has an empty album tag
+    assert_eq!(metadata.album(), None);
+}
+
+#[cfg(feature = "symphonia-mp3")]
+#[test]
+fn id3_tags() {
+    let file = std::fs::File::open("assets/music.mp3").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let metadata = decoder.metadata();
+    assert!(metadata.title().is_some());
+    assert!(metadata.artist().is_some());
+}
+
+#[cfg(feature = "wav")]
+#[test]
+fn wav_has_no_tags() {
+    let file = std::fs::File::open("assets/music.wav").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    assert!(decoder.metadata().tags().is_empty());
+}
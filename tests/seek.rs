@@ -131,31 +131,24 @@ fn seek_does_not_break_channel_order(
 
     let mut source = get_rl(format).convert_samples();
 
-    let mut channel_offset = 0;
     for offset in [1, 4, 7, 40, 41, 120, 179]
         .map(|offset| offset as f32 / (source.sample_rate() as f32))
         .map(Duration::from_secs_f32)
     {
         source.next(); // WINDOW is even, make the amount of calls to next
-                       // uneven to force issues with channels alternating
-                       // between seek to surface
-        channel_offset = (channel_offset + 1) % 2;
+                       // uneven so that the seek starts in the middle of a frame
 
         source.try_seek(beep_start + offset).unwrap();
         let samples: Vec<_> = source.by_ref().take(100).collect();
-        let channel0 = 0 + channel_offset;
         assert!(
-            is_silent(&samples, source.channels(), channel0),
+            is_silent(&samples, source.channels(), 0),
             "channel0 should be silent, 
-    channel0 starts at idx: {channel0}
     seek: {beep_start:?} + {offset:?}
     samples: {samples:?}"
         );
-        let channel1 = (1 + channel_offset) % 2;
         assert!(
-            !is_silent(&samples, source.channels(), channel1),
+            !is_silent(&samples, source.channels(), 1),
             "channel1 should not be silent, 
-    channel1; starts at idx: {channel1}
     seek: {beep_start:?} + {offset:?}
     samples: {samples:?}"
         );
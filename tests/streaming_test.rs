@@ -0,0 +1,56 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::time::Duration;
+
+use rodio::decoder::decode_streaming;
+use rodio::source::SeekError;
+use rodio::Source;
+
+/// A file that can only be read forward, like a network stream.
+struct Unseekable(File);
+
+impl Read for Unseekable {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+fn open(path: &str) -> Unseekable {
+    Unseekable(File::open(path).unwrap())
+}
+
+#[cfg(any(feature = "minimp3", feature = "symphonia-mp3"))]
+#[test]
+fn streaming_mp3() {
+    let mut decoder = decode_streaming(open("assets/RL.mp3")).unwrap();
+    assert_eq!(decoder.channels(), 2);
+    assert!(decoder.any(|x| x != 0));
+}
+
+// note: not with symphonia-vorbis, broken decoder see issue: #516 and #539
+#[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+#[test]
+fn streaming_ogg() {
+    let mut decoder = decode_streaming(open("assets/RL.ogg")).unwrap();
+    assert_eq!(decoder.channels(), 2);
+    assert!(decoder.any(|x| x != 0));
+}
+
+#[cfg(feature = "wav")]
+#[test]
+fn streaming_ends_and_can_not_seek() {
+    let mut decoder = decode_streaming(open("assets/RL.wav")).unwrap();
+    assert!(matches!(
+        decoder.try_seek(Duration::from_secs(1)),
+        Err(SeekError::NotSupported { .. })
+    ));
+    // the silence played while the decoder catches up comes in whole frames
+    assert_eq!(decoder.by_ref().count() % 2, 0);
+    assert_eq!(decoder.next(), None);
+}
+
+#[test]
+fn streaming_unknown_format() {
+    let data: &[u8] = &[0; 1024];
+    assert!(decode_streaming(io::Cursor::new(data.to_vec())).is_err());
+}
@@ -1,4 +1,8 @@
 use std::io::BufReader;
+use std::time::Duration;
+
+use rodio::source::{SeekDirection, UniformSourceIterator};
+use rodio::{SampleFormat, Source};
 
 #[test]
 fn test_wav_encodings() {
@@ -32,3 +36,96 @@ fn test_wav_encodings() {
     let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
     assert!(decoder.any(|x| x != 0));
 }
+
+#[test]
+fn test_wav_sample_format() {
+    let formats = [
+        ("assets/audacity16bit.wav", SampleFormat::I16),
+        ("assets/lmms24bit.wav", SampleFormat::I32),
+        ("assets/audacity32bit.wav", SampleFormat::F32),
+        ("assets/audacity32bit_int.wav", SampleFormat::I32),
+    ];
+    for (path, format) in formats {
+        let file = std::fs::File::open(path).unwrap();
+        let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+        assert_eq!(decoder.sample_format(), format, "{path}");
+    }
+}
+
+#[test]
+fn test_wav_sample_values() {
+    // Every file holds 800 stereo frames at 8 kHz of a left sample at half the
+    // full scale and a right one at minus a quarter.
+    let paths = [
+        "assets/stereo24bit.wav",
+        "assets/stereo32bit_int.wav",
+        "assets/stereo32bit_float.wav",
+    ];
+    for path in paths {
+        let file = std::fs::File::open(path).unwrap();
+        let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+        assert_eq!(decoder.channels(), 2, "{path}");
+        assert_eq!(decoder.sample_rate(), 8000, "{path}");
+        assert_eq!(
+            decoder.total_duration(),
+            Some(Duration::from_millis(100)),
+            "{path}"
+        );
+
+        assert_eq!(decoder.samples_remaining(), Some(1600), "{path}");
+        assert!(decoder.is_seekable(), "{path}");
+
+        let samples: Vec<i16> = decoder.collect();
+        assert_eq!(samples.len(), 1600, "{path}");
+        assert_eq!(&samples[..4], &[16384, -8192, 16384, -8192], "{path}");
+    }
+}
+
+#[test]
+fn test_wav_sample_format_through_wrappers() {
+    let file = std::fs::File::open("assets/audacity32bit_int.wav").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    // the wrappers that keep the samples keep the format of the file
+    let source = decoder
+        .amplify(0.5)
+        .take_duration(Duration::from_secs(1))
+        .buffered()
+        .repeat_infinite();
+    assert_eq!(source.sample_format(), SampleFormat::I32);
+
+    // the ones that convert the samples report the type they convert to
+    let source = source.convert_samples::<f32>().low_pass(1000);
+    assert_eq!(source.sample_format(), SampleFormat::F32);
+    let source: UniformSourceIterator<_, i16> = UniformSourceIterator::new(source, 2, 8000);
+    assert_eq!(source.sample_format(), SampleFormat::I16);
+}
+
+#[test]
+fn test_wav_seek_relative() {
+    let open = || {
+        let file = std::fs::File::open("assets/music.wav").unwrap();
+        rodio::Decoder::new(BufReader::new(file))
+            .unwrap()
+            .amplify(0.5)
+            .pausable(false)
+    };
+    let mut source = open();
+    let frame = source.channels() as usize;
+    let rate = source.sample_rate() as usize;
+    assert_eq!(source.playback_pos(), Some(Duration::ZERO));
+
+    source.by_ref().take(frame * rate / 2).count();
+    assert_eq!(source.playback_pos(), Some(Duration::from_millis(500)));
+
+    source
+        .try_seek_relative(Duration::from_secs(1), SeekDirection::Forward)
+        .unwrap();
+    assert_eq!(source.playback_pos(), Some(Duration::from_millis(1500)));
+    let expected: Vec<i16> = open().skip(frame * rate * 3 / 2).take(100).collect();
+    assert_eq!(source.by_ref().take(100).collect::<Vec<_>>(), expected);
+
+    source
+        .try_seek_relative(Duration::from_secs(5), SeekDirection::Backward)
+        .unwrap();
+    assert_eq!(source.playback_pos(), Some(Duration::ZERO));
+}